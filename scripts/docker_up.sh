@@ -9,7 +9,7 @@
 # - Validates required env vars from .env
 # - Runs a Solana preflight (RPC connectivity, payer balance, program exists, PDA exists)
 #   - Optional: initialize the PDA if missing
-# - Builds + runs the API service container (mounts your Solana keypair + trusted_state.json)
+# - Builds + runs the API service container (mounts your Solana keypair + trusted_state.json + sealing.key)
 #
 # Usage:
 #   ./scripts/docker_up.sh
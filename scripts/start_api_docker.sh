@@ -100,8 +100,10 @@ else
   exit 1
 fi
 
-# Persist trusted_state.json on host (bind mount)
+# Persist trusted_state.json and its sealing key on host (bind mounts)
 touch "${PROJECT_ROOT}/trusted_state.json"
+touch "${PROJECT_ROOT}/sealing.key"
+chmod 600 "${PROJECT_ROOT}/sealing.key"
 
 echo ""
 cd "${PROJECT_ROOT}"
@@ -141,22 +143,44 @@ esac
 echo ""
 echo "Starting API container '${CONTAINER_NAME}' on port ${API_PORT}..."
 
-# Replace running container
+# Seconds Docker waits after SIGTERM before SIGKILL. Must cover the write drain
+# (SHUTDOWN_DRAIN_SECS, default 30) plus the final anchor transaction.
+STOP_TIMEOUT_SECS="${STOP_TIMEOUT_SECS:-60}"
+
+# Replace running container (stop first so it drains writes and anchors its pending root)
 if docker ps -a --format "{{.Names}}" | grep -qx "${CONTAINER_NAME}"; then
+  docker stop -t "${STOP_TIMEOUT_SECS}" "${CONTAINER_NAME}" >/dev/null || true
   docker rm -f "${CONTAINER_NAME}" >/dev/null
 fi
 
 DOCKER_RUN_ARGS=(
   -d
   --name "${CONTAINER_NAME}"
+  --stop-timeout "${STOP_TIMEOUT_SECS}"
   -e "DATABASE_URL=${DATABASE_URL_DOCKER}"
   -e "SOLANA_RPC_URL=${SOLANA_RPC_URL}"
   -e "SOLANA_PROGRAM_ID=${SOLANA_PROGRAM_ID}"
   -e "BATCH_COMMIT_SIZE=${BATCH_COMMIT_SIZE}"
+  -e "SOLANA_NAMESPACE=${SOLANA_NAMESPACE:-default}"
+  -e "SOLANA_SIGNER=${SOLANA_SIGNER:-file}"
   -v "${KEYPAIR_HOST_PATH}:/home/appuser/.config/solana/id.json:ro"
   -v "${PROJECT_ROOT}/trusted_state.json:/app/trusted_state.json"
+  -v "${PROJECT_ROOT}/sealing.key:/app/sealing.key"
 )
 
+case "${SOLANA_SIGNER:-file}" in
+  env)
+    # Passed by name so the secret does not show up in the docker command line
+    DOCKER_RUN_ARGS+=(-e SOLANA_SIGNER_SECRET)
+    ;;
+  remote)
+    DOCKER_RUN_ARGS+=(
+      -e "SOLANA_SIGNER_SOCKET=/run/solana-signer.sock"
+      -v "${SOLANA_SIGNER_SOCKET}:/run/solana-signer.sock"
+    )
+    ;;
+esac
+
 if [[ "${NETWORK_MODE}" == "bridge" ]]; then
   DOCKER_RUN_ARGS+=(--network "${DOCKER_NETWORK}" -p "${API_PORT}:3000")
 else
@@ -3,49 +3,257 @@ use anchor_lang::prelude::*;
 
 declare_id!("6fSQZwqdsr8zVSbE8DTo4tsHDW4af3iZyB5KGzEGqyW8");
 
+/// Number of past roots kept in the `root_history` ring buffer.
+pub const ROOT_HISTORY_LEN: usize = 64;
+
+/// Longest namespace accepted (a PDA seed is at most 32 bytes).
+pub const MAX_NAMESPACE_LEN: usize = 32;
+
+// Every instruction takes the namespace first: it selects the database's accounts, whose PDAs
+// are derived from `[b"merkle_root_account", namespace]` and `[b"root_history", namespace]`.
+// One deployed program thereby anchors any number of databases.
+
 #[program]
 pub mod verifiable_db_program {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>, initial_root: [u8; 32]) -> Result<()> {
+    /// Creates the root account and its history for `namespace`. The signer becomes the
+    /// authority.
+    pub fn initialize(ctx: Context<Initialize>, namespace: String, initial_root: [u8; 32]) -> Result<()> {
+        require!(
+            !namespace.is_empty() && namespace.len() <= MAX_NAMESPACE_LEN,
+            VerifiableDbError::InvalidNamespace
+        );
+        let timestamp = Clock::get()?.unix_timestamp;
+        ctx.accounts
+            .root_history
+            .load_init()?
+            .push(0, initial_root, timestamp);
+
         let merkle_root_account = &mut ctx.accounts.merkle_root_account;
         merkle_root_account.root = initial_root;
-        merkle_root_account.timestamp = Clock::get()?.unix_timestamp;
+        merkle_root_account.timestamp = timestamp;
+        merkle_root_account.sequence = 0;
+        merkle_root_account.authority = ctx.accounts.user.key();
+        merkle_root_account.pending_authority = None;
         Ok(())
     }
 
-    pub fn update_root(ctx: Context<UpdateRoot>, new_root: [u8; 32]) -> Result<()> {
+    /// Replaces the root and appends it to the history. Only the authority may call this, and
+    /// only on top of the state it expects: `expected_prev_root` must be the stored root and
+    /// `sequence` the next sequence number. A delayed or replayed transaction built on older
+    /// state is rejected instead of rolling the root back.
+    ///
+    /// Emits [`RootUpdated`], so the full timeline can be rebuilt from the program's
+    /// transaction logs (the history account only keeps the last `ROOT_HISTORY_LEN` roots).
+    pub fn update_root(
+        ctx: Context<UpdateRoot>,
+        namespace: String,
+        new_root: [u8; 32],
+        expected_prev_root: [u8; 32],
+        sequence: u64,
+    ) -> Result<()> {
         let merkle_root_account = &mut ctx.accounts.merkle_root_account;
+        require!(
+            merkle_root_account.root == expected_prev_root,
+            VerifiableDbError::StaleRoot
+        );
+        require!(
+            merkle_root_account.sequence.checked_add(1) == Some(sequence),
+            VerifiableDbError::StaleSequence
+        );
+        let timestamp = Clock::get()?.unix_timestamp;
         merkle_root_account.root = new_root;
-        merkle_root_account.timestamp = Clock::get()?.unix_timestamp;
+        merkle_root_account.timestamp = timestamp;
+        merkle_root_account.sequence = sequence;
+        ctx.accounts
+            .root_history
+            .load_mut()?
+            .push(sequence, new_root, timestamp);
+        emit!(RootUpdated {
+            namespace,
+            sequence,
+            old_root: expected_prev_root,
+            new_root,
+            timestamp,
+        });
+        Ok(())
+    }
+
+    /// Hands the authority to `new_authority` in one step. The new key does not have to sign,
+    /// so a typo locks the account; prefer `propose_authority` + `accept_authority`.
+    pub fn set_authority(ctx: Context<AuthorityOnly>, _namespace: String, new_authority: Pubkey) -> Result<()> {
+        let merkle_root_account = &mut ctx.accounts.merkle_root_account;
+        merkle_root_account.authority = new_authority;
+        merkle_root_account.pending_authority = None;
+        Ok(())
+    }
+
+    /// First step of a transfer: records `new_authority` as pending. Proposing the current
+    /// authority cancels an outstanding proposal.
+    pub fn propose_authority(
+        ctx: Context<AuthorityOnly>,
+        _namespace: String,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let merkle_root_account = &mut ctx.accounts.merkle_root_account;
+        merkle_root_account.pending_authority = if new_authority == merkle_root_account.authority {
+            None
+        } else {
+            Some(new_authority)
+        };
+        Ok(())
+    }
+
+    /// Second step of a transfer: the pending authority signs to take over.
+    pub fn accept_authority(ctx: Context<AcceptAuthority>, _namespace: String) -> Result<()> {
+        let merkle_root_account = &mut ctx.accounts.merkle_root_account;
+        match merkle_root_account.pending_authority {
+            None => return err!(VerifiableDbError::NoPendingAuthority),
+            Some(pending) if pending != ctx.accounts.new_authority.key() => {
+                return err!(VerifiableDbError::NotPendingAuthority)
+            }
+            Some(pending) => merkle_root_account.authority = pending,
+        }
+        merkle_root_account.pending_authority = None;
         Ok(())
     }
 }
 
 #[derive(Accounts)]
+#[instruction(namespace: String)]
 pub struct Initialize<'info> {
+    // `init` (not `init_if_needed`): re-running initialize must not reset the authority.
     #[account(
-        init_if_needed,
+        init,
         payer = user,
-        space = 8 + 32 + 8,
-        seeds = [b"merkle_root_account"],
+        space = MerkleRootAccount::SPACE,
+        seeds = [b"merkle_root_account", namespace.as_bytes()],
         bump
     )]
     pub merkle_root_account: Account<'info, MerkleRootAccount>,
+    #[account(
+        init,
+        payer = user,
+        space = RootHistory::SPACE,
+        seeds = [b"root_history", namespace.as_bytes()],
+        bump
+    )]
+    pub root_history: AccountLoader<'info, RootHistory>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
+#[instruction(namespace: String)]
 pub struct UpdateRoot<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"merkle_root_account", namespace.as_bytes()],
+        bump,
+        has_one = authority @ VerifiableDbError::Unauthorized
+    )]
     pub merkle_root_account: Account<'info, MerkleRootAccount>,
-    pub user: Signer<'info>,
+    #[account(mut, seeds = [b"root_history", namespace.as_bytes()], bump)]
+    pub root_history: AccountLoader<'info, RootHistory>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(namespace: String)]
+pub struct AuthorityOnly<'info> {
+    #[account(
+        mut,
+        seeds = [b"merkle_root_account", namespace.as_bytes()],
+        bump,
+        has_one = authority @ VerifiableDbError::Unauthorized
+    )]
+    pub merkle_root_account: Account<'info, MerkleRootAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(namespace: String)]
+pub struct AcceptAuthority<'info> {
+    #[account(mut, seeds = [b"merkle_root_account", namespace.as_bytes()], bump)]
+    pub merkle_root_account: Account<'info, MerkleRootAccount>,
+    pub new_authority: Signer<'info>,
 }
 
 #[account]
 pub struct MerkleRootAccount {
     pub root: [u8; 32],
     pub timestamp: i64,
+    /// Number of `update_root` calls applied so far.
+    pub sequence: u64,
+    /// The only key allowed to update the root or change the authority.
+    pub authority: Pubkey,
+    /// Set by `propose_authority`, cleared once accepted or replaced.
+    pub pending_authority: Option<Pubkey>,
+}
+
+impl MerkleRootAccount {
+    /// Discriminator + root + timestamp + sequence + authority + pending authority.
+    pub const SPACE: usize = 8 + 32 + 8 + 8 + 32 + (1 + 32);
+}
+
+/// The last `ROOT_HISTORY_LEN` roots, so a verifier can check that a root was anchored with
+/// one account read. Zero-copy: too large to deserialize onto the stack.
+#[account(zero_copy)]
+pub struct RootHistory {
+    /// Slot the next entry is written to.
+    pub head: u64,
+    /// Slots in use (stops growing at `ROOT_HISTORY_LEN`).
+    pub len: u64,
+    pub entries: [RootHistoryEntry; ROOT_HISTORY_LEN],
+}
+
+#[zero_copy]
+pub struct RootHistoryEntry {
+    pub sequence: u64,
+    pub root: [u8; 32],
+    pub timestamp: i64,
+}
+
+impl RootHistory {
+    /// Discriminator + head + len + entries.
+    pub const SPACE: usize = 8 + std::mem::size_of::<RootHistory>();
+
+    /// Records a root, overwriting the oldest entry once the buffer is full.
+    pub fn push(&mut self, sequence: u64, root: [u8; 32], timestamp: i64) {
+        self.entries[self.head as usize] = RootHistoryEntry {
+            sequence,
+            root,
+            timestamp,
+        };
+        self.head = (self.head + 1) % ROOT_HISTORY_LEN as u64;
+        self.len = (self.len + 1).min(ROOT_HISTORY_LEN as u64);
+    }
+}
+
+/// Logged by every `update_root` (as `Program data:`), one per applied update.
+#[event]
+pub struct RootUpdated {
+    pub namespace: String,
+    pub sequence: u64,
+    pub old_root: [u8; 32],
+    pub new_root: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum VerifiableDbError {
+    #[msg("Signer is not the root account's authority")]
+    Unauthorized,
+    #[msg("No authority transfer is pending")]
+    NoPendingAuthority,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Stored root does not match expected_prev_root")]
+    StaleRoot,
+    #[msg("Sequence is not the next update sequence")]
+    StaleSequence,
+    #[msg("Namespace must be 1 to 32 bytes")]
+    InvalidNamespace,
 }
@@ -1,16 +1,200 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
 import { VerifiableDbProgram } from "../target/types/verifiable_db_program";
 
 describe("verifiable_db_program", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.verifiableDbProgram as Program<VerifiableDbProgram>;
+  // Each database gets its own accounts, derived from its namespace.
+  const pdas = (namespace: string) => ({
+    merkleRootAccount: anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("merkle_root_account"), Buffer.from(namespace)],
+      program.programId
+    )[0],
+    rootHistory: anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("root_history"), Buffer.from(namespace)],
+      program.programId
+    )[0],
+  });
+  const namespace = "fleet-a";
+  const { merkleRootAccount, rootHistory } = pdas(namespace);
+  const wallet = provider.wallet.publicKey;
+  const newAuthority = anchor.web3.Keypair.generate();
+
+  // Builds on the stored root and sequence. Fees are paid by the provider wallet, so other
+  // signers need no lamports.
+  const updateRoot = async (root: number[], authority: anchor.web3.Keypair | null) => {
+    const current = await program.account.merkleRootAccount.fetch(merkleRootAccount);
+    const call = program.methods
+      .updateRoot(namespace, root, current.root, current.sequence.addn(1))
+      .accountsPartial({ merkleRootAccount, authority: authority ? authority.publicKey : wallet });
+    return authority ? call.signers([authority]).rpc() : call.rpc();
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+      assert.fail(`expected ${code}`);
+    } catch (e) {
+      assert.equal(e.error?.errorCode?.code, code, String(e));
+    }
+  };
 
   it("Is initialized!", async () => {
     const initialRoot = new Array(32).fill(0); // [u8;32]
-    const tx = await program.methods.initialize(initialRoot).rpc();
+    const tx = await program.methods.initialize(namespace, initialRoot).rpc();
     console.log("Your transaction signature", tx);
+
+    const account = await program.account.merkleRootAccount.fetch(merkleRootAccount);
+    assert.ok(account.authority.equals(wallet));
+    assert.isNull(account.pendingAuthority);
+    assert.equal(account.sequence.toNumber(), 0);
+  });
+
+  it("Cannot be re-initialized", async () => {
+    try {
+      await program.methods.initialize(namespace, new Array(32).fill(9)).rpc();
+      assert.fail("re-initialize succeeded");
+    } catch (e) {
+      assert.notInclude(String(e), "re-initialize succeeded");
+    }
+  });
+
+  it("Only the authority updates the root", async () => {
+    await updateRoot(new Array(32).fill(1), null);
+    await expectError(updateRoot(new Array(32).fill(2), newAuthority), "Unauthorized");
+
+    const account = await program.account.merkleRootAccount.fetch(merkleRootAccount);
+    assert.deepEqual(account.root, new Array(32).fill(1));
+    assert.equal(account.sequence.toNumber(), 1);
+  });
+
+  it("Rejects updates built on stale state", async () => {
+    const current = await program.account.merkleRootAccount.fetch(merkleRootAccount);
+    const update = (expectedPrevRoot: number[], sequence: anchor.BN) =>
+      program.methods
+        .updateRoot(namespace, new Array(32).fill(7), expectedPrevRoot, sequence)
+        .accountsPartial({ merkleRootAccount, authority: wallet })
+        .rpc();
+
+    await expectError(update(new Array(32).fill(0), current.sequence.addn(1)), "StaleRoot");
+    // Replaying the previous update's sequence, or skipping ahead, is refused too.
+    await expectError(update(current.root, current.sequence), "StaleSequence");
+    await expectError(update(current.root, current.sequence.addn(2)), "StaleSequence");
+
+    const after = await program.account.merkleRootAccount.fetch(merkleRootAccount);
+    assert.deepEqual(after.root, current.root);
+    assert.ok(after.sequence.eq(current.sequence));
+  });
+
+  it("Records every root in the history", async () => {
+    const account = await program.account.merkleRootAccount.fetch(merkleRootAccount);
+    const history = await program.account.rootHistory.fetch(rootHistory);
+    const entries = history.entries.slice(0, history.len.toNumber());
+    assert.deepEqual(
+      entries.map((e) => e.sequence.toNumber()),
+      [0, 1]
+    );
+    const latest = entries[entries.length - 1];
+    assert.deepEqual(latest.root, account.root);
+    assert.ok(latest.timestamp.eq(account.timestamp));
+  });
+
+  it("Transfers the authority in two steps", async () => {
+    await expectError(
+      program.methods
+        .acceptAuthority(namespace)
+        .accountsPartial({ merkleRootAccount, newAuthority: newAuthority.publicKey })
+        .signers([newAuthority])
+        .rpc(),
+      "NoPendingAuthority"
+    );
+
+    await program.methods
+      .proposeAuthority(namespace, newAuthority.publicKey)
+      .accountsPartial({ merkleRootAccount, authority: wallet })
+      .rpc();
+    // Proposing does not hand over anything yet.
+    await updateRoot(new Array(32).fill(3), null);
+
+    await program.methods
+      .acceptAuthority(namespace)
+      .accountsPartial({ merkleRootAccount, newAuthority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+    const account = await program.account.merkleRootAccount.fetch(merkleRootAccount);
+    assert.ok(account.authority.equals(newAuthority.publicKey));
+    assert.isNull(account.pendingAuthority);
+
+    await expectError(updateRoot(new Array(32).fill(4), null), "Unauthorized");
+    await updateRoot(new Array(32).fill(4), newAuthority);
+  });
+
+  it("Sets the authority directly", async () => {
+    await expectError(
+      program.methods.setAuthority(namespace, newAuthority.publicKey).accountsPartial({ merkleRootAccount, authority: wallet }).rpc(),
+      "Unauthorized"
+    );
+    await program.methods
+      .setAuthority(namespace, wallet)
+      .accountsPartial({ merkleRootAccount, authority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+
+    const account = await program.account.merkleRootAccount.fetch(merkleRootAccount);
+    assert.ok(account.authority.equals(wallet));
+    await updateRoot(new Array(32).fill(5), null);
+  });
+
+  it("Keeps namespaces independent", async () => {
+    const other = pdas("fleet-b");
+    await program.methods
+      .initialize("fleet-b", new Array(32).fill(8))
+      .accountsPartial({ merkleRootAccount: other.merkleRootAccount, rootHistory: other.rootHistory })
+      .rpc();
+    await program.methods
+      .updateRoot("fleet-b", new Array(32).fill(9), new Array(32).fill(8), new anchor.BN(1))
+      .accountsPartial({ merkleRootAccount: other.merkleRootAccount, rootHistory: other.rootHistory, authority: wallet })
+      .rpc();
+
+    const b = await program.account.merkleRootAccount.fetch(other.merkleRootAccount);
+    assert.deepEqual(b.root, new Array(32).fill(9));
+    assert.equal(b.sequence.toNumber(), 1);
+    const a = await program.account.merkleRootAccount.fetch(merkleRootAccount);
+    assert.deepEqual(a.root, new Array(32).fill(5), "fleet-a untouched");
+
+    // An update must target the accounts its namespace derives to.
+    try {
+      await program.methods
+        .updateRoot("fleet-b", new Array(32).fill(1), a.root, a.sequence.addn(1))
+        .accountsPartial({ merkleRootAccount, rootHistory, authority: wallet })
+        .rpc();
+      assert.fail("cross-namespace update succeeded");
+    } catch (e) {
+      assert.equal(e.error?.errorCode?.code, "ConstraintSeeds", String(e));
+    }
+  });
+
+  it("Emits RootUpdated for every update", async () => {
+    const current = await program.account.merkleRootAccount.fetch(merkleRootAccount);
+    let listener: number;
+    const event = new Promise<any>((resolve) => {
+      listener = program.addEventListener("rootUpdated", (e) => {
+        if (e.namespace === namespace) resolve(e);
+      });
+    });
+    await updateRoot(new Array(32).fill(6), null);
+    const e = await event;
+    await program.removeEventListener(listener);
+
+    const account = await program.account.merkleRootAccount.fetch(merkleRootAccount);
+    assert.ok(e.sequence.eq(current.sequence.addn(1)));
+    assert.deepEqual(e.oldRoot, current.root);
+    assert.deepEqual(e.newRoot, new Array(32).fill(6));
+    assert.ok(e.timestamp.eq(account.timestamp));
   });
 });
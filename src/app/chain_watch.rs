@@ -0,0 +1,85 @@
+//! Watches the anchor for roots this service did not write.
+//!
+//! Every `CHAIN_WATCH_INTERVAL_SECS` the anchored root is read (through the anchor's long-lived
+//! client) and compared with the roots this instance produced. If someone else moved it, e.g.
+//! another deployment holding the same authority or a leaked key, the service raises an alarm
+//! and turns read-only (`foreign_anchor_write` in `GET /api/reconcile-status`). The usual
+//! repair, migration or reset re-enables writes.
+
+use crate::app::reconcile::{ReconcileAction, ReconcileReport, ReconcileTracker, RootSituation};
+use crate::domain::commitment::RootManager;
+use crate::infra::config;
+use chrono::Utc;
+use primitive_types::H256;
+use std::sync::Arc;
+use tokio::time::{interval, Duration, MissedTickBehavior};
+
+/// Checks the anchor once. If it holds a root this service did not write, records a
+/// `foreign_anchor_write` report in `tracker` (disabling writes) and returns that root.
+///
+/// A root that was already reported while writes are still disabled is not reported again.
+pub async fn check_anchor(root_manager: &RootManager, tracker: &ReconcileTracker) -> anyhow::Result<Option<H256>> {
+    let Some(anchored) = root_manager.foreign_anchored_root().await? else {
+        return Ok(None);
+    };
+    let anchored_hex = hex::encode(anchored.as_bytes());
+    if let Some(report) = tracker.status() {
+        if !report.writes_allowed
+            && report.situation == RootSituation::ForeignAnchorWrite
+            && report.anchored_root.as_deref() == Some(anchored_hex.as_str())
+        {
+            return Ok(Some(anchored));
+        }
+    }
+
+    let main_root = root_manager.get_main_root().await;
+    // While serving, every write moves the SMT and temporary_root together.
+    let temp_root = hex::encode(root_manager.get_temporary_root().await.as_bytes());
+    eprintln!(
+        "> ChainWatch: ALARM: {} holds {} but this service last anchored {}. Another writer moved the anchor; serving READ-ONLY.",
+        root_manager.anchor().name(),
+        anchored_hex,
+        hex::encode(main_root.as_bytes())
+    );
+    let situation = RootSituation::ForeignAnchorWrite;
+    tracker.set(ReconcileReport {
+        situation,
+        action: ReconcileAction::ReadOnly,
+        detail: situation.describe().to_string(),
+        smt_root: temp_root.clone(),
+        temporary_root: temp_root,
+        main_root: hex::encode(main_root.as_bytes()),
+        anchored_root: Some(anchored_hex),
+        writes_allowed: false,
+        reconciled_at: Utc::now(),
+        error: None,
+        resolved_by: None,
+        resolved_at: None,
+    });
+    Ok(Some(anchored))
+}
+
+/// Spawns a task that runs [`check_anchor`] every `CHAIN_WATCH_INTERVAL_SECS` (no-op when the
+/// interval is 0). Read failures are logged and retried on the next tick.
+pub fn start_chain_watch(root_manager: Arc<RootManager>, tracker: ReconcileTracker) {
+    let secs = config::chain_watch_interval_secs();
+    if secs == 0 {
+        println!("> ChainWatch: Anchor watch disabled (CHAIN_WATCH_INTERVAL_SECS=0)");
+        return;
+    }
+    println!("> ChainWatch: Checking the anchored root every {}s", secs);
+
+    tokio::spawn(async move {
+        let mut timer = interval(Duration::from_secs(secs));
+        timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // Startup reconciliation just compared the roots; skip the immediate tick.
+        timer.tick().await;
+
+        loop {
+            timer.tick().await;
+            if let Err(e) = check_anchor(&root_manager, &tracker).await {
+                eprintln!("> ChainWatch: Could not read the anchored root: {}", e);
+            }
+        }
+    });
+}
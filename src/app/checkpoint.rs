@@ -0,0 +1,65 @@
+//! Periodic SMT checkpoints for fast restarts (see `storage::smt::checkpoint`).
+
+use crate::app::database_service::DatabaseService;
+use crate::domain::commitment::RootManager;
+use crate::infra::config;
+use crate::storage::smt::{checkpoint_key, write_checkpoint};
+use primitive_types::H256;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{interval, Duration, MissedTickBehavior};
+
+/// Writes a checkpoint of the current tree. Returns `(root, change_seq)` of the checkpoint.
+///
+/// Writes are paused only while the tree is copied; serialization and disk I/O happen after
+/// the locks are released.
+pub async fn write_checkpoint_now(
+    db_service: &Arc<Mutex<DatabaseService>>,
+    root_manager: &RootManager,
+) -> anyhow::Result<(H256, i64)> {
+    let checkpoint = {
+        let _root_guard = root_manager.lock_root().await;
+        let db_service = db_service.lock().await;
+        db_service.checkpoint_snapshot().await?
+    };
+    let info = (checkpoint.root, checkpoint.change_seq);
+
+    let path = PathBuf::from(config::smt_checkpoint_path());
+    tokio::task::spawn_blocking(move || {
+        let key = checkpoint_key()?;
+        write_checkpoint(&path, &key, &checkpoint)
+    })
+    .await??;
+
+    Ok(info)
+}
+
+/// Spawns a task that checkpoints the tree every `SMT_CHECKPOINT_INTERVAL_SECS`
+/// (no-op when the interval is 0).
+pub fn start_checkpoint_task(db_service: Arc<Mutex<DatabaseService>>, root_manager: Arc<RootManager>) {
+    let secs = config::smt_checkpoint_interval_secs();
+    if secs == 0 {
+        println!("> Checkpoint: SMT checkpoints disabled (SMT_CHECKPOINT_INTERVAL_SECS=0)");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut timer = interval(Duration::from_secs(secs));
+        timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick fires immediately; the tree was just loaded, so skip it.
+        timer.tick().await;
+
+        loop {
+            timer.tick().await;
+            match write_checkpoint_now(&db_service, &root_manager).await {
+                Ok((root, change_seq)) => println!(
+                    "> Checkpoint: Wrote SMT checkpoint root={} change_seq={}",
+                    hex::encode(root.as_bytes()),
+                    change_seq
+                ),
+                Err(e) => eprintln!("> Checkpoint: Failed writing SMT checkpoint: {}", e),
+            }
+        }
+    });
+}
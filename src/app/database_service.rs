@@ -9,7 +9,13 @@
 
 use crate::domain::model::VerifiableModel;
 use crate::domain::verify::verify_smt_multi_update_proof_with_old_values;
-use crate::storage::smt::SmtStore;
+use crate::app::rebuild::{LeafUpdate, ShadowLog, ShadowSnapshot, ShadowTree};
+use crate::storage::anchor_history::AnchorHistory;
+use crate::storage::anchor_jobs::AnchorJobs;
+use crate::storage::smt::{
+    checkpoint_key, read_checkpoint, SmtCheckpoint, SmtStore, MERKLE_NODES_SHADOW_TABLE,
+    MERKLE_NODES_TABLE,
+};
 use crate::storage::smt::{h256_to_smt, smt_to_h256, SmtBlake2bHasher};
 use chrono::{DateTime, Utc};
 use primitive_types::H256;
@@ -23,14 +29,19 @@ use tokio::sync::Mutex;
 use crate::infra::config;
 use crate::crypto::hashing::{hash_key, hash_value};
 use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Advisory lock id held by the running instance (arbitrary, must be stable across instances).
+const INSTANCE_LOCK_ID: i64 = 4_240_001;
 
 /// The main service that manages database interaction and the SMT.
 pub struct DatabaseService {
     pool: PgPool,
     smt_store: Arc<Mutex<SmtStore>>,
+    /// Leaf updates committed while a shadow rebuild is in flight (see `app::rebuild`).
+    shadow_log: ShadowLog,
     /// Held for the lifetime of the process to prevent multiple VerifiableDB API instances
     /// from mutating the same DB/SMT concurrently (which can cause root drift).
-    #[allow(dead_code)]
     instance_lock: Option<sqlx::pool::PoolConnection<sqlx::Postgres>>,
 }
 
@@ -39,66 +50,123 @@ impl DatabaseService {
         &self.pool
     }
 
+    /// Releases the single-instance advisory lock (on shutdown), so a replacement instance can
+    /// start right away instead of waiting for the connection to time out.
+    pub async fn release_instance_lock(&mut self) -> anyhow::Result<()> {
+        if let Some(mut conn) = self.instance_lock.take() {
+            sqlx::query("SELECT pg_advisory_unlock($1)")
+                .bind(INSTANCE_LOCK_ID)
+                .execute(&mut *conn)
+                .await?;
+            println!("> DatabaseService: Released the instance lock.");
+        }
+        Ok(())
+    }
+
     /// Returns the current SMT root computed from the persistent SMT store.
     pub async fn current_smt_root(&self) -> anyhow::Result<H256> {
         let smt = self.smt_store.lock().await;
-        Ok(smt.get_root().await?)
+        smt.get_root().await
     }
 
     pub async fn reset_smt_store(&mut self) -> anyhow::Result<()> {
+        self.shadow_log.cancel();
         self.smt_store = Arc::new(Mutex::new(SmtStore::new_with_pool(self.pool.clone()).await?));
         Ok(())
     }
 
-    /// Rebuilds the SMT from the current contents of the database (post-migration).
+    /// Starts a shadow rebuild: creates an empty `merkle_nodes_shadow`, pins a read-only snapshot
+    /// and starts recording committed leaf updates for replay at swap time.
     ///
-    /// This is used when the DB schema changes and the canonical `row_to_json(table.*)` shape
-    /// (and therefore leaf hashes) may change.
-    pub async fn rebuild_smt_from_db(
-        &mut self,
-        models: Vec<Arc<dyn VerifiableModel>>,
-    ) -> anyhow::Result<(H256, u64)> {
-        // Clear persistent SMT nodes first.
-        sqlx::query("TRUNCATE TABLE merkle_nodes")
+    /// Caller must hold `RootManager::lock_root()` so no write commits between the snapshot and
+    /// the start of the replay log.
+    pub async fn begin_shadow_rebuild(&self) -> anyhow::Result<ShadowSnapshot> {
+        sqlx::query(&format!("DROP TABLE IF EXISTS {}", MERKLE_NODES_SHADOW_TABLE))
             .execute(&self.pool)
             .await?;
+        sqlx::query(&format!(
+            "CREATE TABLE {} (LIKE {} INCLUDING ALL)",
+            MERKLE_NODES_SHADOW_TABLE, MERKLE_NODES_TABLE
+        ))
+        .execute(&self.pool)
+        .await?;
 
-        // Reset SMT store in memory (it will load from the now-empty merkle_nodes table).
-        self.smt_store = Arc::new(Mutex::new(SmtStore::new_with_pool(self.pool.clone()).await?));
-
-        let mut updated_leaves: u64 = 0;
-
-        // Hold the SMT lock for the duration of the rebuild so updates are consistent.
-        let mut smt = self.smt_store.lock().await;
-
-        for model in models {
-            let table_name = model.table_name();
-            let pk_field = model.primary_key_field();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+        // The snapshot is taken at the first statement, not at BEGIN.
+        sqlx::query("SELECT 1").execute(&mut *tx).await?;
 
-            let sql = format!(
-                "SELECT row_to_json({}.*) as record, {}::text as pk_value FROM {}",
-                table_name, pk_field, table_name
-            );
+        self.shadow_log.start();
+        Ok(ShadowSnapshot { tx })
+    }
 
-            let rows = sqlx::query(&sql).fetch_all(&self.pool).await?;
+    /// Replays writes committed during the build into `shadow`, then atomically replaces
+    /// `merkle_nodes` with it and installs it as the live tree.
+    ///
+    /// Caller must hold `RootManager::lock_root()`. Fails (leaving the live tree untouched) if the
+    /// capture was cancelled in the meantime, e.g. by `clear_db` or a schema reset.
+    ///
+    /// Returns `(new_root, leaves, replayed_writes)`.
+    pub async fn swap_in_shadow(&mut self, shadow: ShadowTree) -> anyhow::Result<(H256, u64, u64)> {
+        let replay = self.shadow_log.take().ok_or_else(|| {
+            anyhow::anyhow!("Shadow rebuild was invalidated by a concurrent reset; retry the operation")
+        })?;
+        let ShadowTree { mut store, leaves } = shadow;
+
+        let mut replayed: u64 = 0;
+        for (table_name, updates) in replay {
+            replayed += updates.len() as u64;
+            store.update_all(&updates, &table_name).await?;
+        }
 
-            for row in rows {
-                let record: JsonValue = row.try_get("record")?;
-                let pk_value: String = row.try_get("pk_value")?;
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(&format!("TRUNCATE TABLE {}", MERKLE_NODES_TABLE))
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&format!(
+            "INSERT INTO {} SELECT * FROM {}",
+            MERKLE_NODES_TABLE, MERKLE_NODES_SHADOW_TABLE
+        ))
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(&format!("DROP TABLE {}", MERKLE_NODES_SHADOW_TABLE))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
 
-                let key_hash = hash_key(table_name, &pk_value);
-                let value_hash = hash_value(&record);
-                smt.update(key_hash, value_hash).await?;
-                updated_leaves += 1;
-            }
-        }
+        store.retarget(MERKLE_NODES_TABLE);
+        let new_root = store.get_root().await?;
+        self.smt_store = Arc::new(Mutex::new(store));
+        Ok((new_root, leaves, replayed))
+    }
 
-        let new_root = smt.get_root().await?;
-        Ok((new_root, updated_leaves))
+    /// Abandons a shadow rebuild: stops the replay log and drops `merkle_nodes_shadow`.
+    pub async fn abort_shadow_rebuild(&self) -> anyhow::Result<()> {
+        self.shadow_log.cancel();
+        sqlx::query(&format!("DROP TABLE IF EXISTS {}", MERKLE_NODES_SHADOW_TABLE))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
     }
 
     /// Creates a new instance of the DatabaseService and connects to the database.
+    ///
+    /// The SMT is rebuilt from every leaf in `merkle_nodes`; see `new_fast_start` to restore it
+    /// from a checkpoint instead.
     pub async fn new() -> Result<Self, anyhow::Error> {
+        Self::connect(None).await
+    }
+
+    /// Like `new`, but restores the SMT from the latest authenticated checkpoint and replays
+    /// only leaves changed since, provided the result matches `trusted_root` (the root from the
+    /// trusted state file). Falls back to a full rebuild otherwise.
+    pub async fn new_fast_start(trusted_root: H256) -> Result<Self, anyhow::Error> {
+        Self::connect(Some(trusted_root)).await
+    }
+
+    async fn connect(trusted_root: Option<H256>) -> Result<Self, anyhow::Error> {
         dotenv::dotenv().ok();
         let database_url = config::database_url();
 
@@ -117,6 +185,58 @@ impl DatabaseService {
         .execute(&pool)
         .await?;
 
+        // Change sequence for checkpoint replay: every insert/update of a leaf (including writes
+        // made outside the service) gets a fresh, monotonically increasing `change_seq`.
+        sqlx::query("CREATE SEQUENCE IF NOT EXISTS merkle_nodes_change_seq")
+            .execute(&pool)
+            .await?;
+        sqlx::query(
+            "ALTER TABLE merkle_nodes
+             ADD COLUMN IF NOT EXISTS change_seq BIGINT NOT NULL DEFAULT nextval('merkle_nodes_change_seq')",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS merkle_nodes_change_seq_idx ON merkle_nodes (change_seq)")
+            .execute(&pool)
+            .await?;
+
+        // Owning table and row pk of each leaf, so one model's leaves can be removed without a full
+        // rebuild and a leaf whose row disappeared can be named. NULL for leaves written before
+        // owners were recorded (a repair-roots rebuild fills them in).
+        sqlx::query("ALTER TABLE merkle_nodes ADD COLUMN IF NOT EXISTS table_name TEXT")
+            .execute(&pool)
+            .await?;
+        sqlx::query("ALTER TABLE merkle_nodes ADD COLUMN IF NOT EXISTS pk TEXT")
+            .execute(&pool)
+            .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS merkle_nodes_table_name_idx ON merkle_nodes (table_name)")
+            .execute(&pool)
+            .await?;
+        let has_trigger: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM pg_trigger WHERE tgname = 'merkle_nodes_change_seq_trg')",
+        )
+        .fetch_one(&pool)
+        .await?;
+        if !has_trigger {
+            sqlx::query(
+                "CREATE OR REPLACE FUNCTION merkle_nodes_bump_change_seq() RETURNS trigger AS $$
+                 BEGIN
+                     NEW.change_seq := nextval('merkle_nodes_change_seq');
+                     RETURN NEW;
+                 END
+                 $$ LANGUAGE plpgsql",
+            )
+            .execute(&pool)
+            .await?;
+            sqlx::query(
+                "CREATE TRIGGER merkle_nodes_change_seq_trg
+                 BEFORE INSERT OR UPDATE ON merkle_nodes
+                 FOR EACH ROW EXECUTE FUNCTION merkle_nodes_bump_change_seq()",
+            )
+            .execute(&pool)
+            .await?;
+        }
+
         // Persistent registry for runtime (dynamic) models.
         sqlx::query(
             "CREATE TABLE IF NOT EXISTS verifiable_models (
@@ -140,8 +260,16 @@ impl DatabaseService {
         .execute(&pool)
         .await?;
 
+        // Audit trail of anchored roots (written by RootManager once attached).
+        AnchorHistory::ensure_schema(&pool).await?;
+        AnchorJobs::ensure_schema(&pool).await?;
+
         // Initialize the persistent SMT store with the database connection pool.
-        let smt_store = Arc::new(Mutex::new(SmtStore::new_with_pool(pool.clone()).await?));
+        let smt = match trusted_root {
+            Some(root) => Self::load_smt_from_checkpoint(&pool, root).await?,
+            None => SmtStore::new_with_pool(pool.clone()).await?,
+        };
+        let smt_store = Arc::new(Mutex::new(smt));
 
         // Enforce single-instance by default (opt-out via ALLOW_MULTI_INSTANCE=true).
         let allow_multi = std::env::var("ALLOW_MULTI_INSTANCE").unwrap_or_default() == "true";
@@ -149,10 +277,8 @@ impl DatabaseService {
             None
         } else {
             let mut conn = pool.acquire().await?;
-            // Arbitrary constant lock ID (must be stable across instances).
-            let lock_id: i64 = 4_240_001;
             let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
-                .bind(lock_id)
+                .bind(INSTANCE_LOCK_ID)
                 .fetch_one(&mut *conn)
                 .await?;
             if !locked {
@@ -164,7 +290,62 @@ Set ALLOW_MULTI_INSTANCE=true to bypass (NOT recommended)."
             Some(conn)
         };
 
-        Ok(Self { pool, smt_store, instance_lock })
+        Ok(Self {
+            pool,
+            smt_store,
+            shadow_log: ShadowLog::default(),
+            instance_lock,
+        })
+    }
+
+    /// Restores the SMT from the checkpoint file if it authenticates and reproduces
+    /// `trusted_root`; otherwise rebuilds it from every leaf in `merkle_nodes`.
+    async fn load_smt_from_checkpoint(pool: &PgPool, trusted_root: H256) -> anyhow::Result<SmtStore> {
+        let path = PathBuf::from(config::smt_checkpoint_path());
+        if path.exists() {
+            let restored = async {
+                let key = checkpoint_key()?;
+                let checkpoint = read_checkpoint(&path, &key)?;
+                let change_seq = checkpoint.change_seq;
+                let smt = SmtStore::from_checkpoint(pool.clone(), checkpoint).await?;
+                let root = smt.get_root().await?;
+                if root != trusted_root {
+                    return Err(anyhow::anyhow!(
+                        "checkpoint root {} does not match trusted root {}",
+                        hex::encode(root.as_bytes()),
+                        hex::encode(trusted_root.as_bytes())
+                    ));
+                }
+                Ok::<_, anyhow::Error>((smt, change_seq))
+            }
+            .await;
+
+            match restored {
+                Ok((smt, change_seq)) => {
+                    println!(
+                        "> DatabaseService: Restored SMT from checkpoint (change_seq={}).",
+                        change_seq
+                    );
+                    return Ok(smt);
+                }
+                Err(e) => {
+                    eprintln!("> DatabaseService: Ignoring SMT checkpoint: {}", e);
+                }
+            }
+        }
+
+        println!("> DatabaseService: Rebuilding SMT from merkle_nodes (no usable checkpoint).");
+        SmtStore::new_with_pool(pool.clone()).await
+    }
+
+    /// Copies the current tree for checkpointing.
+    ///
+    /// Caller must hold `RootManager::lock_root()` so no write lands between reading the change
+    /// sequence and copying the tree.
+    pub async fn checkpoint_snapshot(&self) -> anyhow::Result<SmtCheckpoint> {
+        let smt = self.smt_store.lock().await;
+        let change_seq = smt.max_change_seq().await?;
+        Ok(smt.checkpoint(change_seq))
     }
 
     /// Clears the database.
@@ -185,7 +366,8 @@ Set ALLOW_MULTI_INSTANCE=true to bypass (NOT recommended)."
 
         sqlx::query("DELETE FROM merkle_nodes").execute(&self.pool).await?;
 
-        // Also reset the SMT in memory
+        // Also reset the SMT in memory (and invalidate any in-flight shadow rebuild).
+        self.shadow_log.cancel();
         self.smt_store = Arc::new(Mutex::new(SmtStore::new_with_pool(self.pool.clone()).await?));
         Ok(())
     }
@@ -359,10 +541,12 @@ Set ALLOW_MULTI_INSTANCE=true to bypass (NOT recommended)."
             value_hashes.push(crate::crypto::hashing::hash_value(record));
         }
 
-        let updates: Vec<(H256, H256)> = key_hashes
+        let updates: Vec<LeafUpdate> = key_hashes
             .iter()
             .copied()
             .zip(value_hashes.iter().copied())
+            .zip(inserted_ids.iter().cloned())
+            .map(|((k, v), pk)| (k, v, pk))
             .collect();
 
         // Generate proof against the current SMT state (no persistence yet).
@@ -423,9 +607,10 @@ Set ALLOW_MULTI_INSTANCE=true to bypass (NOT recommended)."
         }
 
         // Apply SMT updates + merkle_nodes persistence within the SAME SQL transaction.
-        smt.apply_updates_in_tx(&mut transaction, &updates).await?;
+        smt.apply_updates_in_tx(&mut transaction, model.table_name(), &updates).await?;
 
         transaction.commit().await?;
+        self.shadow_log.record(model.table_name(), &updates);
 
         Ok((proposed_root, proof, inserted_records, inserted_ids))
     }
@@ -794,10 +979,12 @@ Set ALLOW_MULTI_INSTANCE=true to bypass (NOT recommended)."
             value_hashes.push(hash_value(record));
         }
 
-        let updates: Vec<(H256, H256)> = key_hashes
+        let updates: Vec<LeafUpdate> = key_hashes
             .iter()
             .copied()
             .zip(value_hashes.iter().copied())
+            .zip(upserted_ids.iter().cloned())
+            .map(|((k, v), pk)| (k, v, pk))
             .collect();
 
         let mut smt = self.smt_store.lock().await;
@@ -853,10 +1040,124 @@ Set ALLOW_MULTI_INSTANCE=true to bypass (NOT recommended)."
             ));
         }
 
-        smt.apply_updates_in_tx(&mut transaction, &updates).await?;
+        smt.apply_updates_in_tx(&mut transaction, model.table_name(), &updates).await?;
         transaction.commit().await?;
+        self.shadow_log.record(model.table_name(), &updates);
 
         Ok((proposed_root, proof, upserted_records, upserted_ids))
     }
-}
 
+    /// Removes every leaf owned by `table_name` in a single verified state transition
+    /// (`trusted_root` -> returned root). With `drop_table`, the table and its
+    /// `verifiable_models` entry are dropped in the same SQL transaction.
+    ///
+    /// Leaves are found by their recorded owner and, for leaves written before owners were
+    /// recorded, by re-deriving `hash_key(table, pk)` from the rows still in the table.
+    ///
+    /// Caller must hold `RootManager::lock_root()` and apply the returned root with
+    /// `update_temporary_root`. Returns `(new_root, removed_leaves)`.
+    pub async fn remove_model_leaves(
+        &self,
+        table_name: &str,
+        drop_table: bool,
+        trusted_root: H256,
+    ) -> Result<(H256, u64), anyhow::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        // Legacy leaves (no recorded owner): derive keys from the rows of a registered table.
+        let pk_field: Option<String> = sqlx::query_scalar(
+            "SELECT primary_key_field FROM verifiable_models WHERE table_name = $1",
+        )
+        .bind(table_name)
+        .fetch_optional(&mut *transaction)
+        .await?;
+        let table_exists: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+            .bind(format!("public.{}", table_name))
+            .fetch_one(&mut *transaction)
+            .await?;
+        let mut derived_keys: Vec<Vec<u8>> = Vec::new();
+        if let (Some(pk_field), true) = (pk_field, table_exists) {
+            let pks: Vec<String> = sqlx::query_scalar(&format!(
+                "SELECT {}::text FROM {}",
+                pk_field, table_name
+            ))
+            .fetch_all(&mut *transaction)
+            .await?;
+            derived_keys = pks
+                .iter()
+                .map(|pk| hash_key(table_name, pk).as_bytes().to_vec())
+                .collect();
+        }
+
+        let rows = sqlx::query(
+            "SELECT node_hash, node_value FROM merkle_nodes
+             WHERE table_name = $1 OR node_hash = ANY($2)",
+        )
+        .bind(table_name)
+        .bind(&derived_keys)
+        .fetch_all(&mut *transaction)
+        .await?;
+        let mut key_hashes: Vec<H256> = Vec::with_capacity(rows.len());
+        let mut old_values: Vec<H256> = Vec::with_capacity(rows.len());
+        for r in rows {
+            let kh: Vec<u8> = r.try_get("node_hash")?;
+            let vh: Vec<u8> = r.try_get("node_value")?;
+            if kh.len() != 32 || vh.len() != 32 {
+                return Err(anyhow::anyhow!("Invalid leaf length in merkle_nodes"));
+            }
+            key_hashes.push(H256::from_slice(&kh));
+            old_values.push(H256::from_slice(&vh));
+        }
+
+        let mut smt = self.smt_store.lock().await;
+        let mut new_root = trusted_root;
+
+        if !key_hashes.is_empty() {
+            let proof = smt.generate_proof(key_hashes.clone()).await?;
+            let zero_values = vec![H256::zero(); key_hashes.len()];
+
+            let removed_leaves_smt: Vec<_> = key_hashes
+                .iter()
+                .map(|k| (h256_to_smt(*k), h256_to_smt(H256::zero())))
+                .collect();
+            let proposed_root_smt = proof
+                .clone()
+                .compute_root::<SmtBlake2bHasher>(removed_leaves_smt)
+                .unwrap_or_default();
+            let proposed_root = smt_to_h256(&proposed_root_smt);
+
+            let ok = verify_smt_multi_update_proof_with_old_values(
+                trusted_root,
+                proposed_root,
+                key_hashes.clone(),
+                old_values,
+                zero_values,
+                proof,
+            );
+            if !ok {
+                transaction.rollback().await?;
+                return Err(anyhow::anyhow!(
+                    "VERIFIABLE_PROOF_FAILED: trusted_root={} proposed_root={}",
+                    hex::encode(trusted_root.as_bytes()),
+                    hex::encode(proposed_root.as_bytes())
+                ));
+            }
+            new_root = proposed_root;
+        }
+
+        if drop_table {
+            sqlx::query(&format!("DROP TABLE IF EXISTS {}", table_name))
+                .execute(&mut *transaction)
+                .await?;
+            sqlx::query("DELETE FROM verifiable_models WHERE table_name = $1")
+                .bind(table_name)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        smt.remove_leaves_in_tx(&mut transaction, &key_hashes).await?;
+        transaction.commit().await?;
+
+        Ok((new_root, key_hashes.len() as u64))
+    }
+}
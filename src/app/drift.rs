@@ -0,0 +1,225 @@
+//! Row-level drift detection.
+//!
+//! Recomputes the canonical leaf hash of every row of every registered model and compares it
+//! against the leaves persisted in `merkle_nodes`. Unlike `rebuild_smt_from_db`, the scan is
+//! strictly read-only: it never touches the SMT, `merkle_nodes`, or any root, so it can be used
+//! to find out *what* changed before deciding whether a repair is legitimate.
+
+use crate::crypto::hashing::{hash_key, hash_value};
+use crate::domain::model::VerifiableModel;
+use crate::infra::config;
+use crate::storage::smt::{tree_order_sql, RootBuilder};
+use primitive_types::H256;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+
+/// A row whose current contents no longer hash to the stored leaf value.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModifiedRow {
+    pub table: String,
+    pub pk: String,
+    /// Leaf value currently stored in `merkle_nodes` (hex).
+    pub stored_leaf: String,
+    /// Leaf value recomputed from the live row (hex).
+    pub computed_leaf: String,
+}
+
+/// A row present in the table but without any corresponding SMT leaf.
+#[derive(Debug, Clone, Serialize)]
+pub struct InsertedRow {
+    pub table: String,
+    pub pk: String,
+}
+
+/// A stored SMT leaf that no longer has a backing row in any registered table.
+///
+/// The owning table and pk are reported when they were recorded with the leaf (leaves written
+/// before owners were recorded have neither until the next `repair-roots`).
+#[derive(Debug, Clone, Serialize)]
+pub struct DeletedLeaf {
+    pub table: Option<String>,
+    pub pk: Option<String>,
+    pub leaf_key: String,
+    pub stored_leaf: String,
+}
+
+/// Result of a full row-level verification scan.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DriftReport {
+    /// Number of table rows hashed during the scan.
+    pub scanned_rows: u64,
+    /// Number of leaves found in `merkle_nodes`.
+    pub stored_leaves: u64,
+    /// Root of the SMT formed by the stored leaves (what a rebuild from `merkle_nodes` yields).
+    pub stored_leaves_root: String,
+    /// Root of the SMT formed by the recomputed row hashes (what `repair-roots` would anchor).
+    pub rows_root: String,
+    pub modified: Vec<ModifiedRow>,
+    pub inserted_out_of_band: Vec<InsertedRow>,
+    pub deleted_out_of_band: Vec<DeletedLeaf>,
+}
+
+impl DriftReport {
+    /// True when every row matches its stored leaf and there are no orphaned leaves/rows.
+    pub fn is_clean(&self) -> bool {
+        self.modified.is_empty()
+            && self.inserted_out_of_band.is_empty()
+            && self.deleted_out_of_band.is_empty()
+    }
+}
+
+/// Scans every row of `models` and reports rows that diverge from the stored SMT leaves.
+///
+/// The scan runs inside a single `REPEATABLE READ` transaction that is read-only apart from a
+/// session-local temporary table, so it observes one consistent snapshot of application rows +
+/// `merkle_nodes` (writes commit both atomically) without blocking concurrent writers.
+///
+/// Rows are streamed through server-side cursors `REBUILD_BATCH_SIZE` at a time and their leaf
+/// hashes parked in the temporary table; the stored and recomputed leaves are then merged in
+/// tree key order, so both roots are computed and every divergence is classified without
+/// holding either leaf set in memory.
+pub async fn scan_drift(
+    pool: &PgPool,
+    models: Vec<Arc<dyn VerifiableModel>>,
+) -> anyhow::Result<DriftReport> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")
+        .execute(&mut *tx)
+        .await?;
+    // Created before the transaction turns read-only; writing to it stays allowed afterwards.
+    sqlx::query(
+        "CREATE TEMPORARY TABLE drift_row_leaves (
+            leaf_key BYTEA NOT NULL,
+            leaf_value BYTEA NOT NULL,
+            table_name TEXT NOT NULL,
+            pk TEXT NOT NULL
+        ) ON COMMIT DROP",
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("SET TRANSACTION READ ONLY").execute(&mut *tx).await?;
+
+    let batch_size = config::rebuild_batch_size();
+    let mut report = DriftReport::default();
+
+    for model in models {
+        let table_name = model.table_name();
+        let pk_field = model.primary_key_field();
+
+        sqlx::query(&format!(
+            "DECLARE drift_rows NO SCROLL CURSOR FOR
+             SELECT row_to_json({}.*) as record, {}::text as pk_value FROM {}",
+            table_name, pk_field, table_name
+        ))
+        .execute(&mut *tx)
+        .await?;
+        let fetch_rows = format!("FETCH FORWARD {} FROM drift_rows", batch_size);
+        loop {
+            let rows = sqlx::query(&fetch_rows).fetch_all(&mut *tx).await?;
+            if rows.is_empty() {
+                break;
+            }
+            let mut keys: Vec<Vec<u8>> = Vec::with_capacity(rows.len());
+            let mut values: Vec<Vec<u8>> = Vec::with_capacity(rows.len());
+            let mut pks: Vec<String> = Vec::with_capacity(rows.len());
+            for row in rows {
+                let record: JsonValue = row.try_get("record")?;
+                let pk_value: String = row.try_get("pk_value")?;
+                keys.push(hash_key(table_name, &pk_value).as_bytes().to_vec());
+                values.push(hash_value(&record).as_bytes().to_vec());
+                pks.push(pk_value);
+            }
+            report.scanned_rows += pks.len() as u64;
+            sqlx::query(
+                "INSERT INTO drift_row_leaves (leaf_key, leaf_value, table_name, pk)
+                 SELECT k, v, $3, p FROM UNNEST($1::bytea[], $2::bytea[], $4::text[]) AS t(k, v, p)",
+            )
+            .bind(keys)
+            .bind(values)
+            .bind(table_name)
+            .bind(pks)
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query("CLOSE drift_rows").execute(&mut *tx).await?;
+    }
+
+    sqlx::query(&format!(
+        "DECLARE drift_leaves NO SCROLL CURSOR FOR
+         SELECT COALESCE(m.node_hash, r.leaf_key) AS leaf_key,
+                m.node_value AS stored_leaf, m.table_name AS stored_table, m.pk AS stored_pk,
+                r.leaf_value AS computed_leaf, r.table_name AS row_table, r.pk AS row_pk
+         FROM merkle_nodes m
+         FULL JOIN drift_row_leaves r ON r.leaf_key = m.node_hash
+         ORDER BY {}",
+        tree_order_sql("COALESCE(m.node_hash, r.leaf_key)")
+    ))
+    .execute(&mut *tx)
+    .await?;
+    let mut stored_root = RootBuilder::default();
+    let mut rows_root = RootBuilder::default();
+    let fetch_leaves = format!("FETCH FORWARD {} FROM drift_leaves", batch_size);
+    loop {
+        let leaves = sqlx::query(&fetch_leaves).fetch_all(&mut *tx).await?;
+        if leaves.is_empty() {
+            break;
+        }
+        for r in leaves {
+            let key = leaf_hash(r.try_get("leaf_key")?)?;
+            let stored = r
+                .try_get::<Option<Vec<u8>>, _>("stored_leaf")?
+                .map(leaf_hash)
+                .transpose()?;
+            let computed = r
+                .try_get::<Option<Vec<u8>>, _>("computed_leaf")?
+                .map(leaf_hash)
+                .transpose()?;
+            if let Some(v) = stored {
+                report.stored_leaves += 1;
+                stored_root.push(key, v)?;
+            }
+            if let Some(v) = computed {
+                rows_root.push(key, v)?;
+            }
+
+            match (stored, computed) {
+                (Some(stored_leaf), Some(computed_leaf)) if stored_leaf == computed_leaf => {}
+                (Some(stored_leaf), Some(computed_leaf)) => report.modified.push(ModifiedRow {
+                    table: r.try_get("row_table")?,
+                    pk: r.try_get("row_pk")?,
+                    stored_leaf: hex::encode(stored_leaf.as_bytes()),
+                    computed_leaf: hex::encode(computed_leaf.as_bytes()),
+                }),
+                (None, Some(_)) => report.inserted_out_of_band.push(InsertedRow {
+                    table: r.try_get("row_table")?,
+                    pk: r.try_get("row_pk")?,
+                }),
+                (Some(stored_leaf), None) if !stored_leaf.is_zero() => {
+                    report.deleted_out_of_band.push(DeletedLeaf {
+                        table: r.try_get("stored_table")?,
+                        pk: r.try_get("stored_pk")?,
+                        leaf_key: hex::encode(key.as_bytes()),
+                        stored_leaf: hex::encode(stored_leaf.as_bytes()),
+                    })
+                }
+                _ => {}
+            }
+        }
+    }
+    sqlx::query("CLOSE drift_leaves").execute(&mut *tx).await?;
+
+    tx.rollback().await?;
+
+    report.stored_leaves_root = hex::encode(stored_root.root().as_bytes());
+    report.rows_root = hex::encode(rows_root.root().as_bytes());
+    Ok(report)
+}
+
+fn leaf_hash(bytes: Vec<u8>) -> anyhow::Result<H256> {
+    if bytes.len() != 32 {
+        return Err(anyhow::anyhow!("Invalid leaf length in merkle_nodes"));
+    }
+    Ok(H256::from_slice(&bytes))
+}
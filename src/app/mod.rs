@@ -1,2 +1,7 @@
+pub mod chain_watch;
+pub mod checkpoint;
 pub mod database_service;
-
+pub mod drift;
+pub mod rebuild;
+pub mod reconcile;
+pub mod shutdown;
@@ -0,0 +1,289 @@
+//! Online (shadow) SMT rebuilds.
+//!
+//! `/bootstrap/migrate` and `/bootstrap/repair-roots` used to truncate `merkle_nodes` and rebuild
+//! the tree in place while holding the root lock and the `db_service` mutex, which meant a full
+//! outage proportional to table size. A shadow rebuild instead runs in phases:
+//!
+//! 1. **Snapshot** (under the root lock, brief): pin a `REPEATABLE READ` snapshot and start
+//!    recording every leaf update that writers commit from now on (the replay log).
+//! 2. **Build** (no locks): hash every row of the snapshot into a fresh tree persisted in
+//!    `merkle_nodes_shadow`. The live tree keeps serving reads and writes.
+//! 3. **Swap** (under the root lock): replay the recorded writes into the shadow tree, copy it
+//!    over `merkle_nodes` in one transaction and replace the in-memory tree.
+//! 4. **Anchor**: the caller commits the new root while still holding the root lock.
+
+use crate::crypto::hashing::{hash_key, hash_value};
+use crate::domain::model::VerifiableModel;
+use crate::infra::config;
+use crate::storage::smt::{SmtStore, MERKLE_NODES_SHADOW_TABLE};
+use chrono::{DateTime, Utc};
+use primitive_types::H256;
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Phase of the current (or last) rebuild.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RebuildPhase {
+    #[default]
+    Idle,
+    Preparing,
+    Building,
+    Swapping,
+    Anchoring,
+    Completed,
+    Failed,
+}
+
+/// Progress report exposed through `GET /bootstrap/rebuild-status`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RebuildStatus {
+    pub phase: RebuildPhase,
+    /// `migrate` or `repair-roots`.
+    pub operation: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub tables_total: u64,
+    pub tables_done: u64,
+    pub current_table: Option<String>,
+    /// Rows across all tables in the snapshot (known once the build starts).
+    pub rows_total: u64,
+    pub rows_hashed: u64,
+    /// Average hashing throughput since the build started.
+    pub rows_per_second: f64,
+    /// Estimated seconds until all rows are hashed (at the current average rate).
+    pub eta_seconds: Option<u64>,
+    /// Leaf updates committed by writers during the build and replayed at swap time.
+    pub replayed_writes: u64,
+    pub new_root: Option<String>,
+    pub error: Option<String>,
+}
+
+impl RebuildStatus {
+    pub fn in_progress(&self) -> bool {
+        !matches!(
+            self.phase,
+            RebuildPhase::Idle | RebuildPhase::Completed | RebuildPhase::Failed
+        )
+    }
+}
+
+/// Shared handle used by handlers to publish rebuild progress.
+#[derive(Clone, Default)]
+pub struct RebuildTracker {
+    inner: Arc<std::sync::Mutex<RebuildStatus>>,
+}
+
+impl RebuildTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> RebuildStatus {
+        self.inner.lock().unwrap().clone()
+    }
+
+    /// Marks a new rebuild as started. Fails if another one is still running.
+    pub fn try_begin(&self, operation: &str) -> anyhow::Result<()> {
+        let mut s = self.inner.lock().unwrap();
+        if s.in_progress() {
+            return Err(anyhow::anyhow!(
+                "A {} rebuild is already in progress (phase: {:?})",
+                s.operation.as_deref().unwrap_or("unknown"),
+                s.phase
+            ));
+        }
+        *s = RebuildStatus {
+            phase: RebuildPhase::Preparing,
+            operation: Some(operation.to_string()),
+            started_at: Some(Utc::now()),
+            ..Default::default()
+        };
+        Ok(())
+    }
+
+    pub fn update(&self, f: impl FnOnce(&mut RebuildStatus)) {
+        f(&mut self.inner.lock().unwrap());
+    }
+
+    pub fn set_phase(&self, phase: RebuildPhase) {
+        self.update(|s| s.phase = phase);
+    }
+
+    pub fn complete(&self, new_root: H256) {
+        self.update(|s| {
+            s.phase = RebuildPhase::Completed;
+            s.current_table = None;
+            s.new_root = Some(hex::encode(new_root.as_bytes()));
+            s.finished_at = Some(Utc::now());
+        });
+    }
+
+    /// Marks the running rebuild as failed. No-op if nothing is running.
+    pub fn fail(&self, error: &str) {
+        self.update(|s| {
+            if s.in_progress() {
+                s.phase = RebuildPhase::Failed;
+                s.error = Some(error.to_string());
+                s.finished_at = Some(Utc::now());
+            }
+        });
+    }
+}
+
+/// A committed `(leaf_key, leaf_value, pk)` update.
+pub type LeafUpdate = (H256, H256, String);
+
+/// Leaf updates of one table committed by a single write.
+pub type LeafUpdateBatch = (String, Vec<LeafUpdate>);
+
+/// Leaf updates committed by writers while a shadow rebuild is running.
+///
+/// `None` means no rebuild is capturing; `Some` holds updates in commit order.
+#[derive(Clone, Default)]
+pub struct ShadowLog {
+    inner: Arc<std::sync::Mutex<Option<Vec<LeafUpdateBatch>>>>,
+}
+
+impl ShadowLog {
+    pub fn start(&self) {
+        *self.inner.lock().unwrap() = Some(Vec::new());
+    }
+
+    /// Records committed leaf updates of `table_name` (no-op unless a rebuild is capturing).
+    pub fn record(&self, table_name: &str, updates: &[LeafUpdate]) {
+        if let Some(log) = self.inner.lock().unwrap().as_mut() {
+            log.push((table_name.to_string(), updates.to_vec()));
+        }
+    }
+
+    /// Stops capturing and returns the recorded updates, or `None` if capture was cancelled.
+    pub fn take(&self) -> Option<Vec<LeafUpdateBatch>> {
+        self.inner.lock().unwrap().take()
+    }
+
+    /// Stops capturing; a subsequent swap will refuse to install the shadow tree.
+    pub fn cancel(&self) {
+        *self.inner.lock().unwrap() = None;
+    }
+}
+
+/// A pinned, read-only snapshot that a shadow tree is built from.
+pub struct ShadowSnapshot {
+    pub(crate) tx: Transaction<'static, Postgres>,
+}
+
+/// A fully built tree persisted in `merkle_nodes_shadow`, ready to be swapped in.
+pub struct ShadowTree {
+    pub(crate) store: SmtStore,
+    pub(crate) leaves: u64,
+}
+
+impl ShadowTree {
+    pub fn leaves(&self) -> u64 {
+        self.leaves
+    }
+}
+
+/// Rebuilds the SMT from `snapshot` into `merkle_nodes_shadow`.
+///
+/// This is used when the DB schema changes and the canonical `row_to_json(table.*)` shape
+/// (and therefore leaf hashes) may change, or when repairing roots. It takes no service locks;
+/// the live tree keeps serving while it runs.
+///
+/// Rows are streamed through a server-side cursor `REBUILD_BATCH_SIZE` rows at a time, each
+/// batch is hashed in parallel across cores and its leaves are bulk-loaded into the tree and
+/// the shadow table, so memory use is bounded by the batch size rather than the table size.
+pub async fn rebuild_smt_from_db(
+    pool: PgPool,
+    mut snapshot: ShadowSnapshot,
+    models: Vec<Arc<dyn VerifiableModel>>,
+    tracker: &RebuildTracker,
+) -> anyhow::Result<ShadowTree> {
+    let batch_size = config::rebuild_batch_size();
+
+    // Count rows up front (in the same snapshot) so progress can report an ETA.
+    let mut rows_total: u64 = 0;
+    for model in &models {
+        let count: i64 = sqlx::query_scalar(&format!("SELECT count(*) FROM {}", model.table_name()))
+            .fetch_one(&mut *snapshot.tx)
+            .await?;
+        rows_total += count as u64;
+    }
+
+    tracker.update(|s| {
+        s.phase = RebuildPhase::Building;
+        s.tables_total = models.len() as u64;
+        s.rows_total = rows_total;
+    });
+
+    let mut store = SmtStore::new_empty_with_table(pool, MERKLE_NODES_SHADOW_TABLE);
+    let mut leaves: u64 = 0;
+    let started = Instant::now();
+
+    for model in models {
+        let table_name = model.table_name().to_string();
+        let pk_field = model.primary_key_field();
+        tracker.update(|s| s.current_table = Some(table_name.clone()));
+
+        sqlx::query(&format!(
+            "DECLARE rebuild_cursor NO SCROLL CURSOR FOR
+             SELECT row_to_json({}.*) as record, {}::text as pk_value FROM {}",
+            table_name, pk_field, table_name
+        ))
+        .execute(&mut *snapshot.tx)
+        .await?;
+
+        let fetch_sql = format!("FETCH FORWARD {} FROM rebuild_cursor", batch_size);
+        loop {
+            let rows = sqlx::query(&fetch_sql).fetch_all(&mut *snapshot.tx).await?;
+            if rows.is_empty() {
+                break;
+            }
+
+            let mut batch: Vec<(JsonValue, String)> = Vec::with_capacity(rows.len());
+            for row in rows {
+                batch.push((row.try_get("record")?, row.try_get("pk_value")?));
+            }
+
+            let table = table_name.clone();
+            let updates: Vec<LeafUpdate> = tokio::task::spawn_blocking(move || {
+                batch
+                    .par_iter()
+                    .map(|(record, pk_value)| {
+                        (hash_key(&table, pk_value), hash_value(record), pk_value.clone())
+                    })
+                    .collect()
+            })
+            .await?;
+
+            store.update_all(&updates, &table_name).await?;
+            leaves += updates.len() as u64;
+
+            let elapsed = started.elapsed().as_secs_f64();
+            tracker.update(|s| {
+                s.rows_hashed = leaves;
+                if elapsed > 0.0 {
+                    s.rows_per_second = leaves as f64 / elapsed;
+                    let remaining = s.rows_total.saturating_sub(leaves);
+                    s.eta_seconds = Some((remaining as f64 / s.rows_per_second).ceil() as u64);
+                }
+            });
+        }
+
+        sqlx::query("CLOSE rebuild_cursor")
+            .execute(&mut *snapshot.tx)
+            .await?;
+
+        tracker.update(|s| s.tables_done += 1);
+    }
+
+    snapshot.tx.rollback().await?;
+    tracker.update(|s| s.eta_seconds = Some(0));
+
+    Ok(ShadowTree { store, leaves })
+}
@@ -0,0 +1,294 @@
+//! Startup reconciliation of the three roots.
+//!
+//! On startup the service holds three roots that should agree: the SMT root rebuilt from
+//! `merkle_nodes`, the trusted `temporary_root` (sealed state file) and the anchored `main_root`.
+//! Before serving traffic the situation is classified and the configured action applied
+//! (defaults in parentheses):
+//!
+//! - `clean`: all three roots agree (always `serve`).
+//! - `unanchored_tail`: DB and trusted root agree, the anchor lags, e.g. after a crash before
+//!   commit (`auto_anchor`).
+//! - `smt_behind_trusted`: the DB is at an older root this service produced, e.g. a restored
+//!   backup (`require_repair`).
+//! - `db_tampered`: the DB is at a root this service never produced (`require_repair`).
+//! - `chain_ahead_of_local`: the anchor holds a root newer than, or unknown to, this service
+//!   (`read_only`).
+//!
+//! `read_only` and `require_repair` keep reads available but reject writes until a repair,
+//! migration or reset makes the roots consistent again. `refuse` aborts startup.
+//!
+//! While serving, `app::chain_watch` reports `foreign_anchor_write` the same way when the
+//! anchor moves to a root this service did not write (always `read_only`).
+
+use crate::domain::commitment::RootManager;
+use crate::infra::config;
+use chrono::{DateTime, Utc};
+use primitive_types::H256;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// How the three startup roots relate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RootSituation {
+    Clean,
+    UnanchoredTail,
+    SmtBehindTrusted,
+    DbTampered,
+    ChainAheadOfLocal,
+    ForeignAnchorWrite,
+}
+
+impl RootSituation {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            RootSituation::Clean => "SMT, trusted and anchored roots agree.",
+            RootSituation::UnanchoredTail => {
+                "DB and trusted root agree but are not anchored yet (e.g. a crash before commit)."
+            }
+            RootSituation::SmtBehindTrusted => {
+                "The DB is at an older root this service produced (restored backup or lost writes)."
+            }
+            RootSituation::DbTampered => {
+                "The DB is at a root this service never produced (modified outside the service, \
+                 or a crash between a DB commit and the trusted state write). Run \
+                 GET /bootstrap/drift to see what changed."
+            }
+            RootSituation::ChainAheadOfLocal => {
+                "The anchor holds a root newer than, or unknown to, this service (another writer, \
+                 or a lost trusted state file)."
+            }
+            RootSituation::ForeignAnchorWrite => {
+                "While serving, the anchor changed to a root this service did not write (another \
+                 writer holds the anchor's authority). Writes stay disabled until an operator \
+                 reconciles the two."
+            }
+        }
+    }
+}
+
+/// What to do about a situation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconcileAction {
+    /// Serve reads and writes as usual.
+    Serve,
+    /// Anchor temporary_root immediately, then serve.
+    AutoAnchor,
+    /// Serve reads only.
+    ReadOnly,
+    /// Serve reads only until an operator runs `POST /bootstrap/repair-roots`.
+    RequireRepair,
+    /// Abort startup.
+    Refuse,
+}
+
+impl ReconcileAction {
+    fn parse(var: &str, value: &str) -> anyhow::Result<Self> {
+        match value {
+            "serve" => Ok(Self::Serve),
+            "auto_anchor" => Ok(Self::AutoAnchor),
+            "read_only" => Ok(Self::ReadOnly),
+            "require_repair" => Ok(Self::RequireRepair),
+            "refuse" => Ok(Self::Refuse),
+            other => Err(anyhow::anyhow!(
+                "Unknown {} '{}' (expected serve, auto_anchor, read_only, require_repair or refuse)",
+                var,
+                other
+            )),
+        }
+    }
+
+    fn allows_writes(&self) -> bool {
+        matches!(self, Self::Serve | Self::AutoAnchor)
+    }
+}
+
+/// Action per situation (`clean` is always served).
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ReconcilePolicy {
+    pub unanchored_tail: ReconcileAction,
+    pub smt_behind_trusted: ReconcileAction,
+    pub db_tampered: ReconcileAction,
+    pub chain_ahead_of_local: ReconcileAction,
+}
+
+impl ReconcilePolicy {
+    /// Reads `RECONCILE_UNANCHORED_TAIL`, `RECONCILE_SMT_BEHIND_TRUSTED`, `RECONCILE_DB_TAMPERED`
+    /// and `RECONCILE_CHAIN_AHEAD_OF_LOCAL`.
+    pub fn from_config() -> anyhow::Result<Self> {
+        let action = |var: &str, default: &str| {
+            ReconcileAction::parse(var, &config::reconcile_action(var).unwrap_or(default.to_string()))
+        };
+        Ok(Self {
+            unanchored_tail: action("RECONCILE_UNANCHORED_TAIL", "auto_anchor")?,
+            smt_behind_trusted: action("RECONCILE_SMT_BEHIND_TRUSTED", "require_repair")?,
+            db_tampered: action("RECONCILE_DB_TAMPERED", "require_repair")?,
+            chain_ahead_of_local: action("RECONCILE_CHAIN_AHEAD_OF_LOCAL", "read_only")?,
+        })
+    }
+
+    pub fn action_for(&self, situation: RootSituation) -> ReconcileAction {
+        match situation {
+            RootSituation::Clean => ReconcileAction::Serve,
+            RootSituation::UnanchoredTail => self.unanchored_tail,
+            RootSituation::SmtBehindTrusted => self.smt_behind_trusted,
+            RootSituation::DbTampered => self.db_tampered,
+            RootSituation::ChainAheadOfLocal => self.chain_ahead_of_local,
+            // Raised while serving; anything but read-only would overwrite the other writer.
+            RootSituation::ForeignAnchorWrite => ReconcileAction::ReadOnly,
+        }
+    }
+}
+
+/// Outcome of startup reconciliation, or of a later chain-watch alarm (as returned by
+/// `GET /api/reconcile-status`).
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconcileReport {
+    pub situation: RootSituation,
+    pub action: ReconcileAction,
+    pub detail: String,
+    pub smt_root: String,
+    pub temporary_root: String,
+    pub main_root: String,
+    /// Root found on the anchor by the chain watcher (`foreign_anchor_write` only).
+    pub anchored_root: Option<String>,
+    pub writes_allowed: bool,
+    pub reconciled_at: DateTime<Utc>,
+    /// Set when an auto-anchor was attempted and failed (the commit is retried in the background).
+    pub error: Option<String>,
+    /// Operation that made the roots consistent again after startup, if any.
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// Classifies the startup roots.
+///
+/// The anchor history (when attached) tells apart roots this service produced from unknown ones.
+pub async fn classify(
+    root_manager: &RootManager,
+    smt_root: H256,
+) -> anyhow::Result<RootSituation> {
+    let temp_root = root_manager.get_temporary_root().await;
+    let main_root = root_manager.get_main_root().await;
+    let history = root_manager.history();
+
+    if smt_root == temp_root {
+        if temp_root == main_root {
+            return Ok(RootSituation::Clean);
+        }
+        // The anchor differs from our trusted root: either it lags (normal after a crash) or
+        // someone anchored a root we do not know, or one we produced after this trusted state.
+        if let Some(history) = &history {
+            let chain_ahead = match history.first_covering(main_root).await? {
+                Some(entry) => entry.last_seq as u64 > root_manager.update_seq().await,
+                None => history.max_seq().await? > 0,
+            };
+            if chain_ahead {
+                return Ok(RootSituation::ChainAheadOfLocal);
+            }
+        }
+        return Ok(RootSituation::UnanchoredTail);
+    }
+
+    let known = smt_root == main_root
+        || match &history {
+            Some(history) => history.first_covering(smt_root).await?.is_some(),
+            None => false,
+        };
+    Ok(if known {
+        RootSituation::SmtBehindTrusted
+    } else {
+        RootSituation::DbTampered
+    })
+}
+
+/// Classifies the startup roots and applies `policy`. Fails if the action is `refuse`.
+pub async fn reconcile(
+    root_manager: &RootManager,
+    smt_root: H256,
+    policy: &ReconcilePolicy,
+) -> anyhow::Result<ReconcileReport> {
+    let situation = classify(root_manager, smt_root).await?;
+    let action = policy.action_for(situation);
+    let temp_root = root_manager.get_temporary_root().await;
+    let main_root = root_manager.get_main_root().await;
+
+    println!(
+        "> Reconcile: {:?} -> {:?} (smt_root={} temporary_root={} main_root={})",
+        situation,
+        action,
+        hex::encode(smt_root.as_bytes()),
+        hex::encode(temp_root.as_bytes()),
+        hex::encode(main_root.as_bytes())
+    );
+
+    if action == ReconcileAction::Refuse {
+        return Err(anyhow::anyhow!(
+            "Startup reconciliation: {:?}. {} Refusing to start (policy: refuse).",
+            situation,
+            situation.describe()
+        ));
+    }
+
+    let mut error = None;
+    if action == ReconcileAction::AutoAnchor {
+        println!("> Reconcile: Anchoring temporary_root before serving...");
+        if let Err(e) = root_manager.commit_now().await {
+            eprintln!("> Reconcile: Auto-anchor failed (retried in the background): {}", e);
+            error = Some(e.to_string());
+        }
+    }
+
+    Ok(ReconcileReport {
+        situation,
+        action,
+        detail: situation.describe().to_string(),
+        smt_root: hex::encode(smt_root.as_bytes()),
+        temporary_root: hex::encode(temp_root.as_bytes()),
+        main_root: hex::encode(main_root.as_bytes()),
+        anchored_root: None,
+        writes_allowed: action.allows_writes(),
+        reconciled_at: Utc::now(),
+        error,
+        resolved_by: None,
+        resolved_at: None,
+    })
+}
+
+/// Shared handle holding the reconciliation outcome (`None` until reconciliation ran).
+#[derive(Clone, Default)]
+pub struct ReconcileTracker {
+    inner: Arc<std::sync::Mutex<Option<ReconcileReport>>>,
+}
+
+impl ReconcileTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> Option<ReconcileReport> {
+        self.inner.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, report: ReconcileReport) {
+        *self.inner.lock().unwrap() = Some(report);
+    }
+
+    /// Whether data writes are accepted (always, if reconciliation did not run).
+    pub fn writes_allowed(&self) -> bool {
+        self.inner.lock().unwrap().as_ref().is_none_or(|r| r.writes_allowed)
+    }
+
+    /// Records that `operation` (repair-roots, migrate, a reset) made the roots consistent.
+    pub fn resolve(&self, operation: &str) {
+        if let Some(report) = self.inner.lock().unwrap().as_mut() {
+            if !report.writes_allowed {
+                println!("> Reconcile: Roots made consistent by {}; writes re-enabled.", operation);
+                report.writes_allowed = true;
+                report.resolved_by = Some(operation.to_string());
+                report.resolved_at = Some(Utc::now());
+            }
+        }
+    }
+}
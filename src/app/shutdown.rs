@@ -0,0 +1,165 @@
+//! Graceful shutdown: stop taking writes, drain the ones in flight, then anchor and persist.
+//!
+//! Every mutating request holds a [`WriteGuard`] from the [`ShutdownGate`] while it changes the
+//! DB / SMT. On SIGTERM or SIGINT the gate closes, so new writes get `503`, and [`finalize`]
+//! waits (up to `SHUTDOWN_DRAIN_SECS`) for the guards to drop. Only then does it commit the
+//! pending root, flush the trusted state, write the final SMT checkpoint and release the
+//! single-instance advisory lock. Reads keep being served until the HTTP server stops.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, Notify};
+
+use crate::app::checkpoint;
+use crate::app::database_service::DatabaseService;
+use crate::domain::commitment::RootManager;
+
+#[derive(Default)]
+struct GateInner {
+    closed: AtomicBool,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+/// Admits mutating requests until shutdown begins, and tracks the ones in flight.
+#[derive(Clone, Default)]
+pub struct ShutdownGate {
+    inner: Arc<GateInner>,
+}
+
+/// Held by a mutating request while it runs; dropping it lets the drain finish.
+pub struct WriteGuard {
+    inner: Arc<GateInner>,
+}
+
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        if self.inner.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.inner.idle.notify_waiters();
+        }
+    }
+}
+
+impl ShutdownGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Admits a write, or `None` once shutdown has begun.
+    pub fn try_enter(&self) -> Option<WriteGuard> {
+        // Count first so a concurrent `drain` cannot miss a write that got past the check.
+        self.inner.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = WriteGuard {
+            inner: self.inner.clone(),
+        };
+        if self.inner.closed.load(Ordering::SeqCst) {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// Stops admitting writes.
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.inner.closed.load(Ordering::SeqCst)
+    }
+
+    /// Writes currently holding a guard.
+    pub fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Waits until no write is in flight. Returns `false` if `timeout` elapsed first.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        let wait = async {
+            loop {
+                let idle = self.inner.idle.notified();
+                tokio::pin!(idle);
+                idle.as_mut().enable();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.is_ok()
+    }
+}
+
+/// Resolves with the signal name on SIGTERM (Docker / Kubernetes stop) or SIGINT (Ctrl+C).
+pub async fn wait_for_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut term = signal(SignalKind::terminate()).expect("failed to install SIGTERM handler");
+        tokio::select! {
+            _ = term.recv() => "SIGTERM",
+            _ = tokio::signal::ctrl_c() => "SIGINT",
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "SIGINT"
+    }
+}
+
+/// Runs the shutdown sequence: close the gate, drain writes (up to `drain_timeout`), commit
+/// the pending root, flush the trusted state, write the final checkpoint and release the
+/// advisory lock. Later steps still run when an earlier one fails; the first error is returned.
+pub async fn finalize(
+    gate: &ShutdownGate,
+    root_manager: &Arc<RootManager>,
+    db_service: &Arc<Mutex<DatabaseService>>,
+    drain_timeout: Duration,
+) -> anyhow::Result<()> {
+    gate.close();
+    println!(
+        "> Shutdown: No longer accepting writes. Draining {} in-flight write(s) (up to {:?})...",
+        gate.in_flight(),
+        drain_timeout
+    );
+    if gate.drain(drain_timeout).await {
+        println!("> Shutdown: Writes drained.");
+    } else {
+        eprintln!(
+            "> Shutdown: Drain timed out with {} write(s) still in flight; anchoring what has been applied.",
+            gate.in_flight()
+        );
+    }
+
+    let mut first_error: Option<anyhow::Error> = None;
+
+    println!("> Shutdown: Committing pending temporary_root to the anchor...");
+    if let Err(e) = root_manager.commit_pending_root().await {
+        eprintln!("> Shutdown: Error committing pending root: {}", e);
+        first_error.get_or_insert(e);
+    }
+
+    if let Err(e) = root_manager.flush_trusted_state().await {
+        eprintln!("> Shutdown: Error flushing trusted state: {}", e);
+        first_error.get_or_insert(e);
+    }
+
+    println!("> Shutdown: Writing final SMT checkpoint...");
+    if let Err(e) = checkpoint::write_checkpoint_now(db_service, root_manager).await {
+        eprintln!("> Shutdown: Error writing SMT checkpoint: {}", e);
+        first_error.get_or_insert(e);
+    }
+
+    root_manager.shutdown();
+
+    if let Err(e) = db_service.lock().await.release_instance_lock().await {
+        eprintln!("> Shutdown: Error releasing the instance lock: {}", e);
+        first_error.get_or_insert(e);
+    }
+
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,101 @@
+// src/bin/anchor_indexer.rs
+//
+// Rebuilds the anchored-root timeline of one namespace from the Solana program's
+// `RootUpdated` events, read from an RPC endpoint or from a saved transactions file, and
+// writes it to a JSON file. Needs no database and no service: auditors can run it on their
+// own. Exits with 1 if the timeline has gaps or does not end at the on-chain root.
+
+use solana_program::pubkey::Pubkey;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use verifiable_memory_example::infra::config;
+use verifiable_memory_example::infra::solana::{self, indexer};
+
+fn usage_and_exit() -> ! {
+    eprintln!(
+        "Usage: cargo run --bin anchor_indexer -- [--fixture <transactions.json>] [--rpc-url <url>]\n\
+                [--namespace <ns>] [--out <timeline.json>] [--save-transactions <path>] [--limit <n>]\n\
+         \n\
+           --fixture            read transactions from a JSON file instead of the RPC endpoint\n\
+           --rpc-url            RPC endpoint (default SOLANA_RPC_URL)\n\
+           --namespace          root account to index (default SOLANA_NAMESPACE, then `default`)\n\
+           --out                where to write the timeline (default anchor_timeline.json)\n\
+           --save-transactions  also save the fetched transactions, for re-running with --fixture\n\
+           --limit              only fetch the newest <n> transactions (the timeline is then partial)\n\
+         \n\
+         Requires SOLANA_PROGRAM_ID.\n"
+    );
+    std::process::exit(2);
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv::dotenv().ok();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        usage_and_exit();
+    }
+    let arg = |flag: &str| -> Option<String> {
+        let i = args.iter().position(|a| a == flag)?;
+        Some(args.get(i + 1).cloned().unwrap_or_else(|| usage_and_exit()))
+    };
+    let fixture = arg("--fixture").map(PathBuf::from);
+    let namespace = arg("--namespace").unwrap_or_else(config::solana_namespace);
+    let out = PathBuf::from(arg("--out").unwrap_or_else(|| "anchor_timeline.json".to_string()));
+    let save_transactions = arg("--save-transactions").map(PathBuf::from);
+    let limit = match arg("--limit") {
+        Some(n) => Some(n.parse::<usize>().map_err(|_| anyhow::anyhow!("--limit must be a number"))?),
+        None => None,
+    };
+    let program_id = Pubkey::from_str(&config::solana_program_id())
+        .map_err(|e| anyhow::anyhow!("SOLANA_PROGRAM_ID is not a valid pubkey: {}", e))?;
+
+    println!("> Anchor indexer: program {}, namespace '{}'", program_id, namespace);
+    let (transactions, head) = match &fixture {
+        Some(path) => {
+            println!("> Reading transactions from {}...", path.display());
+            (indexer::load_transactions(path)?, None)
+        }
+        None => {
+            let rpc_url = arg("--rpc-url").unwrap_or_else(config::solana_rpc_url);
+            let client = solana::SolanaClient::new(&rpc_url)?;
+            let (address, _bump) = solana::get_merkle_root_account_pubkey(&namespace)?;
+            println!("> Fetching the transaction history of {} from {}...", address, rpc_url);
+            let transactions = indexer::fetch_transactions(&client, &address, limit).await?;
+            (transactions, Some(client.read_account(&namespace).await?))
+        }
+    };
+    println!("> {} transactions", transactions.len());
+    if let Some(path) = &save_transactions {
+        std::fs::write(path, serde_json::to_string_pretty(&transactions)?)?;
+        println!("> Saved the transactions to {}", path.display());
+    }
+
+    let mut timeline = indexer::AnchorTimeline::build(&program_id, &namespace, &transactions);
+    if let Some(account) = &head {
+        timeline.check_head(account);
+    }
+    std::fs::write(&out, serde_json::to_string_pretty(&timeline)?)?;
+    println!(
+        "> Wrote {} root updates to {}{}",
+        timeline.entries.len(),
+        out.display(),
+        timeline
+            .entries
+            .last()
+            .map(|e| format!(" (latest: update {}, root {})", e.sequence, e.new_root))
+            .unwrap_or_default()
+    );
+
+    if !timeline.is_complete() {
+        for issue in &timeline.issues {
+            eprintln!("  ✗ {}", issue);
+        }
+        eprintln!("> Timeline is INCOMPLETE ({} issues).", timeline.issues.len());
+        std::process::exit(1);
+    }
+    println!("> Timeline is complete and continuous.");
+    Ok(())
+}
@@ -1,10 +1,18 @@
 // src/bin/api_server.rs
 
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{Mutex, RwLock};
 use tower_http::cors::{Any, CorsLayer};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
+use verifiable_memory_example::app::chain_watch;
+use verifiable_memory_example::app::checkpoint;
+use verifiable_memory_example::app::reconcile::{self, ReconcilePolicy};
+use verifiable_memory_example::app::shutdown;
+use verifiable_memory_example::infra::config;
+use verifiable_memory_example::storage::anchor_history::AnchorHistory;
+use verifiable_memory_example::storage::anchor_jobs::AnchorJobs;
 use verifiable_memory_example::transport;
 use verifiable_memory_example::DatabaseService;
 use verifiable_memory_example::ModelRegistry;
@@ -19,30 +27,44 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // --- Root Manager Initialization ---
     println!("> Initializing RootManager...");
     let root_manager = Arc::new(RootManager::new().await?);
-    let batch_size = std::env::var("BATCH_COMMIT_SIZE")
-        .ok()
-        .and_then(|v| v.parse::<u64>().ok())
-        .unwrap_or(10);
+    let policy = root_manager.policy();
     root_manager.clone().start_background_commit_task();
-    println!("> RootManager initialized. Background commit task started (commits every {} updates).", batch_size);
+    match policy.max_age {
+        Some(max_age) => println!(
+            "> RootManager initialized. Background commit task started (commits every {} updates, or once a write is {}s old).",
+            policy.batch_size,
+            max_age.as_secs()
+        ),
+        None => println!(
+            "> RootManager initialized. Background commit task started (commits every {} updates).",
+            policy.batch_size
+        ),
+    }
 
     // --- Service Initialization ---
     println!("> Initializing DatabaseService...");
-    let db_service = DatabaseService::new().await?;
-    // Log root alignment at startup (helps debug verification issues)
-    if let Ok(smt_root) = db_service.current_smt_root().await {
-        let temp_root = root_manager.get_temporary_root().await;
-        let main_root = root_manager.get_main_root().await;
+    // Restore the SMT from the latest checkpoint when it matches the trusted root.
+    let db_service = DatabaseService::new_fast_start(root_manager.get_temporary_root().await).await?;
+    let pool = db_service.pool().clone();
+    root_manager
+        .attach_history(AnchorHistory::new(pool.clone()))
+        .await?;
+    println!("> Anchor history attached (GET /api/anchors).");
+    root_manager
+        .attach_jobs(AnchorJobs::new(pool.clone()))
+        .await?;
+    println!("> Anchor retry queue attached.");
+
+    // --- Startup reconciliation: classify smt_root / temporary_root / main_root and apply the policy ---
+    let reconcile_policy = ReconcilePolicy::from_config()?;
+    let smt_root = db_service.current_smt_root().await?;
+    let reconcile_report = reconcile::reconcile(&root_manager, smt_root, &reconcile_policy).await?;
+    if !reconcile_report.writes_allowed {
         println!(
-            "> Startup roots: smt_root={} temporary_root={} main_root={} match(smt==temp)={} match(smt==main)={}",
-            hex::encode(smt_root.as_bytes()),
-            hex::encode(temp_root.as_bytes()),
-            hex::encode(main_root.as_bytes()),
-            smt_root == temp_root,
-            smt_root == main_root
+            "> Serving READ-ONLY ({:?}): {} See GET /api/reconcile-status.",
+            reconcile_report.situation, reconcile_report.detail
         );
     }
-    let pool = db_service.pool().clone();
 
     // --- Optional: Warm-start model registry from DB (no schema change / no bootstrap needed) ---
     //
@@ -65,11 +87,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    let app_state = transport::http::AppState {
-        db_service: Arc::new(Mutex::new(db_service)),
+    let db_service = Arc::new(Mutex::new(db_service));
+    checkpoint::start_checkpoint_task(db_service.clone(), root_manager.clone());
+    let db_service_for_shutdown = db_service.clone();
+
+    let app_state = transport::http::AppState::new(
+        db_service,
         model_registry,
-        root_manager: root_manager.clone(),
-    };
+        root_manager.clone(),
+    );
+    app_state.reconcile.set(reconcile_report);
+    chain_watch::start_chain_watch(root_manager.clone(), app_state.reconcile.clone());
+    let shutdown_gate = app_state.shutdown.clone();
     println!("> DatabaseService initialized successfully.");
 
     // --- API Server Initialization ---
@@ -81,24 +110,44 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
     println!("> API server listening on http://0.0.0.0:3000");
     println!("> Swagger UI available at http://localhost:3000/swagger-ui");
-    println!("> Press Ctrl+C to gracefully shutdown and commit pending root to blockchain");
+    println!("> SIGTERM / Ctrl+C drains writes, commits the pending root to the anchor, then exits");
 
-    // Setup graceful shutdown handler
-    let root_manager_for_shutdown = root_manager.clone();
-    tokio::select! {
-        result = axum::serve(listener, app) => {
-            result?;
-        }
-        _ = tokio::signal::ctrl_c() => {
-            println!("\n> Shutdown signal received (Ctrl+C)...");
-            println!("> Committing pending temporary_root to blockchain...");
-            if let Err(e) = root_manager_for_shutdown.commit_pending_root().await {
-                eprintln!("> Error committing pending root during shutdown: {}", e);
-            }
-            root_manager_for_shutdown.shutdown();
-            println!("> Graceful shutdown complete.");
+    // Serve until SIGTERM/SIGINT. Axum then stops accepting connections and finishes the
+    // requests it has, while `shutdown::finalize` drains writes and anchors the pending root.
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let mut server = tokio::spawn(async move {
+        axum::serve(listener, app)
+            .with_graceful_shutdown(async {
+                let _ = stop_rx.await;
+            })
+            .await
+    });
+    let signal = tokio::select! {
+        result = &mut server => {
+            result??;
+            return Ok(());
         }
+        signal = shutdown::wait_for_signal() => signal,
+    };
+    println!("\n> Shutdown signal received ({})...", signal);
+    let _ = stop_tx.send(());
+
+    let drain_timeout = Duration::from_secs(config::shutdown_drain_secs());
+    if let Err(e) = shutdown::finalize(
+        &shutdown_gate,
+        &root_manager,
+        &db_service_for_shutdown,
+        drain_timeout,
+    )
+    .await
+    {
+        eprintln!("> Shutdown finished with errors: {}", e);
+    }
+    // Remaining (read) requests get the same grace period before the process exits.
+    if tokio::time::timeout(drain_timeout, server).await.is_err() {
+        eprintln!("> Shutdown: Open connections did not close within {:?}; exiting.", drain_timeout);
     }
+    println!("> Graceful shutdown complete.");
 
     Ok(())
 }
@@ -0,0 +1,16 @@
+// src/bin/evm_standin.rs
+//
+// Local JSON-RPC stand-in for an EVM node, used as an `evm=` anchor target (see README
+// "Multiple anchors"). Listens on EVM_STANDIN_ADDR (default 127.0.0.1:8545).
+
+use verifiable_memory_example::infra::anchor::evm_standin;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv::dotenv().ok();
+    let addr = std::env::var("EVM_STANDIN_ADDR").unwrap_or_else(|_| "127.0.0.1:8545".to_string());
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    println!("> EVM stand-in listening on http://{} (state is in memory)", addr);
+    axum::serve(listener, evm_standin::router()).await?;
+    Ok(())
+}
@@ -1,21 +1,29 @@
-use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_program::pubkey::Pubkey;
-use solana_sdk::commitment_config::CommitmentConfig;
-use solana_sdk::signer::keypair::read_keypair_file;
-use solana_sdk::signer::Signer;
 use std::str::FromStr;
 
+use verifiable_memory_example::infra::anchor;
 use verifiable_memory_example::infra::config;
 use verifiable_memory_example::infra::solana;
 
 fn usage_and_exit() -> ! {
     eprintln!(
         "Usage: cargo run --bin preflight -- [--init-pda-if-missing]\n\
+                [--propose-authority <pubkey> | --accept-authority | --set-authority <pubkey>]\n\
+         \n\
+         The authority flags manage who may update the on-chain root (Solana only):\n\
+           --propose-authority  current authority (the payer) proposes a new authority\n\
+           --accept-authority   the payer, as the proposed authority, takes over\n\
+           --set-authority      current authority hands over in one step (no confirmation)\n\
          \n\
          Requires env vars:\n\
            DATABASE_URL, SOLANA_RPC_URL, SOLANA_PROGRAM_ID, BATCH_COMMIT_SIZE\n\
-         And Solana payer key:\n\
-           ~/.config/solana/id.json\n"
+         Optional: SOLANA_NAMESPACE (root account to use, default `default`)\n\
+         And the Solana payer key, from SOLANA_SIGNER:\n\
+           file (default)  keypair file at SOLANA_KEYPAIR_PATH (default ~/.config/solana/id.json)\n\
+           env             base58 keypair in SOLANA_SIGNER_SECRET\n\
+           remote          external signer on the Unix socket SOLANA_SIGNER_SOCKET\n\
+         \n\
+         With ANCHOR_BACKEND=file|memory only DATABASE_URL and BATCH_COMMIT_SIZE are required.\n"
     );
     std::process::exit(2);
 }
@@ -30,24 +38,77 @@ async fn main() -> anyhow::Result<()> {
     }
 
     let init_pda_if_missing = args.iter().any(|a| a == "--init-pda-if-missing");
+    let accept_authority = args.iter().any(|a| a == "--accept-authority");
+    let pubkey_arg = |flag: &str| -> anyhow::Result<Option<Pubkey>> {
+        match args.iter().position(|a| a == flag) {
+            None => Ok(None),
+            Some(i) => {
+                let value = args.get(i + 1).unwrap_or_else(|| usage_and_exit());
+                Pubkey::from_str(value)
+                    .map(Some)
+                    .map_err(|e| anyhow::anyhow!("{} is not a valid pubkey: {}", flag, e))
+            }
+        }
+    };
+    let propose_authority = pubkey_arg("--propose-authority")?;
+    let set_authority = pubkey_arg("--set-authority")?;
+    let authority_actions =
+        accept_authority as usize + propose_authority.is_some() as usize + set_authority.is_some() as usize;
+    if authority_actions > 1 {
+        usage_and_exit();
+    }
+
+    let anchor = anchor::from_config()?;
+    if anchor.name() != "solana" {
+        if authority_actions > 0 {
+            return Err(anyhow::anyhow!(
+                "Authority flags need ANCHOR_BACKEND=solana (got {})",
+                anchor.name()
+            ));
+        }
+        let _ = config::database_url();
+        let batch = config::batch_commit_size();
+        println!("> Preflight:");
+        println!("  ANCHOR_BACKEND={}", anchor.name());
+        println!("  BATCH_COMMIT_SIZE={}", batch);
+        if init_pda_if_missing {
+            anchor.initialize().await?;
+        }
+        let root = anchor.read_root().await?;
+        println!("  Root is readable from anchor (ok). Root bytes[0..4]={:02x?}", &root.as_bytes()[0..4]);
+        println!("> Preflight OK.");
+        return Ok(());
+    }
 
     // Force-read config (nice error messages if missing)
     let rpc_url = config::solana_rpc_url();
     let program_id_str = config::solana_program_id();
+    let namespace = config::solana_namespace();
     let _ = config::database_url();
     let batch = config::batch_commit_size();
 
     println!("> Preflight:");
     println!("  SOLANA_RPC_URL={}", rpc_url);
     println!("  SOLANA_PROGRAM_ID={}", program_id_str);
+    println!("  SOLANA_NAMESPACE={}", namespace);
     println!("  BATCH_COMMIT_SIZE={}", batch);
 
-    // Same payer location the service uses.
-    let payer_path = shellexpand::tilde("~/.config/solana/id.json").to_string();
-    let payer =
-        read_keypair_file(&payer_path).map_err(|e| anyhow::anyhow!("Failed to read {}: {}", payer_path, e))?;
+    // One client for every check, with the signer and transaction settings the service uses.
+    let chain = solana::SolanaClient::new(&rpc_url)?;
+    let payer = chain.payer().await?;
+    println!("  SOLANA_SIGNER={}", payer.name());
+
+    let tx_options = chain.options();
+    println!(
+        "  Transactions: commitment {:?}, priority fee {:?}, compute unit limit {:?}, {} resubmits, cluster {}",
+        tx_options.commitment.commitment,
+        tx_options.priority_fee,
+        tx_options.compute_unit_limit,
+        tx_options.resubmits,
+        tx_options.cluster
+    );
 
-    let client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+    let client = chain.rpc();
 
     // Basic RPC connectivity
     let version = client.get_version().await?;
@@ -76,15 +137,23 @@ async fn main() -> anyhow::Result<()> {
     }
 
     // PDA existence
-    let (pda, _bump) = Pubkey::find_program_address(&[b"merkle_root_account"], &program_id);
+    let (pda, _bump) = solana::get_merkle_root_account_pubkey(&namespace)?;
     println!("  Merkle root PDA: {}", pda);
 
-    let pda_exists = client.get_account(&pda).await.is_ok();
+    let pda_exists = client
+        .get_account_with_commitment(&pda, client.commitment())
+        .await?
+        .value
+        .is_some();
     if pda_exists {
         println!("  PDA account exists.");
+        // Refuse an account another authority controls before sending anything to it.
+        if !accept_authority {
+            chain.read_account(&namespace).await?.ensure_authority(&payer.pubkey())?;
+        }
     } else if init_pda_if_missing {
         println!("  PDA missing -> initializing on-chain merkle root account...");
-        solana::initialize().await?;
+        anchor.initialize().await?;
         // Recheck
         client
             .get_account(&pda)
@@ -97,8 +166,46 @@ async fn main() -> anyhow::Result<()> {
         ));
     }
 
+    // Authority changes requested on the command line
+    if let Some(new_authority) = propose_authority {
+        let signature = chain.propose_authority(&namespace, &new_authority).await?;
+        println!("  Proposed {} as the new authority ({}).", new_authority, signature);
+        println!("  It takes over once it runs: preflight --accept-authority");
+    } else if let Some(new_authority) = set_authority {
+        let signature = chain.set_authority(&namespace, &new_authority).await?;
+        println!("  Authority set to {} ({}).", new_authority, signature);
+    } else if accept_authority {
+        let signature = chain.accept_authority(&namespace).await?;
+        println!("  Payer accepted the authority ({}).", signature);
+    }
+
+    // Only the authority may update the root
+    let account = chain.read_account(&namespace).await?;
+    println!("  Authority: {}", account.authority);
+    println!("  Update sequence: {}", account.sequence);
+    if let Some(pending) = account.pending_authority {
+        println!("  Pending authority: {}", pending);
+    }
+    account.ensure_authority(&payer.pubkey())?;
+    println!("  Payer is the root authority (ok).");
+
+    // Root history ring buffer
+    let history = chain.read_history(&namespace).await?;
+    println!(
+        "  Root history: {} of {} entries (oldest sequence {:?}).",
+        history.entries.len(),
+        solana::ROOT_HISTORY_LEN,
+        history.oldest_sequence()
+    );
+    if history.entries.last().map(|e| (e.sequence, e.root)) != Some((account.sequence, account.root)) {
+        return Err(anyhow::anyhow!(
+            "Root history does not end with the current root (sequence {})",
+            account.sequence
+        ));
+    }
+
     // Root readable
-    let root = solana::read_root().await?;
+    let root = anchor.read_root().await?;
     println!("  Root is readable from chain (ok). Root bytes[0..4]={:02x?}", &root.as_bytes()[0..4]);
 
     println!("> Preflight OK.");
@@ -0,0 +1,352 @@
+//! Cap on the lamports spent anchoring, and cost forecasts for commit policies.
+//!
+//! The cap is off unless `ANCHOR_BUDGET_LAMPORTS` is set. Spend is the sum of the fees recorded
+//! with each anchor (see `storage::anchor_history`) in the current calendar
+//! `ANCHOR_BUDGET_PERIOD` (UTC hour, day, week or month). Once it reaches the cap,
+//! `ANCHOR_BUDGET_ACTION` applies until the period rolls over:
+//! - `widen` (default): background commits use a batch size and max age
+//!   `ANCHOR_BUDGET_WIDEN_FACTOR` times larger, so fewer anchors cover the same writes;
+//! - `refuse`: background and on-demand commits are refused. Forced, reset and shutdown
+//!   commits still go through, since they keep the anchor in step with the database.
+//!
+//! Either way an alert is logged once per period.
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, TimeZone, Timelike, Utc};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::domain::commitment::policy::{CommitPolicy, CommitReason};
+use crate::infra::config;
+
+/// Calendar period the budget applies to (UTC).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl BudgetPeriod {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "hour" => Some(BudgetPeriod::Hour),
+            "day" => Some(BudgetPeriod::Day),
+            "week" => Some(BudgetPeriod::Week),
+            "month" => Some(BudgetPeriod::Month),
+            _ => None,
+        }
+    }
+
+    /// The period name, which is also its Postgres `date_trunc` unit.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Hour => "hour",
+            BudgetPeriod::Day => "day",
+            BudgetPeriod::Week => "week",
+            BudgetPeriod::Month => "month",
+        }
+    }
+
+    /// Start of the period containing `at` (weeks start on Monday, like `date_trunc`).
+    pub fn start_of(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let day = Utc
+            .with_ymd_and_hms(at.year(), at.month(), at.day(), 0, 0, 0)
+            .unwrap();
+        match self {
+            BudgetPeriod::Hour => day + ChronoDuration::hours(at.hour() as i64),
+            BudgetPeriod::Day => day,
+            BudgetPeriod::Week => day - ChronoDuration::days(at.weekday().num_days_from_monday() as i64),
+            BudgetPeriod::Month => Utc.with_ymd_and_hms(at.year(), at.month(), 1, 0, 0, 0).unwrap(),
+        }
+    }
+
+    /// Nominal length in seconds (a month counts as 30 days), for forecasts.
+    pub fn secs(&self) -> f64 {
+        match self {
+            BudgetPeriod::Hour => 3_600.0,
+            BudgetPeriod::Day => 86_400.0,
+            BudgetPeriod::Week => 7.0 * 86_400.0,
+            BudgetPeriod::Month => 30.0 * 86_400.0,
+        }
+    }
+}
+
+/// What happens once the budget of the current period is spent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetAction {
+    /// Anchor less often (batch size and max age multiplied by `widen_factor`).
+    Widen,
+    /// Stop anchoring, except for forced, reset and shutdown commits.
+    Refuse,
+}
+
+/// The configured anchoring budget.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BudgetPolicy {
+    pub limit_lamports: u64,
+    pub period: BudgetPeriod,
+    pub action: BudgetAction,
+    pub widen_factor: u64,
+}
+
+impl BudgetPolicy {
+    /// Reads `ANCHOR_BUDGET_LAMPORTS`, `ANCHOR_BUDGET_PERIOD`, `ANCHOR_BUDGET_ACTION` and
+    /// `ANCHOR_BUDGET_WIDEN_FACTOR`. `None` when no budget is set.
+    pub fn from_config() -> anyhow::Result<Option<Self>> {
+        let Some(limit_lamports) = config::anchor_budget_lamports()? else {
+            return Ok(None);
+        };
+        let period = config::anchor_budget_period();
+        let period = BudgetPeriod::parse(&period).ok_or_else(|| {
+            anyhow::anyhow!("ANCHOR_BUDGET_PERIOD must be hour, day, week or month (got {:?})", period)
+        })?;
+        let action = match config::anchor_budget_action().as_str() {
+            "widen" => BudgetAction::Widen,
+            "refuse" => BudgetAction::Refuse,
+            other => {
+                return Err(anyhow::anyhow!(
+                    "ANCHOR_BUDGET_ACTION must be widen or refuse (got {:?})",
+                    other
+                ))
+            }
+        };
+        Ok(Some(Self {
+            limit_lamports,
+            period,
+            action,
+            widen_factor: config::anchor_budget_widen_factor(),
+        }))
+    }
+
+    /// `policy` with its thresholds multiplied by `widen_factor`.
+    pub fn widen(&self, policy: CommitPolicy) -> CommitPolicy {
+        let factor = self.widen_factor.max(1);
+        CommitPolicy {
+            batch_size: policy.batch_size.saturating_mul(factor),
+            max_age: policy.max_age.map(|d| d.saturating_mul(factor.min(u32::MAX as u64) as u32)),
+        }
+    }
+
+    /// Whether a commit for `reason` may go ahead once the budget is spent.
+    pub fn allows(&self, reason: CommitReason) -> bool {
+        match self.action {
+            BudgetAction::Widen => true,
+            BudgetAction::Refuse => matches!(
+                reason,
+                CommitReason::Forced | CommitReason::Reset | CommitReason::Shutdown
+            ),
+        }
+    }
+}
+
+/// A commit refused because the anchoring budget of the current period is spent.
+#[derive(Debug, Clone, Copy)]
+pub struct BudgetExceeded {
+    pub spent_lamports: u64,
+    pub limit_lamports: u64,
+    pub period: BudgetPeriod,
+    /// When the next period starts and anchoring resumes.
+    pub resumes_at: DateTime<Utc>,
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Anchoring budget exceeded: {} of {} lamports spent this {}; anchoring resumes at {}",
+            self.spent_lamports,
+            self.limit_lamports,
+            self.period.as_str(),
+            self.resumes_at.to_rfc3339()
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// Budget and spend of the current period (`GET /api/commit-status`, `GET /api/anchors/costs`).
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    #[serde(flatten)]
+    pub policy: BudgetPolicy,
+    pub period_start: DateTime<Utc>,
+    pub spent_lamports: u64,
+    pub remaining_lamports: u64,
+    pub exceeded: bool,
+    /// The commit policy in force (widened while the budget is exceeded).
+    pub effective_policy: CommitPolicy,
+}
+
+struct PeriodSpend {
+    start: DateTime<Utc>,
+    spent: u64,
+    alerted: bool,
+}
+
+/// Spend of the current period against a `BudgetPolicy` (held by `RootManager`).
+pub struct BudgetTracker {
+    policy: BudgetPolicy,
+    spend: Mutex<PeriodSpend>,
+}
+
+impl BudgetTracker {
+    pub fn new(policy: BudgetPolicy) -> Self {
+        Self {
+            spend: Mutex::new(PeriodSpend {
+                start: policy.period.start_of(Utc::now()),
+                spent: 0,
+                alerted: false,
+            }),
+            policy,
+        }
+    }
+
+    pub fn policy(&self) -> BudgetPolicy {
+        self.policy
+    }
+
+    /// Sets the spend of the period starting at `start` (from the anchor history at startup).
+    pub fn seed(&self, start: DateTime<Utc>, spent: u64) {
+        let mut spend = self.spend.lock().unwrap();
+        *spend = PeriodSpend {
+            start,
+            spent,
+            alerted: false,
+        };
+        self.check(&mut spend);
+    }
+
+    /// Adds the fee of an anchor confirmed at `at`.
+    pub fn record(&self, fee_lamports: u64, at: DateTime<Utc>) {
+        let mut spend = self.spend.lock().unwrap();
+        self.roll(&mut spend, at);
+        spend.spent = spend.spent.saturating_add(fee_lamports);
+        self.check(&mut spend);
+    }
+
+    /// Whether the budget of the current period is spent.
+    pub fn exceeded(&self) -> bool {
+        let mut spend = self.spend.lock().unwrap();
+        self.roll(&mut spend, Utc::now());
+        spend.spent >= self.policy.limit_lamports
+    }
+
+    /// The commit policy in force: `policy`, widened while the budget is exceeded.
+    pub fn effective_policy(&self, policy: CommitPolicy) -> CommitPolicy {
+        if self.policy.action == BudgetAction::Widen && self.exceeded() {
+            self.policy.widen(policy)
+        } else {
+            policy
+        }
+    }
+
+    /// Fails with `BudgetExceeded` if a commit for `reason` is not allowed right now.
+    pub fn check_commit(&self, reason: CommitReason) -> Result<(), BudgetExceeded> {
+        if self.policy.allows(reason) || !self.exceeded() {
+            return Ok(());
+        }
+        let spend = self.spend.lock().unwrap();
+        Err(BudgetExceeded {
+            spent_lamports: spend.spent,
+            limit_lamports: self.policy.limit_lamports,
+            period: self.policy.period,
+            resumes_at: self.next_period(spend.start),
+        })
+    }
+
+    pub fn status(&self, policy: CommitPolicy) -> BudgetStatus {
+        let effective_policy = self.effective_policy(policy);
+        let spend = self.spend.lock().unwrap();
+        BudgetStatus {
+            policy: self.policy,
+            period_start: spend.start,
+            spent_lamports: spend.spent,
+            remaining_lamports: self.policy.limit_lamports.saturating_sub(spend.spent),
+            exceeded: spend.spent >= self.policy.limit_lamports,
+            effective_policy,
+        }
+    }
+
+    /// Starts a new period if `at` is past the current one.
+    fn roll(&self, spend: &mut PeriodSpend, at: DateTime<Utc>) {
+        let start = self.policy.period.start_of(at);
+        if start > spend.start {
+            *spend = PeriodSpend {
+                start,
+                spent: 0,
+                alerted: false,
+            };
+        }
+    }
+
+    /// Alerts (once per period) when the spend reaches the limit.
+    fn check(&self, spend: &mut PeriodSpend) {
+        if spend.alerted || spend.spent < self.policy.limit_lamports {
+            return;
+        }
+        spend.alerted = true;
+        let consequence = match self.policy.action {
+            BudgetAction::Widen => format!(
+                "widening the commit policy {}x",
+                self.policy.widen_factor
+            ),
+            BudgetAction::Refuse => "refusing background and on-demand commits".to_string(),
+        };
+        eprintln!(
+            "> RootManager: ALERT: anchoring budget exceeded ({} of {} lamports spent this {}); {} until {}",
+            spend.spent,
+            self.policy.limit_lamports,
+            self.policy.period.as_str(),
+            consequence,
+            self.next_period(spend.start).to_rfc3339()
+        );
+    }
+
+    fn next_period(&self, start: DateTime<Utc>) -> DateTime<Utc> {
+        // One and a half nominal lengths past the start is always inside the next period,
+        // whatever the length of the month.
+        let probe = start + ChronoDuration::seconds((self.policy.period.secs() * 1.5) as i64);
+        self.policy.period.start_of(probe)
+    }
+}
+
+/// Expected anchoring cost of one commit policy.
+#[derive(Debug, Clone, Serialize)]
+pub struct CostForecast {
+    pub batch_size: u64,
+    /// Updates per anchor once `max_age` is taken into account.
+    pub updates_per_anchor: f64,
+    pub anchors_per_day: f64,
+    pub lamports_per_day: f64,
+    pub lamports_per_update: f64,
+}
+
+/// Forecasts the cost of anchoring `updates_per_sec` writes with `batch_size` and `max_age`,
+/// at `fee_per_anchor` lamports per anchor.
+///
+/// A batch closes after `batch_size` updates or, when fewer arrive within `max_age`, after
+/// `max_age` with however many came in (at least one; no anchor is made without updates).
+pub fn forecast(
+    fee_per_anchor: f64,
+    updates_per_sec: f64,
+    batch_size: u64,
+    max_age: Option<Duration>,
+) -> CostForecast {
+    let batch = batch_size.max(1) as f64;
+    let updates_per_anchor = match max_age {
+        Some(max_age) => batch.min((updates_per_sec * max_age.as_secs_f64()).max(1.0)),
+        None => batch,
+    };
+    let anchors_per_day = updates_per_sec * 86_400.0 / updates_per_anchor;
+    CostForecast {
+        batch_size,
+        updates_per_anchor,
+        anchors_per_day,
+        lamports_per_day: anchors_per_day * fee_per_anchor,
+        lamports_per_update: fee_per_anchor / updates_per_anchor,
+    }
+}
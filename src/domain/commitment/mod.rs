@@ -1,4 +1,9 @@
+pub mod budget;
+pub mod policy;
 pub mod root_manager;
 
-pub use root_manager::RootManager;
-
+pub use budget::{BudgetAction, BudgetExceeded, BudgetPeriod, BudgetPolicy, BudgetStatus};
+pub use policy::{CommitPolicy, CommitReason, RetryPolicy};
+pub use root_manager::{
+    AnchorHealth, AnchorProgress, AnchorState, CommitStatus, RootManager, RootUpdate,
+};
@@ -0,0 +1,138 @@
+//! When pending temporary_root updates get anchored.
+//!
+//! A commit is due when either rule fires:
+//! - **count**: `BATCH_COMMIT_SIZE` updates have accumulated since the last anchor;
+//! - **max-age** (opt-in): the oldest un-anchored update is `COMMIT_MAX_AGE_SECS` old, so a
+//!   quiet deployment cannot leave a write un-anchored indefinitely.
+//!
+//! Commits can also be requested explicitly (`RootManager::commit_now`).
+//!
+//! A failed commit is retried by the background task after an exponential backoff with jitter
+//! (`RetryPolicy`). After `ANCHOR_BREAKER_THRESHOLD` consecutive failures the circuit breaker
+//! opens and the service reports itself degraded until an anchor succeeds again.
+
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::infra::config;
+
+/// Why a commit was made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitReason {
+    /// `batch_size` updates accumulated.
+    BatchSize,
+    /// The oldest pending update reached `max_age`.
+    MaxAge,
+    /// Requested via `RootManager::commit_now` (e.g. `POST /api/commit`).
+    OnDemand,
+    /// Final commit on graceful shutdown.
+    Shutdown,
+    /// Root replaced by a rebuild (migrate / repair-roots).
+    Forced,
+    /// Roots reset (clear-data / schema reset).
+    Reset,
+    /// Background retry of a failed commit.
+    Retry,
+}
+
+impl CommitReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommitReason::BatchSize => "batch_size",
+            CommitReason::MaxAge => "max_age",
+            CommitReason::OnDemand => "on_demand",
+            CommitReason::Shutdown => "shutdown",
+            CommitReason::Forced => "forced",
+            CommitReason::Reset => "reset",
+            CommitReason::Retry => "retry",
+        }
+    }
+}
+
+/// Thresholds that make pending updates due for anchoring.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CommitPolicy {
+    /// Pending updates that trigger a commit.
+    pub batch_size: u64,
+    /// Maximum age of the oldest pending update (`None` = no time-based commits).
+    #[serde(rename = "max_age_secs", serialize_with = "serialize_secs")]
+    pub max_age: Option<Duration>,
+}
+
+impl CommitPolicy {
+    /// Reads `BATCH_COMMIT_SIZE` and `COMMIT_MAX_AGE_SECS`.
+    pub fn from_config() -> Self {
+        let max_age_secs = config::commit_max_age_secs();
+        Self {
+            batch_size: config::batch_commit_size(),
+            max_age: (max_age_secs > 0).then(|| Duration::from_secs(max_age_secs)),
+        }
+    }
+
+    /// Returns the rule that makes a commit due, if any.
+    ///
+    /// `oldest_pending_age` is `None` when nothing is waiting to be anchored.
+    pub fn evaluate(&self, pending: u64, oldest_pending_age: Option<Duration>) -> Option<CommitReason> {
+        let age = oldest_pending_age?;
+        if pending >= self.batch_size {
+            return Some(CommitReason::BatchSize);
+        }
+        match self.max_age {
+            Some(max_age) if age >= max_age => Some(CommitReason::MaxAge),
+            _ => None,
+        }
+    }
+}
+
+/// Backoff and circuit breaker for failed commits.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RetryPolicy {
+    /// Delay before the first retry; doubles with every further failure.
+    #[serde(rename = "base_delay_ms", serialize_with = "serialize_millis")]
+    pub base_delay: Duration,
+    /// Upper bound of the delay.
+    #[serde(rename = "max_delay_ms", serialize_with = "serialize_millis")]
+    pub max_delay: Duration,
+    /// Consecutive failures that open the circuit breaker (service degraded).
+    pub breaker_threshold: u32,
+}
+
+impl RetryPolicy {
+    /// Reads `ANCHOR_RETRY_BASE_MS`, `ANCHOR_RETRY_MAX_MS` and `ANCHOR_BREAKER_THRESHOLD`.
+    pub fn from_config() -> Self {
+        let base_delay = Duration::from_millis(config::anchor_retry_base_ms());
+        Self {
+            base_delay,
+            max_delay: Duration::from_millis(config::anchor_retry_max_ms()).max(base_delay),
+            breaker_threshold: config::anchor_breaker_threshold(),
+        }
+    }
+
+    /// Delay before retrying after `failures` consecutive failures (>= 1).
+    ///
+    /// Exponential, capped at `max_delay`, with "equal jitter": a random point in the upper half
+    /// of the window, so retries of several instances do not line up.
+    pub fn backoff(&self, failures: u32) -> Duration {
+        let exp = failures.saturating_sub(1).min(20);
+        let window = self.base_delay.saturating_mul(1 << exp).min(self.max_delay);
+        let half = window / 2;
+        half + half.mul_f64(rand::random::<f64>())
+    }
+
+    /// Whether `failures` consecutive failures open the circuit breaker.
+    pub fn breaker_open(&self, failures: u32) -> bool {
+        failures >= self.breaker_threshold
+    }
+}
+
+fn serialize_millis<S: serde::Serializer>(v: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u64(v.as_millis() as u64)
+}
+
+fn serialize_secs<S: serde::Serializer>(v: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+    match v {
+        Some(d) => s.serialize_some(&d.as_secs()),
+        None => s.serialize_none(),
+    }
+}
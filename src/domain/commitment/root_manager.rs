@@ -1,11 +1,33 @@
 //! Manages the dual-root system: main_root (on-chain) and temporary_root (in-memory).
 //!
 //! The temporary_root is updated on every write operation, while the main_root
-//! is committed to the blockchain periodically (configurable via BATCH_COMMIT_SIZE env var, default: 10)
-//! to reduce costs and latency.
+//! is committed to the blockchain according to the `CommitPolicy` (every `BATCH_COMMIT_SIZE`
+//! updates, or once the oldest pending update is `COMMIT_MAX_AGE_SECS` old) to reduce costs
+//! and latency.
+//!
+//! Anchoring is pipelined: a commit snapshots the root being anchored and releases the root
+//! lock before talking to the chain, so writes keep advancing temporary_root while the
+//! transaction confirms. Every update gets a sequence number; callers that need their update
+//! anchored wait on `wait_for_anchor(seq)`, which is driven by a `watch` channel.
+//!
+//! `trusted_state.json` is sealed with a MAC keyed by the `infra::sealing` key provider, and
+//! its update sequence number doubles as a monotonic counter: a file that fails the MAC, or
+//! whose counter is behind the anchored sequence (an older copy restored by the host), is
+//! refused at startup. For anchors that count their own writes (the Solana account sequence,
+//! the transparency log head), the file also seals how many writes this service may have made;
+//! it is raised before every write, and a file the anchor is ahead of is refused as well. That
+//! check does not depend on `anchor_history`, which lives in the host's database.
+//!
+//! With an anchoring budget configured (`budget`), the fee of every anchor counts against the
+//! current period; once it is spent, commits are widened or refused as configured.
 
-use crate::infra::solana;
-use crate::infra::config;
+use crate::domain::commitment::budget::{BudgetAction, BudgetPolicy, BudgetStatus, BudgetTracker};
+use crate::domain::commitment::policy::{CommitPolicy, CommitReason, RetryPolicy};
+use crate::infra::anchor::{self, AnchorReceipt, ChargedFee, RootAnchor, StaleAnchor};
+use crate::infra::sealing;
+use crate::storage::anchor_history::{AnchorHistory, AnchorRecord};
+use crate::storage::anchor_jobs::{AnchorJob, AnchorJobs};
+use chrono::{DateTime, Utc};
 use hex;
 use primitive_types::H256;
 use serde::{Deserialize, Serialize};
@@ -13,7 +35,7 @@ use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
-use tokio::sync::Mutex;
+use tokio::sync::{watch, Mutex, MutexGuard};
 use tokio::time::{interval, Duration};
 
 /// Structure for the trusted state file
@@ -21,6 +43,156 @@ use tokio::time::{interval, Duration};
 struct TrustedState {
     root: String, // Hex encoded root
     timestamp: u64,
+    /// Sequence number of the update that produced `root` (absent in older files).
+    #[serde(default)]
+    seq: u64,
+    /// Hex MAC over root, seq, timestamp and anchor_seq (absent in files written before
+    /// sealing).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mac: Option<String>,
+    /// Upper bound of the anchor's own write sequence (see `RootManager::anchor_seq`); absent
+    /// for anchors that do not count writes, and in older files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    anchor_seq: Option<u64>,
+}
+
+const TRUSTED_STATE_MAGIC: &[u8; 8] = b"VMTRUST1";
+
+/// Bytes covered by the trusted state MAC. `anchor_seq` is only appended when present, so
+/// files sealed before it existed still verify.
+fn trusted_state_mac_input(root: H256, seq: u64, timestamp: u64, anchor_seq: Option<u64>) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(TRUSTED_STATE_MAGIC.len() + 56);
+    buf.extend_from_slice(TRUSTED_STATE_MAGIC);
+    buf.extend_from_slice(root.as_bytes());
+    buf.extend_from_slice(&seq.to_le_bytes());
+    buf.extend_from_slice(&timestamp.to_le_bytes());
+    if let Some(anchor_seq) = anchor_seq {
+        buf.extend_from_slice(&anchor_seq.to_le_bytes());
+    }
+    buf
+}
+
+/// Contents of a trusted state file that parsed and (if sealed) verified.
+struct LoadedState {
+    root: H256,
+    seq: u64,
+    anchor_seq: Option<u64>,
+    sealed: bool,
+}
+
+/// temporary_root updates that have not been anchored yet.
+#[derive(Default)]
+struct PendingUpdates {
+    /// Sequence number of the latest temporary_root update (never reset).
+    seq: u64,
+    /// Updates made since the last anchor snapshot.
+    count: u64,
+    /// When the oldest of those updates was made.
+    since: Option<Instant>,
+    /// `(seq, root)` of those updates, recorded in `anchor_covered_roots` once anchored.
+    roots: Vec<(u64, H256)>,
+    /// Snapshot currently being anchored, if any.
+    in_flight: Option<InFlight>,
+}
+
+/// A root snapshot handed to the anchor, with the pending updates it covers.
+#[derive(Clone)]
+struct InFlight {
+    root: H256,
+    seq: u64,
+    count: u64,
+    since: Option<Instant>,
+    roots: Vec<(u64, H256)>,
+}
+
+/// Result of a temporary_root update.
+#[derive(Debug, Clone, Copy)]
+pub struct RootUpdate {
+    /// Sequence number of this update; pass it to `wait_for_anchor`.
+    pub seq: u64,
+    /// Whether this update made a commit due under the commit policy.
+    pub commit_due: bool,
+}
+
+/// Anchoring progress, published on every anchor attempt.
+#[derive(Debug, Clone, Default)]
+pub struct AnchorProgress {
+    /// Highest update sequence number covered by an anchored root.
+    pub anchored_seq: u64,
+    pub anchored_root: H256,
+    /// Highest update sequence number covered by a failed attempt.
+    pub failed_seq: u64,
+    pub last_error: Option<String>,
+}
+
+/// Consecutive commit failures and when the background task may retry.
+#[derive(Default)]
+struct RetryState {
+    failures: u32,
+    next_attempt_at: Option<Instant>,
+    last_error: Option<String>,
+    last_failure_at: Option<DateTime<Utc>>,
+    /// When the current run of failures started (`created_at` of the queued job).
+    first_failure_at: Option<DateTime<Utc>>,
+}
+
+/// Overall anchoring condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnchorState {
+    /// The last commit succeeded (or none failed yet).
+    Healthy,
+    /// Commits are failing and being retried with backoff.
+    Retrying,
+    /// The circuit breaker is open: `breaker_threshold` consecutive commits failed.
+    Degraded,
+}
+
+/// Anchoring health exposed through `GET /health`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnchorHealth {
+    pub state: AnchorState,
+    pub consecutive_failures: u32,
+    pub breaker_threshold: u32,
+    /// Seconds until the background task retries (0 when a retry is due now).
+    pub next_retry_in_secs: Option<f64>,
+    pub last_error: Option<String>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+    /// Updates not yet anchored (including any in flight).
+    pub pending_updates: u64,
+    pub oldest_pending_age_secs: Option<f64>,
+}
+
+/// The most recent successful anchor made by this process.
+#[derive(Clone, Copy)]
+struct LastAnchor {
+    root: H256,
+    at: DateTime<Utc>,
+    reason: CommitReason,
+}
+
+/// Anchoring state exposed through `GET /api/commit-status`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitStatus {
+    pub main_root: String,
+    pub temporary_root: String,
+    /// Updates not yet anchored (including any in flight).
+    pub pending_updates: u64,
+    /// Root currently being anchored, if a commit is in flight.
+    pub anchoring_root: Option<String>,
+    /// Sequence number of the latest update and of the latest anchored update.
+    pub update_seq: u64,
+    pub anchored_seq: u64,
+    /// Age of the oldest un-anchored update, i.e. the current exposure window.
+    pub oldest_pending_age_secs: Option<f64>,
+    /// Root, time and reason of the last anchor made by this process (`None` since startup).
+    pub last_anchor_root: Option<String>,
+    pub last_anchor_at: Option<DateTime<Utc>>,
+    pub last_anchor_reason: Option<CommitReason>,
+    pub seconds_since_last_anchor: Option<f64>,
+    pub policy: CommitPolicy,
+    /// Anchoring budget and spend of the current period (`None` without a budget).
+    pub budget: Option<BudgetStatus>,
 }
 
 /// Manages the dual-root system for efficient batching of blockchain commits.
@@ -29,42 +201,93 @@ pub struct RootManager {
     main_root: Arc<Mutex<H256>>,
     /// The root in memory (updated on every write, fast).
     temporary_root: Arc<Mutex<H256>>,
-    /// Counter for tracking how many times temporary_root has been updated.
-    update_counter: Arc<Mutex<u64>>,
+    /// Updates since the last anchor (count + age of the oldest one).
+    pending: Arc<Mutex<PendingUpdates>>,
+    /// Last successful anchor (for exposure reporting).
+    last_anchor: Arc<Mutex<Option<LastAnchor>>>,
+    /// Anchoring progress (`wait_for_anchor` subscribes to it).
+    anchor_progress: watch::Sender<AnchorProgress>,
     /// Flag to control the background commit task.
     shutdown: Arc<tokio::sync::Notify>,
     /// Notification to trigger immediate commit check (when threshold is reached).
     commit_trigger: Arc<tokio::sync::Notify>,
-    /// Lock to prevent writes while committing to blockchain.
-    commit_in_progress: Arc<tokio::sync::Mutex<bool>>,
+    /// Held for the duration of one anchor write so commits reach the chain in order.
+    ///
+    /// Lock order is `root_lock` -> `commit_slot`. A holder of `commit_slot` never waits for
+    /// `root_lock`, and only holds `root_lock` while taking the root snapshot.
+    commit_slot: Arc<tokio::sync::Mutex<()>>,
     /// Single-writer lock that must cover the entire critical section of:
     /// DB write -> SMT update/proof -> verify -> update temporary_root/trusted_state.
     ///
-    /// The background commit task must also acquire this lock so it cannot interleave with writes.
+    /// Commits take this lock only to snapshot temporary_root, not for the chain round-trip.
     root_lock: Arc<tokio::sync::Mutex<()>>,
-    /// Number of temporary_root updates before committing to main_root (blockchain).
-    batch_commit_size: u64,
+    /// When pending updates are committed to main_root (blockchain).
+    policy: CommitPolicy,
     /// Path to the trusted state file inside the TEE.
     state_file_path: PathBuf,
+    /// MAC key sealing the trusted state file (from `infra::sealing`).
+    seal_key: [u8; 32],
+    /// Counter of the sealed trusted state file found at startup, checked against the anchored
+    /// sequence once the history is attached (`None` if initialized from the anchor).
+    restored_seq: Option<u64>,
+    /// Upper bound of the anchor's own write sequence, sealed in the trusted state file and
+    /// raised before every write (`None` if the anchor does not report a sequence).
+    anchor_seq: std::sync::Mutex<Option<u64>>,
+    /// Where main_root is anchored (Solana by default, see `infra::anchor`).
+    anchor: Arc<dyn RootAnchor>,
+    /// Where successful anchors are recorded (see `attach_history`).
+    history: std::sync::RwLock<Option<AnchorHistory>>,
+    /// Backoff and circuit breaker for failed commits.
+    retry_policy: RetryPolicy,
+    retry: std::sync::Mutex<RetryState>,
+    /// Durable queue of failed commits (see `attach_jobs`).
+    jobs: std::sync::RwLock<Option<AnchorJobs>>,
+    /// Anchoring budget, from `ANCHOR_BUDGET_*` (`None` = unlimited).
+    budget: Option<BudgetTracker>,
 }
 
 impl RootManager {
-    /// Creates a new RootManager and initializes it with the current root from Solana.
-    /// The batch commit size can be configured via the `BATCH_COMMIT_SIZE` environment variable.
-    /// Defaults to 10 if not set.
+    /// Creates a new RootManager and initializes it with the current root from the anchor
+    /// selected by `ANCHOR_BACKEND` (Solana by default).
+    /// The commit policy is read from `BATCH_COMMIT_SIZE` and `COMMIT_MAX_AGE_SECS`.
     pub async fn new() -> anyhow::Result<Self> {
-        // Initialize main_root from Solana blockchain
-        let blockchain_root = solana::read_root().await?;
+        Self::with_anchor(anchor::from_config()?).await
+    }
+
+    /// Creates a new RootManager that reads and commits main_root through `anchor`.
+    pub async fn with_anchor(anchor: Arc<dyn RootAnchor>) -> anyhow::Result<Self> {
+        // Initialize main_root from the anchor
+        let anchored = anchor.read_record().await?;
+        let blockchain_root = anchored.root;
 
-        let batch_commit_size = config::batch_commit_size();
+        let policy = CommitPolicy::from_config();
+        let budget = BudgetPolicy::from_config()?;
 
         println!(
-            "> RootManager: Batch commit size set to {} operations",
-            batch_commit_size
+            "> RootManager: Batch commit size set to {} operations, max age {} (anchor: {})",
+            policy.batch_size,
+            policy
+                .max_age
+                .map(|d| format!("{}s", d.as_secs()))
+                .unwrap_or_else(|| "disabled".to_string()),
+            anchor.name()
         );
+        if let Some(budget) = &budget {
+            println!(
+                "> RootManager: Anchoring budget {} lamports per {} (then {})",
+                budget.limit_lamports,
+                budget.period.as_str(),
+                match budget.action {
+                    BudgetAction::Widen => format!("widen {}x", budget.widen_factor),
+                    BudgetAction::Refuse => "refuse".to_string(),
+                }
+            );
+        }
 
         // Define trusted state file path (default to "trusted_state.json" in current dir)
         let state_file_path = PathBuf::from("trusted_state.json");
+        let key_provider = sealing::from_config()?;
+        let seal_key = key_provider.key("trusted_state")?;
 
         // If we're doing a "reset run" (single-tenant dev workflows), ignore any existing trusted state
         // so we don't warn about mismatches before bootstrap applies the schema + resets roots.
@@ -79,40 +302,88 @@ impl RootManager {
 
         // Try to load trusted root from file
         let mut initial_temp_root = blockchain_root;
+        let mut initial_seq = 0u64;
+        let mut restored_seq = None;
+        let mut anchor_seq = None;
 
-        if state_file_path.exists() {
+        // An empty file is a placeholder (e.g. created for a Docker bind mount), not state.
+        let has_state = fs::metadata(&state_file_path).map(|m| m.len() > 0).unwrap_or(false);
+        if has_state {
             println!(
                 "> RootManager: Found trusted state file at {:?}",
                 state_file_path
             );
-            match Self::load_root_from_file(&state_file_path) {
-                Ok(trusted_root) => {
-                    if trusted_root != blockchain_root {
-                        println!("> RootManager: WARNING: Trusted local root differs from blockchain root!");
-                        println!(
-                            "  - Blockchain Root: {}",
-                            hex::encode(blockchain_root.as_bytes())
-                        );
-                        println!(
-                            "  - Trusted Local Root: {}",
-                            hex::encode(trusted_root.as_bytes())
-                        );
-                        println!("> RootManager: Using Trusted Local Root as the source of truth.");
-                        println!("> RootManager: Pending changes will be committed to blockchain shortly.");
-                        initial_temp_root = trusted_root;
-                    } else {
-                        println!("> RootManager: Trusted local root matches blockchain root.");
-                    }
+            let loaded = Self::load_root_from_file(&state_file_path, &seal_key).map_err(|e| {
+                anyhow::anyhow!(
+                    "Trusted state file {:?} failed verification ({}). Refusing to start: it may \
+                     have been tampered with. Remove it to resync from the anchor (un-anchored \
+                     writes are lost).",
+                    state_file_path,
+                    e
+                )
+            })?;
+            if !loaded.sealed {
+                // Written before sealing: only trusted if it adds nothing beyond the anchor.
+                if loaded.root != blockchain_root {
+                    return Err(anyhow::anyhow!(
+                        "Trusted state file {:?} is not sealed and differs from the anchored root. \
+                         Refusing to start. Remove it to resync from the anchor.",
+                        state_file_path
+                    ));
                 }
-                Err(e) => {
-                    eprintln!("> RootManager: Failed to load trusted state file: {}", e);
-                    eprintln!("> RootManager: Falling back to blockchain root.");
+                println!("> RootManager: Sealing legacy trusted state file (matches blockchain root).");
+                Self::save_root_to_file(
+                    &state_file_path,
+                    &seal_key,
+                    blockchain_root,
+                    loaded.seq,
+                    anchored.sequence,
+                )?;
+            } else {
+                restored_seq = Some(loaded.seq);
+                if let (Some(sealed), Some(actual)) = (loaded.anchor_seq, anchored.sequence) {
+                    if actual > sealed {
+                        return Err(anyhow::anyhow!(
+                            "Trusted state file {:?} is stale: it allows for {} anchor write(s) \
+                             but the anchor has applied {}. Refusing to start: an older copy of \
+                             the file may have been restored, or another writer is anchoring. \
+                             Remove it to resync from the anchor (un-anchored writes are lost).",
+                            state_file_path,
+                            sealed,
+                            actual
+                        ));
+                    }
                 }
+                anchor_seq = loaded.anchor_seq;
+            }
+            initial_seq = loaded.seq;
+            let trusted_root = loaded.root;
+            if trusted_root != blockchain_root {
+                println!("> RootManager: WARNING: Trusted local root differs from blockchain root!");
+                println!(
+                    "  - Blockchain Root: {}",
+                    hex::encode(blockchain_root.as_bytes())
+                );
+                println!(
+                    "  - Trusted Local Root: {}",
+                    hex::encode(trusted_root.as_bytes())
+                );
+                println!("> RootManager: Using Trusted Local Root as the source of truth.");
+                println!("> RootManager: Pending changes will be committed to blockchain shortly.");
+                initial_temp_root = trusted_root;
+            } else {
+                println!("> RootManager: Trusted local root matches blockchain root.");
             }
         } else {
             println!("> RootManager: No trusted state file found. Initializing from blockchain root.");
             // Create the file with the initial root
-            if let Err(e) = Self::save_root_to_file(&state_file_path, blockchain_root) {
+            if let Err(e) = Self::save_root_to_file(
+                &state_file_path,
+                &seal_key,
+                blockchain_root,
+                0,
+                anchored.sequence,
+            ) {
                 eprintln!(
                     "> RootManager: Failed to create initial trusted state file: {}",
                     e
@@ -120,66 +391,262 @@ impl RootManager {
             }
         }
 
+        // A trusted root ahead of the chain is un-anchored work; start its max-age clock now.
+        let in_sync = initial_temp_root == blockchain_root;
+        let pending = PendingUpdates {
+            seq: initial_seq,
+            since: (!in_sync).then(Instant::now),
+            roots: if in_sync { Vec::new() } else { vec![(initial_seq, initial_temp_root)] },
+            ..Default::default()
+        };
+        let (anchor_progress, _) = watch::channel(AnchorProgress {
+            anchored_seq: if in_sync { initial_seq } else { 0 },
+            anchored_root: blockchain_root,
+            ..Default::default()
+        });
+
         let manager = Self {
             main_root: Arc::new(Mutex::new(blockchain_root)),
             temporary_root: Arc::new(Mutex::new(initial_temp_root)),
-            update_counter: Arc::new(Mutex::new(0)),
+            pending: Arc::new(Mutex::new(pending)),
+            last_anchor: Arc::new(Mutex::new(None)),
+            anchor_progress,
             shutdown: Arc::new(tokio::sync::Notify::new()),
             commit_trigger: Arc::new(tokio::sync::Notify::new()),
-            commit_in_progress: Arc::new(tokio::sync::Mutex::new(false)),
+            commit_slot: Arc::new(tokio::sync::Mutex::new(())),
             root_lock: Arc::new(tokio::sync::Mutex::new(())),
-            batch_commit_size,
+            policy,
             state_file_path,
+            seal_key,
+            restored_seq,
+            anchor_seq: std::sync::Mutex::new(anchor_seq.max(anchored.sequence)),
+            anchor,
+            history: std::sync::RwLock::new(None),
+            retry_policy: RetryPolicy::from_config(),
+            retry: std::sync::Mutex::new(RetryState::default()),
+            jobs: std::sync::RwLock::new(None),
+            budget: budget.map(BudgetTracker::new),
         };
 
         Ok(manager)
     }
 
-    /// Helper to load root from file
-    fn load_root_from_file(path: &PathBuf) -> anyhow::Result<H256> {
+    /// The anchor main_root is committed to.
+    pub fn anchor(&self) -> &Arc<dyn RootAnchor> {
+        &self.anchor
+    }
+
+    /// Records every successful anchor in `history` from now on.
+    ///
+    /// Also moves the update sequence past anything already recorded, so sequence numbers
+    /// stay unique across restarts even if the trusted state file was reset.
+    ///
+    /// Fails if the trusted state file found at startup is behind the anchored sequence, i.e.
+    /// an older copy of the file was restored.
+    pub async fn attach_history(&self, history: AnchorHistory) -> anyhow::Result<()> {
+        let recorded = history.max_seq().await?;
+        if let Some(seq) = self.restored_seq {
+            if seq < recorded {
+                return Err(anyhow::anyhow!(
+                    "Trusted state file {:?} is stale: its counter is at update #{} but update #{} \
+                     is already anchored. Refusing to start: an older copy of the file may have \
+                     been restored. Remove it to resync from the anchor (un-anchored writes are \
+                     lost).",
+                    self.state_file_path,
+                    seq,
+                    recorded
+                ));
+            }
+        }
+        let in_sync = self.get_temporary_root().await == self.get_main_root().await;
+        {
+            let mut pending = self.pending.lock().await;
+            if pending.seq <= recorded {
+                pending.seq = recorded;
+                if !in_sync {
+                    // The un-anchored trusted root gets a number of its own.
+                    pending.seq += 1;
+                    let root = self.get_temporary_root().await;
+                    pending.roots = vec![(pending.seq, root)];
+                }
+            }
+            let anchored = if in_sync { pending.seq } else { recorded };
+            self.anchor_progress.send_modify(|p| p.anchored_seq = p.anchored_seq.max(anchored));
+        }
+        if let Some(budget) = &self.budget {
+            let start = budget.policy().period.start_of(Utc::now());
+            let spend = history.spend_since(Some(start)).await?;
+            budget.seed(start, spend.fee_lamports.max(0) as u64);
+        }
+        *self.history.write().unwrap() = Some(history);
+        Ok(())
+    }
+
+    /// The anchor history, if one is attached.
+    pub fn history(&self) -> Option<AnchorHistory> {
+        self.history.read().unwrap().clone()
+    }
+
+    /// Persists failed commits in `jobs` from now on, and restores the ones a previous run
+    /// left queued: their attempt count, backoff deadline, last error and covered roots.
+    ///
+    /// Call after `attach_history`, which settles the update sequence numbers.
+    pub async fn attach_jobs(&self, jobs: AnchorJobs) -> anyhow::Result<()> {
+        let anchored_seq = self.anchor_progress.borrow().anchored_seq;
+        jobs.complete_through(anchored_seq).await?;
+        let queued = jobs.pending().await?;
+
+        if let (Some(first), Some(last)) = (queued.first(), queued.last()) {
+            {
+                let mut pending = self.pending.lock().await;
+                let mut roots: Vec<(u64, H256)> = queued
+                    .iter()
+                    .flat_map(|job| job.covered_roots.iter().copied())
+                    .filter(|(seq, _)| *seq > anchored_seq)
+                    .collect();
+                roots.extend(std::mem::take(&mut pending.roots));
+                roots.sort_by_key(|(seq, _)| *seq);
+                roots.dedup_by_key(|(seq, _)| *seq);
+                pending.count = pending.count.max(roots.len() as u64);
+                pending.roots = roots;
+                let age = (Utc::now() - first.created_at).to_std().unwrap_or_default();
+                if let Some(since) = Instant::now().checked_sub(age) {
+                    pending.since = Some(pending.since.map_or(since, |s| s.min(since)));
+                }
+            }
+            let attempts = queued.iter().map(|job| job.attempts).max().unwrap_or(0);
+            let delay = (last.next_attempt_at - Utc::now()).to_std().unwrap_or_default();
+            *self.retry.lock().unwrap() = RetryState {
+                failures: attempts,
+                next_attempt_at: Some(Instant::now() + delay),
+                last_error: Some(last.last_error.clone()),
+                last_failure_at: Some(last.created_at.max(first.created_at)),
+                first_failure_at: Some(first.created_at),
+            };
+            println!(
+                "> RootManager: Restored {} queued anchor job(s) through update #{} ({} failed attempts, last error: {})",
+                queued.len(),
+                last.last_seq,
+                attempts,
+                last.last_error
+            );
+        }
+
+        *self.jobs.write().unwrap() = Some(jobs);
+        Ok(())
+    }
+
+    /// Retry/circuit-breaker state of anchoring.
+    pub async fn anchor_health(&self) -> AnchorHealth {
+        let status = self.commit_status().await;
+        let retry = self.retry.lock().unwrap();
+        let state = if retry.failures == 0 {
+            AnchorState::Healthy
+        } else if self.retry_policy.breaker_open(retry.failures) {
+            AnchorState::Degraded
+        } else {
+            AnchorState::Retrying
+        };
+        AnchorHealth {
+            state,
+            consecutive_failures: retry.failures,
+            breaker_threshold: self.retry_policy.breaker_threshold,
+            next_retry_in_secs: retry
+                .next_attempt_at
+                .filter(|_| retry.failures > 0)
+                .map(|t| t.saturating_duration_since(Instant::now()).as_secs_f64()),
+            last_error: retry.last_error.clone(),
+            last_failure_at: retry.last_failure_at,
+            pending_updates: status.pending_updates,
+            oldest_pending_age_secs: status.oldest_pending_age_secs,
+        }
+    }
+
+    /// The durable job queue, if one is attached.
+    pub fn jobs(&self) -> Option<AnchorJobs> {
+        self.jobs.read().unwrap().clone()
+    }
+
+    /// Helper to load root (and its update sequence number) from file, verifying its MAC
+    fn load_root_from_file(path: &PathBuf, key: &[u8; 32]) -> anyhow::Result<LoadedState> {
         let content = fs::read_to_string(path)?;
         let state: TrustedState = serde_json::from_str(&content)?;
-        let root_bytes = hex::decode(state.root)?;
+        let root_bytes = hex::decode(&state.root)?;
         if root_bytes.len() != 32 {
             return Err(anyhow::anyhow!("Invalid root length in trusted state file"));
         }
-        Ok(H256::from_slice(&root_bytes))
+        let root = H256::from_slice(&root_bytes);
+        let sealed = match &state.mac {
+            Some(tag) => {
+                let input = trusted_state_mac_input(root, state.seq, state.timestamp, state.anchor_seq);
+                sealing::verify_mac(key, &input, &hex::decode(tag)?)?;
+                true
+            }
+            None => false,
+        };
+        Ok(LoadedState {
+            root,
+            seq: state.seq,
+            anchor_seq: state.anchor_seq,
+            sealed,
+        })
     }
 
-    /// Helper to save root to file
-    fn save_root_to_file(path: &PathBuf, root: H256) -> anyhow::Result<()> {
+    /// Helper to seal and save root to file
+    ///
+    /// Written in place rather than via rename: Docker bind-mounts this single file.
+    fn save_root_to_file(
+        path: &PathBuf,
+        key: &[u8; 32],
+        root: H256,
+        seq: u64,
+        anchor_seq: Option<u64>,
+    ) -> anyhow::Result<()> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        let tag = sealing::mac(key, &trusted_state_mac_input(root, seq, timestamp, anchor_seq));
         let state = TrustedState {
             root: hex::encode(root.as_bytes()),
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)?
-                .as_secs(),
+            timestamp,
+            seq,
+            mac: Some(hex::encode(tag)),
+            anchor_seq,
         };
         let content = serde_json::to_string_pretty(&state)?;
         fs::write(path, content)?;
         Ok(())
     }
 
-    /// Updates the temporary_root with a new value.
-    /// This is called on every successful write operation.
-    /// Blocks if a blockchain commit is in progress to prevent root overwrites.
-    /// Returns true if this update triggers a commit (based on batch_commit_size).
-    /// If threshold is reached, triggers immediate commit check in background task.
-    pub async fn update_temporary_root(&self, new_root: H256) -> bool {
-        // Wait if a commit is in progress - this prevents overwriting temporary_root
-        // while the blockchain commit is happening
-        loop {
-            let commit_lock = self.commit_in_progress.lock().await;
-            if !*commit_lock {
-                drop(commit_lock);
-                break;
-            }
-            drop(commit_lock);
-            // Wait a bit before checking again
-            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+    fn save_trusted_state(&self, root: H256, seq: u64) -> anyhow::Result<()> {
+        let anchor_seq = *self.anchor_seq.lock().unwrap();
+        Self::save_root_to_file(&self.state_file_path, &self.seal_key, root, seq, anchor_seq)
+    }
+
+    /// Raises the sealed bound of the anchor's write sequence before a write that may land, so
+    /// the file is never behind the anchor after a crash. Failed writes leave it one high,
+    /// which is safe. No-op if the anchor does not report a sequence.
+    async fn seal_anchor_intent(&self) -> anyhow::Result<()> {
+        let pending = self.pending.lock().await;
+        match self.anchor_seq.lock().unwrap().as_mut() {
+            Some(anchor_seq) => *anchor_seq += 1,
+            None => return Ok(()),
         }
+        let root = self.get_temporary_root().await;
+        self.save_trusted_state(root, pending.seq)
+    }
+
+    /// Updates the temporary_root with a new value.
+    /// This is called on every successful write operation, under `lock_root()`.
+    ///
+    /// Never waits for an in-flight anchor commit: the commit works on its own snapshot.
+    /// If the update makes a commit due, the background task is notified immediately.
+    pub async fn update_temporary_root(&self, new_root: H256) -> RootUpdate {
+        let mut pending = self.pending.lock().await;
+        let seq = pending.seq + 1;
 
         // Save to trusted file FIRST
-        if let Err(e) = Self::save_root_to_file(&self.state_file_path, new_root) {
+        if let Err(e) = self.save_trusted_state(new_root, seq) {
             eprintln!(
                 "> RootManager: CRITICAL ERROR: Failed to save root to trusted file: {}",
                 e
@@ -191,18 +658,25 @@ impl RootManager {
         *temp_root = new_root;
         drop(temp_root);
 
-        let mut counter = self.update_counter.lock().await;
-        *counter += 1;
-        let count = *counter;
-        let triggers_commit = count % self.batch_commit_size == 0;
-        drop(counter);
+        pending.seq = seq;
+        pending.count += 1;
+        pending.roots.push((seq, new_root));
+        let since = *pending.since.get_or_insert_with(Instant::now);
+        let update = RootUpdate {
+            seq: pending.seq,
+            commit_due: self
+                .effective_policy()
+                .evaluate(pending.count, Some(since.elapsed()))
+                .is_some(),
+        };
+        drop(pending);
 
-        // If threshold reached, notify background task to commit immediately
-        if triggers_commit {
+        // If a commit is due, notify background task to commit immediately
+        if update.commit_due {
             self.commit_trigger.notify_one();
         }
 
-        triggers_commit
+        update
     }
 
     /// Acquires the single-writer "root lock".
@@ -210,8 +684,8 @@ impl RootManager {
     /// Hold this lock for the entire write critical section:
     /// DB write -> SMT update/proof -> verify -> update_temporary_root.
     ///
-    /// IMPORTANT: do NOT hold this lock while waiting for a background commit to complete,
-    /// or you can deadlock the commit task.
+    /// IMPORTANT: do NOT hold this lock while waiting for an anchor (`wait_for_anchor`),
+    /// or the commit that would satisfy the wait can never snapshot the root.
     pub async fn lock_root(&self) -> tokio::sync::MutexGuard<'_, ()> {
         self.root_lock.lock().await
     }
@@ -228,59 +702,424 @@ impl RootManager {
         *main_root
     }
 
-    /// Waits for any in-progress blockchain commit to complete.
-    /// This should be called after update_temporary_root returns true (triggers_commit)
-    /// to ensure the commit finishes before proceeding with the next operation.
-    pub async fn wait_for_commit_completion(&self) {
-        // Give the background task a moment to start and set the commit_in_progress flag
-        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    /// Reads the anchor while no commit is in flight and returns its root if this instance did
+    /// not write it: anything but main_root or a root produced since (an attempt may have
+    /// landed although it reported failure). `None` means the anchor is where we left it.
+    pub async fn foreign_anchored_root(&self) -> anyhow::Result<Option<H256>> {
+        let _slot = self.commit_slot.lock().await;
+        let anchored = self.anchor.read_root().await?;
+        if anchored == self.get_main_root().await || anchored == self.get_temporary_root().await {
+            return Ok(None);
+        }
+        let pending = self.pending.lock().await;
+        Ok((!pending.roots.iter().any(|(_, root)| *root == anchored)).then_some(anchored))
+    }
 
-        // Now wait until commit_in_progress becomes false
-        loop {
-            let commit_lock = self.commit_in_progress.lock().await;
-            if !*commit_lock {
-                drop(commit_lock);
-                break;
+    /// Subscribes to anchoring progress (one notification per anchor attempt).
+    pub fn subscribe_anchors(&self) -> watch::Receiver<AnchorProgress> {
+        self.anchor_progress.subscribe()
+    }
+
+    /// Waits until update `seq` is covered by an anchored root.
+    ///
+    /// Fails if the commit attempt covering `seq` failed (the update stays pending and is
+    /// retried by the background task).
+    pub async fn wait_for_anchor(&self, seq: u64) -> anyhow::Result<()> {
+        let mut rx = self.anchor_progress.subscribe();
+        let progress = rx
+            .wait_for(|p| p.anchored_seq >= seq || p.failed_seq >= seq)
+            .await?
+            .clone();
+        if progress.anchored_seq >= seq {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Anchoring update #{} failed: {}",
+                seq,
+                progress.last_error.unwrap_or_default()
+            ))
+        }
+    }
+
+    /// Waits up to `timeout` for update `seq` to be covered by an anchored root.
+    ///
+    /// Unlike `wait_for_anchor`, failed attempts do not end the wait (they are retried).
+    /// Returns whether `seq` is anchored.
+    pub async fn wait_for_anchor_timeout(&self, seq: u64, timeout: Duration) -> bool {
+        let mut rx = self.anchor_progress.subscribe();
+        let anchored = rx.wait_for(|p| p.anchored_seq >= seq);
+        let result = tokio::time::timeout(timeout, anchored).await;
+        matches!(result, Ok(Ok(_)))
+    }
+
+    /// Current anchoring progress.
+    pub fn anchor_progress(&self) -> AnchorProgress {
+        self.anchor_progress.borrow().clone()
+    }
+
+    /// Sequence number of the latest temporary_root update.
+    pub async fn update_seq(&self) -> u64 {
+        self.pending.lock().await.seq
+    }
+
+    /// The policy that decides when pending updates are committed.
+    pub fn policy(&self) -> CommitPolicy {
+        self.policy
+    }
+
+    /// The commit policy in force: `policy`, widened while a `widen` budget is exceeded.
+    pub fn effective_policy(&self) -> CommitPolicy {
+        match &self.budget {
+            Some(budget) => budget.effective_policy(self.policy),
+            None => self.policy,
+        }
+    }
+
+    /// Anchoring budget and spend of the current period (`None` without a budget).
+    pub fn budget_status(&self) -> Option<BudgetStatus> {
+        self.budget.as_ref().map(|b| b.status(self.policy))
+    }
+
+    /// Fails with `BudgetExceeded` if the budget does not allow a commit for `reason` now.
+    fn check_budget(&self, reason: CommitReason) -> anyhow::Result<()> {
+        match &self.budget {
+            Some(budget) => Ok(budget.check_commit(reason)?),
+            None => Ok(()),
+        }
+    }
+
+    /// Counts the fee of an anchor against the budget.
+    fn record_spend(&self, fee_lamports: Option<u64>, at: DateTime<Utc>) {
+        if let (Some(budget), Some(fee)) = (&self.budget, fee_lamports) {
+            budget.record(fee, at);
+        }
+    }
+
+    /// Counts the fee a failed anchor write was still charged (a transaction that landed but
+    /// failed) against the budget, and hands the error back.
+    fn charge_failed<'e>(&self, e: &'e anyhow::Error) -> &'e anyhow::Error {
+        self.record_spend(ChargedFee::of(e), Utc::now());
+        e
+    }
+
+    /// Reports pending updates and how long ago the last anchor happened.
+    pub async fn commit_status(&self) -> CommitStatus {
+        let (pending_updates, oldest, anchoring_root, update_seq) = {
+            let pending = self.pending.lock().await;
+            let in_flight = pending.in_flight.as_ref();
+            (
+                pending.count + in_flight.map_or(0, |f| f.count),
+                in_flight.and_then(|f| f.since).or(pending.since).map(|t| t.elapsed()),
+                in_flight.map(|f| hex::encode(f.root.as_bytes())),
+                pending.seq,
+            )
+        };
+        let anchored_seq = self.anchor_progress.borrow().anchored_seq;
+        let last = *self.last_anchor.lock().await;
+        CommitStatus {
+            main_root: hex::encode(self.get_main_root().await.as_bytes()),
+            temporary_root: hex::encode(self.get_temporary_root().await.as_bytes()),
+            pending_updates,
+            anchoring_root,
+            update_seq,
+            anchored_seq,
+            oldest_pending_age_secs: oldest.map(|d| d.as_secs_f64()),
+            last_anchor_root: last.map(|a| hex::encode(a.root.as_bytes())),
+            last_anchor_at: last.map(|a| a.at),
+            last_anchor_reason: last.map(|a| a.reason),
+            seconds_since_last_anchor: last
+                .map(|a| (Utc::now() - a.at).num_milliseconds().max(0) as f64 / 1000.0),
+            policy: self.policy,
+            budget: self.budget_status(),
+        }
+    }
+
+    /// Snapshots temporary_root and the pending updates it covers as the in-flight commit.
+    /// Caller must hold `root_lock` and `commit_slot`.
+    async fn begin_anchor(&self) -> InFlight {
+        let root = self.get_temporary_root().await;
+        let mut pending = self.pending.lock().await;
+        let snapshot = InFlight {
+            root,
+            seq: pending.seq,
+            count: pending.count,
+            since: pending.since.take(),
+            roots: std::mem::take(&mut pending.roots),
+        };
+        pending.count = 0;
+        pending.in_flight = Some(snapshot.clone());
+        snapshot
+    }
+
+    /// Anchors an in-flight snapshot. Caller must hold `commit_slot` (but not `root_lock`,
+    /// unless writes must stay blocked, e.g. for forced commits and shutdown).
+    ///
+    /// Returns `false` if the snapshot was already anchored (main_root unchanged).
+    async fn finish_anchor(&self, snapshot: InFlight, reason: CommitReason) -> anyhow::Result<bool> {
+        if snapshot.root == self.get_main_root().await {
+            // Roots are already in sync, nothing is actually pending.
+            self.pending.lock().await.in_flight = None;
+            self.note_anchor_success(snapshot.seq).await;
+            self.anchor_progress.send_modify(|p| {
+                p.anchored_seq = p.anchored_seq.max(snapshot.seq);
+                p.anchored_root = snapshot.root;
+            });
+            return Ok(false);
+        }
+
+        let start = Instant::now();
+        // Only replace the root this instance last anchored, so a stale instance cannot roll
+        // the anchor back.
+        let expected_prev = self.get_main_root().await;
+        let written = match self.seal_anchor_intent().await {
+            Ok(()) => self.anchor.write_root_after(expected_prev, snapshot.root).await,
+            Err(e) => Err(e.context("Failed sealing the anchor write in the trusted state file")),
+        };
+        let result = match written {
+            Err(e) => match self.charge_failed(&e).downcast_ref::<StaleAnchor>().copied() {
+                Some(stale) => self.resolve_stale(stale, snapshot.root).await,
+                None => Err(e),
+            },
+            ok => ok,
+        };
+        match result {
+            Ok(receipt) => {
+                let confirmed_at = Utc::now();
+                *self.main_root.lock().await = snapshot.root;
+                self.pending.lock().await.in_flight = None;
+                *self.last_anchor.lock().await = Some(LastAnchor {
+                    root: snapshot.root,
+                    at: confirmed_at,
+                    reason,
+                });
+                let first_seq = self.anchor_progress.borrow().anchored_seq + 1;
+                self.record_spend(receipt.fee_lamports, confirmed_at);
+                self.record_history(AnchorRecord {
+                    root: snapshot.root,
+                    first_seq: first_seq.min(snapshot.seq),
+                    last_seq: snapshot.seq,
+                    update_count: snapshot.count,
+                    reason: reason.as_str().to_string(),
+                    backend: self.anchor.name().to_string(),
+                    signature: receipt.signature,
+                    slot: receipt.slot,
+                    fee_lamports: receipt.fee_lamports,
+                    confirmed_at,
+                    covered_roots: snapshot.roots,
+                    targets: receipt.targets,
+                })
+                .await;
+                // Clear the retry state before waking waiters so they see it settled.
+                self.note_anchor_success(snapshot.seq).await;
+                self.anchor_progress.send_modify(|p| {
+                    p.anchored_seq = p.anchored_seq.max(snapshot.seq);
+                    p.anchored_root = snapshot.root;
+                });
+                println!(
+                    "> RootManager: ✓ Committed temporary_root to blockchain ({:?}, {} updates through #{}, took {:?}, root: {})",
+                    reason,
+                    snapshot.count,
+                    snapshot.seq,
+                    start.elapsed(),
+                    hex::encode(snapshot.root.as_bytes())
+                );
+                Ok(true)
+            }
+            Err(e) => {
+                self.note_anchor_failure(&snapshot, reason, &e).await;
+                // Hand the updates back so the next commit covers them again.
+                {
+                    let mut pending = self.pending.lock().await;
+                    pending.in_flight = None;
+                    pending.count += snapshot.count;
+                    pending.since = snapshot.since.or(pending.since);
+                    let newer = std::mem::take(&mut pending.roots);
+                    pending.roots = snapshot.roots;
+                    pending.roots.extend(newer);
+                }
+                self.anchor_progress.send_modify(|p| {
+                    p.failed_seq = p.failed_seq.max(snapshot.seq);
+                    p.last_error = Some(e.to_string());
+                });
+                Err(e)
             }
-            drop(commit_lock);
-            // Poll every 10ms until commit completes
-            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
         }
     }
 
-    /// Commits the temporary_root to the blockchain as the new main_root.
-    /// This is called by the background task based on batch_commit_size.
-    pub async fn commit_temporary_to_main(&self) -> anyhow::Result<()> {
-        let temp_root = self.get_temporary_root().await;
+    /// Re-reads the anchor after a conditional write found it moved. If it already holds `root`
+    /// (an earlier attempt landed although it reported failure), the commit is done. Otherwise
+    /// another writer moved it and the stale error stands; retries keep failing, and the
+    /// breaker opens, until an operator reconciles the two.
+    async fn resolve_stale(&self, stale: StaleAnchor, root: H256) -> anyhow::Result<AnchorReceipt> {
+        match self.anchor.read_root().await {
+            Ok(actual) if actual == root => {
+                println!(
+                    "> RootManager: Anchor already holds {} (an earlier attempt landed); commit complete.",
+                    hex::encode(root.as_bytes())
+                );
+                Ok(AnchorReceipt::default())
+            }
+            Ok(actual) => {
+                eprintln!(
+                    "> RootManager: CRITICAL: Anchor holds {} but this instance last anchored {}. Another writer is anchoring; not overwriting it.",
+                    hex::encode(actual.as_bytes()),
+                    hex::encode(stale.expected.as_bytes())
+                );
+                Err(StaleAnchor {
+                    actual: Some(actual),
+                    ..stale
+                }
+                .into())
+            }
+            Err(e) => {
+                eprintln!("> RootManager: Could not re-read the anchor after a stale write: {}", e);
+                Err(stale.into())
+            }
+        }
+    }
 
-        // Write to Solana blockchain
-        solana::write_root(temp_root).await?;
+    /// Clears the retry state and the queued jobs covered by an anchor through `seq`.
+    async fn note_anchor_success(&self, seq: u64) {
+        let failures = std::mem::take(&mut *self.retry.lock().unwrap()).failures;
+        if failures > 0 {
+            println!(
+                "> RootManager: Anchoring recovered after {} failed attempt(s); circuit breaker closed.",
+                failures
+            );
+        }
+        if let Some(jobs) = self.jobs() {
+            if let Err(e) = jobs.complete_through(seq).await {
+                eprintln!("> RootManager: ERROR removing completed anchor jobs: {}", e);
+            }
+        }
+    }
 
-        // Update main_root to match temporary_root
-        let mut main_root = self.main_root.lock().await;
-        *main_root = temp_root;
+    /// Counts a failed commit, schedules the retry and persists the job (if a queue is attached).
+    async fn note_anchor_failure(&self, snapshot: &InFlight, reason: CommitReason, error: &anyhow::Error) {
+        let now = Utc::now();
+        let (failures, delay, first_failure_at) = {
+            let mut retry = self.retry.lock().unwrap();
+            retry.failures += 1;
+            let delay = self.retry_policy.backoff(retry.failures);
+            retry.next_attempt_at = Some(Instant::now() + delay);
+            retry.last_error = Some(error.to_string());
+            retry.last_failure_at = Some(now);
+            let first_failure_at = *retry.first_failure_at.get_or_insert(now);
+            (retry.failures, delay, first_failure_at)
+        };
+        eprintln!(
+            "> RootManager: ✗ Anchor attempt {} failed ({:?}, through update #{}), retrying in {:?}: {}",
+            failures, reason, snapshot.seq, delay, error
+        );
+        if failures == self.retry_policy.breaker_threshold {
+            eprintln!(
+                "> RootManager: Circuit breaker OPEN after {} consecutive failures; service degraded until an anchor succeeds.",
+                failures
+            );
+        }
 
-        println!("> RootManager: Committed temporary_root to blockchain (main_root updated)");
+        let Some(jobs) = self.jobs() else {
+            return;
+        };
+        let anchored_seq = self.anchor_progress.borrow().anchored_seq;
+        let job = AnchorJob {
+            first_seq: (anchored_seq + 1).min(snapshot.seq),
+            last_seq: snapshot.seq,
+            root: snapshot.root,
+            reason: reason.as_str().to_string(),
+            attempts: failures,
+            last_error: error.to_string(),
+            next_attempt_at: now + chrono::Duration::from_std(delay).unwrap_or_default(),
+            created_at: first_failure_at,
+            covered_roots: snapshot.roots.clone(),
+        };
+        if let Err(e) = jobs.record_failure(&job).await {
+            eprintln!("> RootManager: ERROR persisting anchor job: {}", e);
+        }
+    }
 
+    /// Appends an anchor to the history (if attached). The anchor is already on chain, so a
+    /// failure here is logged rather than returned.
+    async fn record_history(&self, record: AnchorRecord) {
+        let Some(history) = self.history() else {
+            return;
+        };
+        if let Err(e) = history.record(&record).await {
+            eprintln!(
+                "> RootManager: ERROR recording anchor of {} in anchor_history: {}",
+                hex::encode(record.root.as_bytes()),
+                e
+            );
+        }
+    }
+
+    /// Takes `root_lock` and the commit slot, waiting out any in-flight commit without
+    /// holding `root_lock` (so writes are never blocked behind a chain round-trip).
+    async fn lock_root_and_commit_slot(&self) -> (MutexGuard<'_, ()>, MutexGuard<'_, ()>) {
+        loop {
+            let root_guard = self.root_lock.lock().await;
+            if let Ok(slot) = self.commit_slot.try_lock() {
+                return (root_guard, slot);
+            }
+            drop(root_guard);
+            drop(self.commit_slot.lock().await);
+        }
+    }
+
+    /// Anchors the current temporary_root now, regardless of the commit policy.
+    ///
+    /// Returns `false` if there was nothing to anchor (temporary_root == main_root).
+    /// Do not call this while holding `lock_root()`.
+    pub async fn commit_now(&self) -> anyhow::Result<bool> {
+        self.check_budget(CommitReason::OnDemand)?;
+        let (root_guard, _slot) = self.lock_root_and_commit_slot().await;
+        let snapshot = self.begin_anchor().await;
+        drop(root_guard);
+        self.finish_anchor(snapshot, CommitReason::OnDemand).await
+    }
+
+    /// Makes sure update `seq` is anchored, committing on demand if it is still pending.
+    ///
+    /// Concurrent callers share commits: once the commit slot is free, a caller whose update
+    /// was covered by someone else's commit returns without anchoring again.
+    /// Do not call this while holding `lock_root()`.
+    pub async fn anchor_through(&self, seq: u64) -> anyhow::Result<()> {
+        if self.anchor_progress.borrow().anchored_seq >= seq {
+            return Ok(());
+        }
+        let (root_guard, _slot) = self.lock_root_and_commit_slot().await;
+        if self.anchor_progress.borrow().anchored_seq >= seq {
+            return Ok(());
+        }
+        self.check_budget(CommitReason::OnDemand)?;
+        let update_seq = self.update_seq().await;
+        if seq > update_seq {
+            return Err(anyhow::anyhow!(
+                "Update #{} does not exist yet (latest is #{})",
+                seq,
+                update_seq
+            ));
+        }
+        let snapshot = self.begin_anchor().await;
+        drop(root_guard);
+        self.finish_anchor(snapshot, CommitReason::OnDemand).await?;
         Ok(())
     }
 
-    /// Force-sets the temporary_root and main_root to `new_root` and commits it to Solana immediately.
+    /// Force-sets the temporary_root and main_root to `new_root` and commits it to the anchor
+    /// immediately.
     ///
     /// This is intended for schema migrations where the SMT must be rebuilt from the post-migration DB
     /// and both roots must be updated to match that rebuilt state right away.
+    ///
+    /// Caller must hold `lock_root()` so the forced commit cannot interleave with writes (the
+    /// lock is not reentrant, so it is not taken here). Any in-flight commit finishes first.
     pub async fn force_set_roots_and_commit(&self, new_root: H256) -> anyhow::Result<()> {
-        // Ensure forced repair/migration cannot interleave with writes or background commits.
-        let _root_guard = self.root_lock.lock().await;
-
-        // Block writes during the forced commit.
-        {
-            let mut commit_flag = self.commit_in_progress.lock().await;
-            *commit_flag = true;
-        }
+        let _slot = self.commit_slot.lock().await;
 
         // Save to trusted file first (crash recovery invariant).
-        if let Err(e) = Self::save_root_to_file(&self.state_file_path, new_root) {
+        let seq = self.pending.lock().await.seq;
+        if let Err(e) = self.save_trusted_state(new_root, seq) {
             eprintln!(
                 "> RootManager: CRITICAL ERROR: Failed to save root to trusted file: {}",
                 e
@@ -292,55 +1131,31 @@ impl RootManager {
             *temp_root = new_root;
         }
 
-        // Commit to chain and update main_root.
-        let commit_res = solana::write_root(new_root).await;
-        match commit_res {
-            Ok(_) => {
-                let mut main_root = self.main_root.lock().await;
-                *main_root = new_root;
-
-                // Reset counter so batching resumes from a clean state.
-                let mut counter = self.update_counter.lock().await;
-                *counter = 0;
-            }
-            Err(e) => {
-                // Ensure we unblock writes even on error.
-                {
-                    let mut commit_flag = self.commit_in_progress.lock().await;
-                    *commit_flag = false;
-                }
-                return Err(e);
-            }
-        }
-
-        // Unblock writes.
-        {
-            let mut commit_flag = self.commit_in_progress.lock().await;
-            *commit_flag = false;
-        }
-
+        // Commit to chain and update main_root; clears pending updates so batching resumes
+        // from a clean state.
+        let snapshot = self.begin_anchor().await;
+        self.finish_anchor(snapshot, CommitReason::Forced).await?;
         Ok(())
     }
 
     /// Starts the background task that periodically commits the temporary_root to main_root.
-    /// This task checks immediately when threshold is reached (via commit_trigger) or every second as fallback.
+    /// This task checks immediately when a commit becomes due on write (via commit_trigger) and
+    /// every second otherwise, which is also what enforces the max-age rule.
     pub fn start_background_commit_task(self: Arc<Self>) {
-        let batch_size = self.batch_commit_size;
         tokio::spawn(async move {
             let mut interval_timer = interval(Duration::from_secs(1));
             let shutdown = self.shutdown.clone();
             let commit_trigger = self.commit_trigger.clone();
-            let mut last_committed_count = 0u64;
 
             loop {
                 tokio::select! {
                     _ = interval_timer.tick() => {
-                        // Periodic check (fallback)
-                        self.check_and_commit_if_needed(&mut last_committed_count, batch_size).await;
+                        // Periodic check (max-age + fallback)
+                        self.check_and_commit_if_needed().await;
                     }
                     _ = commit_trigger.notified() => {
-                        // Immediate check when threshold is reached
-                        self.check_and_commit_if_needed(&mut last_committed_count, batch_size).await;
+                        // Immediate check when a write made a commit due
+                        self.check_and_commit_if_needed().await;
                     }
                     _ = shutdown.notified() => {
                         println!("> RootManager: Background commit task shutting down");
@@ -352,69 +1167,130 @@ impl RootManager {
     }
 
     /// Helper method to check if commit is needed and perform it.
-    /// Sets commit_in_progress flag to block writes during blockchain commit.
-    async fn check_and_commit_if_needed(&self, last_committed_count: &mut u64, batch_size: u64) {
-        // Prevent any interleaving with writes (DB/SMT/proof/root updates).
-        let _root_guard = self.root_lock.lock().await;
-
-        let counter = self.update_counter.lock().await;
-        let count = *counter;
-        drop(counter);
+    ///
+    /// Writes are only paused while the root is snapshotted; they continue during the
+    /// chain round-trip.
+    async fn check_and_commit_if_needed(&self) {
+        // After a failure, retry once the backoff has elapsed (regardless of the policy).
+        let (failures, retry_at) = {
+            let retry = self.retry.lock().unwrap();
+            (retry.failures, retry.next_attempt_at)
+        };
+        if failures > 0 && retry_at.is_some_and(|t| Instant::now() < t) {
+            return;
+        }
+        // A `refuse` budget that is spent holds background commits (retries included) until
+        // the next period; the alert was logged when it ran out.
+        if self.check_budget(CommitReason::BatchSize).is_err() {
+            return;
+        }
 
-        // Only commit if we've reached a new multiple of batch_size that hasn't been committed yet
-        if count > 0 && count % batch_size == 0 && count > *last_committed_count {
-            // Check if temporary_root differs from main_root
-            let temp_root = self.get_temporary_root().await;
-            let main_root = self.get_main_root().await;
+        let root_guard = self.root_lock.lock().await;
+        // Another commit (on demand / forced) is in flight; re-check on the next tick.
+        let Ok(_slot) = self.commit_slot.try_lock() else {
+            return;
+        };
 
-            if temp_root != main_root {
-                // Set commit_in_progress flag to block new writes
-                {
-                    let mut commit_flag = self.commit_in_progress.lock().await;
-                    *commit_flag = true;
-                }
+        let (count, oldest) = {
+            let pending = self.pending.lock().await;
+            (pending.count, pending.since.map(|t| t.elapsed()))
+        };
+        let reason = if failures > 0 && oldest.is_some() {
+            Some(CommitReason::Retry)
+        } else {
+            self.effective_policy().evaluate(count, oldest)
+        };
+        let Some(reason) = reason else {
+            return;
+        };
 
-                println!(
-                    "> RootManager: Detected batch threshold reached (operation #{}). Committing to blockchain...",
-                    count
-                );
-                println!("> RootManager: Write operations paused during blockchain commit...");
-
-                match self.commit_temporary_to_main().await {
-                    Ok(_) => {
-                        *last_committed_count = count;
-                        println!(
-                            "> RootManager: ✓ Successfully committed batch #{} to blockchain (root: {})",
-                            count / batch_size,
-                            hex::encode(temp_root.as_bytes())
-                        );
-                    }
-                    Err(e) => {
-                        eprintln!("> RootManager: ✗ ERROR committing to blockchain: {}", e);
-                    }
-                }
+        let snapshot = self.begin_anchor().await;
+        drop(root_guard);
 
-                // Clear commit_in_progress flag to allow writes again
-                {
-                    let mut commit_flag = self.commit_in_progress.lock().await;
-                    *commit_flag = false;
-                }
-                println!("> RootManager: Write operations resumed.");
-            } else {
-                // Roots are already in sync, just update the counter to avoid re-checking
-                *last_committed_count = count;
-                println!(
-                    "> RootManager: Batch threshold reached but roots are already in sync (operation #{})",
-                    count
-                );
-            }
-        }
+        println!(
+            "> RootManager: Commit due ({:?}, {} pending updates). Anchoring in the background...",
+            reason, count
+        );
+        // Failures are logged, counted and queued by `finish_anchor`.
+        let _ = self.finish_anchor(snapshot, reason).await;
     }
 
     /// Resets both main_root and temporary_root to a new value (typically zero after clearing DB).
     /// This is useful when the database is cleared and the blockchain root is reset.
-    #[allow(dead_code)] // Reserved for future use
+    ///
+    /// Caller must hold `lock_root()`; any in-flight commit finishes first.
     pub async fn reset_roots(&self, new_root: H256) {
+        let _slot = self.commit_slot.lock().await;
+        self.reset_roots_locked(new_root).await;
+    }
+
+    /// Writes `new_root` to the anchor, then resets both roots to it (see `reset_roots`).
+    ///
+    /// Like a commit, it only replaces the root this instance last anchored: if another writer
+    /// moved the anchor, it fails with [`StaleAnchor`] and nothing is reset. Goes through the
+    /// commit slot so an in-flight commit cannot land on the chain after the reset. Nothing is
+    /// reset if the anchor write fails. Caller must hold `lock_root()`.
+    pub async fn anchor_and_reset_roots(&self, new_root: H256) -> anyhow::Result<()> {
+        self.anchor_reset(new_root, false).await
+    }
+
+    /// Like `anchor_and_reset_roots`, but overwrites whatever the anchor holds. Only for explicit
+    /// operator resets (e.g. `clear-data` after a foreign anchor write).
+    pub async fn force_anchor_and_reset_roots(&self, new_root: H256) -> anyhow::Result<()> {
+        self.anchor_reset(new_root, true).await
+    }
+
+    async fn anchor_reset(&self, new_root: H256, force: bool) -> anyhow::Result<()> {
+        let _slot = self.commit_slot.lock().await;
+        self.seal_anchor_intent()
+            .await
+            .map_err(|e| e.context("Failed sealing the anchor write in the trusted state file"))?;
+        let receipt = if force {
+            self.anchor
+                .write_root(new_root)
+                .await
+                .inspect_err(|e| {
+                    self.charge_failed(e);
+                })?
+        } else {
+            let expected_prev = self.get_main_root().await;
+            match self.anchor.write_root_after(expected_prev, new_root).await {
+                Err(e) => match self.charge_failed(&e).downcast_ref::<StaleAnchor>().copied() {
+                    Some(stale) => self.resolve_stale(stale, new_root).await?,
+                    None => return Err(e),
+                },
+                Ok(receipt) => receipt,
+            }
+        };
+        let AnchorReceipt {
+            signature,
+            slot,
+            fee_lamports,
+            targets,
+        } = receipt;
+        let confirmed_at = Utc::now();
+        self.record_spend(fee_lamports, confirmed_at);
+        let seq = self.pending.lock().await.seq;
+        self.record_history(AnchorRecord {
+            root: new_root,
+            first_seq: seq,
+            last_seq: seq,
+            update_count: 0,
+            reason: CommitReason::Reset.as_str().to_string(),
+            backend: self.anchor.name().to_string(),
+            signature,
+            slot,
+            fee_lamports,
+            confirmed_at,
+            covered_roots: Vec::new(),
+            targets,
+        })
+        .await;
+        self.reset_roots_locked(new_root).await;
+        Ok(())
+    }
+
+    async fn reset_roots_locked(&self, new_root: H256) {
         let mut main_root = self.main_root.lock().await;
         *main_root = new_root;
         drop(main_root);
@@ -423,11 +1299,27 @@ impl RootManager {
         *temp_root = new_root;
         drop(temp_root);
 
-        let mut counter = self.update_counter.lock().await;
-        *counter = 0;
+        let seq = {
+            let mut pending = self.pending.lock().await;
+            pending.count = 0;
+            pending.since = None;
+            pending.roots.clear();
+            pending.seq
+        };
+        self.anchor_progress.send_modify(|p| {
+            p.anchored_seq = seq;
+            p.anchored_root = new_root;
+        });
+        // Nothing is left to retry.
+        *self.retry.lock().unwrap() = RetryState::default();
+        if let Some(jobs) = self.jobs() {
+            if let Err(e) = jobs.clear().await {
+                eprintln!("> RootManager: ERROR clearing anchor jobs: {}", e);
+            }
+        }
 
         // Also reset the trusted file
-        if let Err(e) = Self::save_root_to_file(&self.state_file_path, new_root) {
+        if let Err(e) = self.save_trusted_state(new_root, seq) {
             eprintln!("> RootManager: Failed to reset trusted state file: {}", e);
         }
 
@@ -448,6 +1340,23 @@ impl RootManager {
         }
     }
 
+    /// Writes the current temporary_root and update counter to the trusted state file and
+    /// syncs it to disk (used on shutdown, so the latest root survives even if the final
+    /// anchor failed).
+    pub async fn flush_trusted_state(&self) -> anyhow::Result<()> {
+        let _root_guard = self.root_lock.lock().await;
+        let root = self.get_temporary_root().await;
+        let seq = self.pending.lock().await.seq;
+        self.save_trusted_state(root, seq)?;
+        fs::File::open(&self.state_file_path)?.sync_all()?;
+        println!(
+            "> RootManager: Trusted state flushed (update #{}, root: {})",
+            seq,
+            hex::encode(root.as_bytes())
+        );
+        Ok(())
+    }
+
     /// Shuts down the background commit task.
     pub fn shutdown(&self) {
         self.shutdown.notify_one();
@@ -456,8 +1365,10 @@ impl RootManager {
     /// Commits the temporary_root to blockchain if it differs from main_root.
     /// This should be called during graceful shutdown to ensure no data is lost.
     pub async fn commit_pending_root(&self) -> anyhow::Result<()> {
-        // Prevent any interleaving with in-flight writes during shutdown.
+        // Prevent any interleaving with in-flight writes during shutdown; writes stay blocked
+        // until the final commit completes.
         let _root_guard = self.root_lock.lock().await;
+        let _slot = self.commit_slot.lock().await;
 
         let start = Instant::now();
         let temp_root = self.get_temporary_root().await;
@@ -476,13 +1387,8 @@ impl RootManager {
                 hex::encode(main_root.as_bytes())
             );
 
-            // Set commit_in_progress to prevent new writes during shutdown commit
-            {
-                let mut commit_flag = self.commit_in_progress.lock().await;
-                *commit_flag = true;
-            }
-
-            match self.commit_temporary_to_main().await {
+            let snapshot = self.begin_anchor().await;
+            match self.finish_anchor(snapshot, CommitReason::Shutdown).await {
                 Ok(_) => {
                     let duration = start.elapsed();
                     println!(
@@ -510,4 +1416,3 @@ impl RootManager {
         }
     }
 }
-
@@ -85,7 +85,7 @@ pub fn verify_smt_multi_update_proof(
 
     let new_leaves_smt: Vec<_> = keys
         .into_iter()
-        .zip(new_values.into_iter())
+        .zip(new_values)
         .map(|(k, v)| (h256_to_smt(k), h256_to_smt(v)))
         .collect();
 
@@ -128,13 +128,13 @@ pub fn verify_smt_multi_update_proof_with_old_values(
     let old_leaves_smt: Vec<_> = keys
         .iter()
         .copied()
-        .zip(old_values.into_iter())
+        .zip(old_values)
         .map(|(k, v)| (h256_to_smt(k), h256_to_smt(v)))
         .collect();
 
     let new_leaves_smt: Vec<_> = keys
         .into_iter()
-        .zip(new_values.into_iter())
+        .zip(new_values)
         .map(|(k, v)| (h256_to_smt(k), h256_to_smt(v)))
         .collect();
 
@@ -0,0 +1,142 @@
+//! Anchors roots in an EVM-style root registry contract over JSON-RPC.
+//!
+//! The contract exposes `root() returns (bytes32)` and `setRoot(bytes32)`. Transactions are
+//! sent with `eth_sendTransaction` from the node's first unlocked account, so this targets dev
+//! nodes and the local stand-in (`infra::anchor::evm_standin`), not a production signer.
+
+use async_trait::async_trait;
+use primitive_types::H256;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use super::{AnchorReceipt, RootAnchor};
+
+/// `keccak256("root()")[..4]`
+const ROOT_SELECTOR: [u8; 4] = [0xeb, 0xf0, 0xc7, 0x17];
+/// `keccak256("setRoot(bytes32)")[..4]`
+const SET_ROOT_SELECTOR: [u8; 4] = [0xda, 0xb5, 0xf3, 0x40];
+
+/// How long to wait for a transaction receipt.
+const RECEIPT_TIMEOUT: Duration = Duration::from_secs(30);
+const RECEIPT_POLL: Duration = Duration::from_millis(200);
+
+/// Publishes roots to `contract` through the JSON-RPC endpoint `rpc_url`.
+pub struct EvmAnchor {
+    rpc_url: String,
+    contract: String,
+    client: reqwest::Client,
+}
+
+impl EvmAnchor {
+    pub fn new(rpc_url: impl Into<String>, contract: impl Into<String>) -> Self {
+        Self {
+            rpc_url: rpc_url.into(),
+            contract: contract.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn rpc(&self, method: &str, params: Value) -> anyhow::Result<Value> {
+        let response: Value = self
+            .client
+            .post(&self.rpc_url)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow::anyhow!("{} failed: {}", method, error));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+}
+
+fn parse_quantity(value: &Value) -> Option<u64> {
+    u64::from_str_radix(value.as_str()?.trim_start_matches("0x"), 16).ok()
+}
+
+#[async_trait]
+impl RootAnchor for EvmAnchor {
+    fn name(&self) -> &str {
+        "evm"
+    }
+
+    async fn initialize(&self) -> anyhow::Result<()> {
+        // Contracts are deployed out of band; just make sure this one answers.
+        self.read_root().await?;
+        Ok(())
+    }
+
+    async fn read_root(&self) -> anyhow::Result<H256> {
+        let result = self
+            .rpc(
+                "eth_call",
+                json!([
+                    { "to": self.contract, "data": format!("0x{}", hex::encode(ROOT_SELECTOR)) },
+                    "latest"
+                ]),
+            )
+            .await?;
+        let bytes = hex::decode(result.as_str().unwrap_or_default().trim_start_matches("0x"))?;
+        if bytes.len() != 32 {
+            return Err(anyhow::anyhow!(
+                "root() on {} returned {} bytes (expected 32)",
+                self.contract,
+                bytes.len()
+            ));
+        }
+        Ok(H256::from_slice(&bytes))
+    }
+
+    async fn write_root(&self, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        let accounts = self.rpc("eth_accounts", json!([])).await?;
+        let from = accounts
+            .get(0)
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("{} has no unlocked account to send from", self.rpc_url))?
+            .to_string();
+        let data = format!(
+            "0x{}{}",
+            hex::encode(SET_ROOT_SELECTOR),
+            hex::encode(new_root.as_bytes())
+        );
+        let tx_hash = self
+            .rpc(
+                "eth_sendTransaction",
+                json!([{ "from": from, "to": self.contract, "data": data }]),
+            )
+            .await?
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("eth_sendTransaction returned no hash"))?
+            .to_string();
+
+        let deadline = tokio::time::Instant::now() + RECEIPT_TIMEOUT;
+        let receipt = loop {
+            let receipt = self.rpc("eth_getTransactionReceipt", json!([tx_hash])).await?;
+            if !receipt.is_null() {
+                break receipt;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(anyhow::anyhow!("No receipt for {} after {:?}", tx_hash, RECEIPT_TIMEOUT));
+            }
+            tokio::time::sleep(RECEIPT_POLL).await;
+        };
+        if receipt.get("status").and_then(parse_quantity) != Some(1) {
+            return Err(anyhow::anyhow!("setRoot transaction {} reverted", tx_hash));
+        }
+
+        println!(
+            "> Anchor: Wrote new root to EVM contract {}: {} (tx {})",
+            self.contract,
+            hex::encode(new_root.as_bytes()),
+            tx_hash
+        );
+        Ok(AnchorReceipt {
+            signature: Some(tx_hash),
+            slot: receipt.get("blockNumber").and_then(parse_quantity),
+            ..Default::default()
+        })
+    }
+}
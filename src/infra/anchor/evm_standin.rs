@@ -0,0 +1,113 @@
+//! In-memory JSON-RPC stand-in for an EVM node hosting root registry contracts.
+//!
+//! Implements just enough of the Ethereum JSON-RPC API for `EvmAnchor`: `eth_chainId`,
+//! `eth_blockNumber`, `eth_accounts`, `eth_call` (`root()`), `eth_sendTransaction`
+//! (`setRoot(bytes32)`) and `eth_getTransactionReceipt`. Every address behaves as its own
+//! registry contract. Each transaction is mined immediately into a new block. State is lost on
+//! exit. Served by the `evm_standin` binary; tests mount the router in-process.
+
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use primitive_types::H256;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// The single unlocked account transactions are sent from.
+const STANDIN_ACCOUNT: &str = "0x00000000000000000000000000000000000000a1";
+const CHAIN_ID: &str = "0x539"; // 1337, the usual dev chain id
+
+#[derive(Default)]
+struct Chain {
+    block: u64,
+    /// Lower-cased contract address -> stored root.
+    roots: HashMap<String, H256>,
+    /// Transaction hash -> (block number, success).
+    receipts: HashMap<String, (u64, bool)>,
+}
+
+type Shared = Arc<Mutex<Chain>>;
+
+/// Router answering JSON-RPC requests on `POST /`.
+pub fn router() -> Router {
+    Router::new()
+        .route("/", post(handle))
+        .with_state(Shared::default())
+}
+
+async fn handle(State(chain): State<Shared>, Json(request): Json<Value>) -> Json<Value> {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let method = request.get("method").and_then(Value::as_str).unwrap_or_default();
+    let params = request.get("params").cloned().unwrap_or_else(|| json!([]));
+    let response = match dispatch(&mut chain.lock().unwrap(), method, &params) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => {
+            json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+        }
+    };
+    Json(response)
+}
+
+fn dispatch(chain: &mut Chain, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+    let invalid = |msg: &str| (-32602, msg.to_string());
+    match method {
+        "eth_chainId" => Ok(json!(CHAIN_ID)),
+        "eth_blockNumber" => Ok(json!(format!("0x{:x}", chain.block))),
+        "eth_accounts" => Ok(json!([STANDIN_ACCOUNT])),
+        "eth_call" => {
+            let (to, data) = call_target(params).ok_or_else(|| invalid("expected [{ to, data }]"))?;
+            if !data.starts_with("ebf0c717") {
+                return Err((-32000, "execution reverted: unknown function".to_string()));
+            }
+            let root = chain.roots.get(&to).copied().unwrap_or_else(H256::zero);
+            Ok(json!(format!("0x{}", hex::encode(root.as_bytes()))))
+        }
+        "eth_sendTransaction" => {
+            let (to, data) = call_target(params).ok_or_else(|| invalid("expected [{ from, to, data }]"))?;
+            chain.block += 1;
+            let tx_hash = format!(
+                "0x{}",
+                hex::encode(Sha256::digest(format!("{}:{}:{}", chain.block, to, data)))
+            );
+            let ok = match data.strip_prefix("dab5f340").map(hex::decode) {
+                Some(Ok(bytes)) if bytes.len() == 32 => {
+                    chain.roots.insert(to, H256::from_slice(&bytes));
+                    true
+                }
+                _ => false,
+            };
+            chain.receipts.insert(tx_hash.clone(), (chain.block, ok));
+            Ok(json!(tx_hash))
+        }
+        "eth_getTransactionReceipt" => {
+            let hash = params
+                .get(0)
+                .and_then(Value::as_str)
+                .ok_or_else(|| invalid("expected [hash]"))?;
+            Ok(match chain.receipts.get(hash) {
+                Some((block, ok)) => json!({
+                    "transactionHash": hash,
+                    "blockNumber": format!("0x{:x}", block),
+                    "status": if *ok { "0x1" } else { "0x0" },
+                }),
+                None => Value::Null,
+            })
+        }
+        other => Err((-32601, format!("method {} not supported by the stand-in", other))),
+    }
+}
+
+/// `(to, data)` of the call object in `params[0]`, lower-cased and without `0x` prefixes.
+fn call_target(params: &Value) -> Option<(String, String)> {
+    let call = params.get(0)?;
+    let to = call.get("to")?.as_str()?.to_lowercase();
+    let data = call
+        .get("data")
+        .or_else(|| call.get("input"))?
+        .as_str()?
+        .trim_start_matches("0x")
+        .to_lowercase();
+    Some((to, data))
+}
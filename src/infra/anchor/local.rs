@@ -0,0 +1,143 @@
+//! Offline anchors: a local JSON file and an in-process value.
+//!
+//! Neither provides any external trust; they exist so the write/commit/restart flow can run
+//! without an RPC endpoint or a funded keypair.
+
+use async_trait::async_trait;
+use primitive_types::H256;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+use super::{AnchorReceipt, AnchoredState, RootAnchor, StaleAnchor};
+
+#[derive(Serialize, Deserialize)]
+struct AnchoredRoot {
+    root: String, // Hex encoded root
+    timestamp: u64,
+}
+
+/// Stores the anchored root in a JSON file (written atomically via rename).
+///
+/// A missing file reads as the zero root, like a freshly initialized on-chain account.
+pub struct FileAnchor {
+    path: PathBuf,
+}
+
+impl FileAnchor {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn write_file(&self, root: H256) -> anyhow::Result<()> {
+        let state = AnchoredRoot {
+            root: hex::encode(root.as_bytes()),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs(),
+        };
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&state)?)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// The stored root and the Unix time it was written (`None` if the file does not exist).
+    fn read_file(&self) -> anyhow::Result<Option<(H256, u64)>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let state: AnchoredRoot = serde_json::from_str(&fs::read_to_string(&self.path)?)?;
+        let bytes = hex::decode(state.root)?;
+        if bytes.len() != 32 {
+            return Err(anyhow::anyhow!("Invalid root length in anchor file {:?}", self.path));
+        }
+        Ok(Some((H256::from_slice(&bytes), state.timestamp)))
+    }
+}
+
+#[async_trait]
+impl RootAnchor for FileAnchor {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    async fn initialize(&self) -> anyhow::Result<()> {
+        if !self.path.exists() {
+            self.write_file(H256::zero())?;
+            println!("> Anchor: Initialized file anchor at {:?}", self.path);
+        }
+        Ok(())
+    }
+
+    async fn read_root(&self) -> anyhow::Result<H256> {
+        Ok(self.read_file()?.map(|(root, _)| root).unwrap_or_else(H256::zero))
+    }
+
+    async fn read_record(&self) -> anyhow::Result<AnchoredState> {
+        Ok(match self.read_file()? {
+            Some((root, timestamp)) => AnchoredState {
+                address: Some(self.path.display().to_string()),
+                timestamp: Some(timestamp as i64),
+                ..AnchoredState::root_only(self.name(), root)
+            },
+            None => AnchoredState::root_only(self.name(), H256::zero()),
+        })
+    }
+
+    async fn write_root(&self, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        self.write_file(new_root)?;
+        println!(
+            "> Anchor: Wrote new root to {:?}: {}",
+            self.path,
+            hex::encode(new_root.as_bytes())
+        );
+        Ok(AnchorReceipt::default())
+    }
+}
+
+/// Keeps the anchored root in memory. Share one instance (via `Arc`) across restarts in tests.
+#[derive(Default)]
+pub struct MemoryAnchor {
+    root: Mutex<H256>,
+}
+
+impl MemoryAnchor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RootAnchor for MemoryAnchor {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    async fn initialize(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn read_root(&self) -> anyhow::Result<H256> {
+        Ok(*self.root.lock().await)
+    }
+
+    async fn write_root(&self, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        *self.root.lock().await = new_root;
+        Ok(AnchorReceipt::default())
+    }
+
+    async fn write_root_after(&self, expected_prev: H256, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        let mut root = self.root.lock().await;
+        if *root != expected_prev {
+            return Err(StaleAnchor {
+                expected: expected_prev,
+                actual: Some(*root),
+            }
+            .into());
+        }
+        *root = new_root;
+        Ok(AnchorReceipt::default())
+    }
+}
@@ -0,0 +1,258 @@
+//! Root anchoring backends.
+//!
+//! `RootManager` and the bootstrap handlers publish the committed SMT root through a
+//! [`RootAnchor`] instead of calling Solana directly. The backend is picked by `ANCHOR_BACKEND`:
+//!
+//! - `solana` (default): the on-chain Merkle root account of `SOLANA_NAMESPACE` (see
+//!   `infra::solana`).
+//! - `file`: a local JSON file (`ANCHOR_FILE_PATH`), for offline development and CI.
+//! - `memory`: an in-process value that is lost on exit, for tests.
+//! - `log`: a local MAC-chained transparency log (`ANCHOR_LOG_PATH`).
+//!
+//! With `ANCHOR_TARGETS` set, roots go to several targets at once through [`MultiAnchor`]
+//! (`ANCHOR_QUORUM` decides which must succeed). Targets are written `kind` or `kind=arg`:
+//! `solana=<rpc_url>`, `file=<path>`, `log=<path>`, `memory`, or `evm=<contract>@<rpc_url>`
+//! for an EVM-style registry contract (see `evm`).
+
+use async_trait::async_trait;
+use primitive_types::H256;
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::infra::{config, sealing};
+
+pub mod evm;
+pub mod evm_standin;
+pub mod local;
+pub mod multi;
+pub mod solana;
+pub mod transparency_log;
+
+pub use evm::EvmAnchor;
+pub use local::{FileAnchor, MemoryAnchor};
+pub use multi::{AnchorTarget, MultiAnchor, QuorumPolicy};
+pub use solana::SolanaAnchor;
+pub use transparency_log::TransparencyLogAnchor;
+
+/// Evidence returned by a successful anchor write.
+#[derive(Debug, Clone, Default)]
+pub struct AnchorReceipt {
+    /// Transaction signature, when the backend is a chain.
+    pub signature: Option<String>,
+    /// Slot the transaction landed in, when known.
+    pub slot: Option<u64>,
+    /// Transaction fee paid, in lamports (Solana; summed over targets for `MultiAnchor`).
+    pub fee_lamports: Option<u64>,
+    /// Outcome per target, when the root went to several (see `MultiAnchor`).
+    pub targets: Vec<AnchorTargetStatus>,
+}
+
+/// Outcome of one target of a multi-target anchor write.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnchorTargetStatus {
+    pub target: String,
+    pub ok: bool,
+    pub signature: Option<String>,
+    pub slot: Option<u64>,
+    pub fee_lamports: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// The anchored root with whatever the backend records about it, as read live from the anchor
+/// (returned by `GET /api/roots`). Fields a backend does not keep are `None`. Not to be
+/// confused with `storage::anchor_history::AnchorRecord`, this service's own log of anchors.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnchoredState {
+    pub backend: String,
+    #[serde(serialize_with = "serialize_root")]
+    pub root: H256,
+    /// Account holding the root, for on-chain backends.
+    pub address: Option<String>,
+    /// Slot the record was read at.
+    pub slot: Option<u64>,
+    /// Unix time the root was written.
+    pub timestamp: Option<i64>,
+    /// Number of root updates the anchor has applied.
+    pub sequence: Option<u64>,
+    pub authority: Option<String>,
+    pub pending_authority: Option<String>,
+    /// One entry per target, when the root goes to several (see `MultiAnchor`).
+    pub targets: Vec<AnchorTargetRecord>,
+}
+
+impl AnchoredState {
+    /// A record holding only the root.
+    pub fn root_only(backend: &str, root: H256) -> Self {
+        Self {
+            backend: backend.to_string(),
+            root,
+            address: None,
+            slot: None,
+            timestamp: None,
+            sequence: None,
+            authority: None,
+            pending_authority: None,
+            targets: Vec::new(),
+        }
+    }
+}
+
+/// What one target of a multi-target anchor holds (`record`), or why it could not be read.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnchorTargetRecord {
+    pub target: String,
+    pub record: Option<AnchoredState>,
+    pub error: Option<String>,
+}
+
+fn serialize_root<S: serde::Serializer>(root: &H256, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode(root.as_bytes()))
+}
+
+/// A conditional write found the anchor no longer holding the root it expected to replace:
+/// another writer, or a delayed transaction, moved it. Carried inside `anyhow::Error`; check
+/// with `err.downcast_ref::<StaleAnchor>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleAnchor {
+    pub expected: H256,
+    /// The root the anchor holds now, when it could be read back.
+    pub actual: Option<H256>,
+}
+
+impl fmt::Display for StaleAnchor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Stale anchor: expected root {}", hex::encode(self.expected.as_bytes()))?;
+        match self.actual {
+            Some(actual) => write!(f, ", anchor holds {}", hex::encode(actual.as_bytes())),
+            None => write!(f, ", anchor has moved"),
+        }
+    }
+}
+
+impl std::error::Error for StaleAnchor {}
+
+/// The fee of a write that failed after its transaction landed (e.g. the program rejected it
+/// as stale): the fee was charged all the same. Attached to the write's error as context, which
+/// keeps its message and what it downcasts to; read it with [`ChargedFee::of`].
+#[derive(Debug, Clone)]
+pub struct ChargedFee {
+    pub fee_lamports: u64,
+    message: String,
+}
+
+impl ChargedFee {
+    /// Attaches `fee_lamports` to `e`, unless it already carries a fee.
+    pub fn attach(e: anyhow::Error, fee_lamports: u64) -> anyhow::Error {
+        if Self::of(&e).is_some() {
+            return e;
+        }
+        let message = e.to_string();
+        e.context(ChargedFee {
+            fee_lamports,
+            message,
+        })
+    }
+
+    /// The fee charged by the failed write behind `e`, if any.
+    pub fn of(e: &anyhow::Error) -> Option<u64> {
+        e.downcast_ref::<ChargedFee>().map(|c| c.fee_lamports)
+    }
+}
+
+impl fmt::Display for ChargedFee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (fee of {} lamports charged)", self.message, self.fee_lamports)
+    }
+}
+
+/// A place the trusted root is anchored to (normally a blockchain account).
+#[async_trait]
+pub trait RootAnchor: Send + Sync {
+    /// Short backend name used in logs and status output.
+    fn name(&self) -> &str;
+
+    /// Creates the anchor (e.g. the on-chain account) if it does not exist yet.
+    async fn initialize(&self) -> anyhow::Result<()>;
+
+    /// Reads the currently anchored root.
+    async fn read_root(&self) -> anyhow::Result<H256>;
+
+    /// Reads the anchored root together with the metadata the backend keeps about it. The
+    /// default reports only the root.
+    async fn read_record(&self) -> anyhow::Result<AnchoredState> {
+        Ok(AnchoredState::root_only(self.name(), self.read_root().await?))
+    }
+
+    /// Anchors `new_root`, replacing the previous one.
+    async fn write_root(&self, new_root: H256) -> anyhow::Result<AnchorReceipt>;
+
+    /// Anchors `new_root` only if the anchor still holds `expected_prev`, failing with
+    /// [`StaleAnchor`] otherwise. The default reads the root back first, which narrows the race
+    /// but cannot close it; the Solana program checks on-chain.
+    async fn write_root_after(&self, expected_prev: H256, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        let actual = self.read_root().await?;
+        if actual != expected_prev {
+            return Err(StaleAnchor {
+                expected: expected_prev,
+                actual: Some(actual),
+            }
+            .into());
+        }
+        self.write_root(new_root).await
+    }
+}
+
+/// Builds the anchor selected by `ANCHOR_TARGETS` (several targets) or `ANCHOR_BACKEND`.
+pub fn from_config() -> anyhow::Result<Arc<dyn RootAnchor>> {
+    if let Some(specs) = config::anchor_targets() {
+        let targets = specs
+            .split(',')
+            .map(str::trim)
+            .filter(|spec| !spec.is_empty())
+            .map(|spec| Ok(AnchorTarget::new(spec, target_from_spec(spec)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let policy = QuorumPolicy::parse(&config::anchor_quorum(), targets.len())?;
+        println!(
+            "> Anchor: {} targets, policy {}: {}",
+            targets.len(),
+            policy,
+            targets.iter().map(|t| t.label.as_str()).collect::<Vec<_>>().join(", ")
+        );
+        return Ok(Arc::new(MultiAnchor::new(targets, policy)?));
+    }
+    target_from_spec(&config::anchor_backend())
+}
+
+/// Builds one anchor from a target spec (`kind` or `kind=arg`, see the module docs).
+pub fn target_from_spec(spec: &str) -> anyhow::Result<Arc<dyn RootAnchor>> {
+    let (kind, arg) = match spec.split_once('=') {
+        Some((kind, arg)) => (kind.trim().to_lowercase(), Some(arg.trim().to_string())),
+        None => (spec.trim().to_lowercase(), None),
+    };
+    match (kind.as_str(), arg) {
+        ("solana", None) => Ok(Arc::new(SolanaAnchor::from_config()?)),
+        ("solana", Some(rpc_url)) => Ok(Arc::new(SolanaAnchor::new(&rpc_url)?)),
+        ("file", path) => Ok(Arc::new(FileAnchor::new(
+            path.unwrap_or_else(config::anchor_file_path),
+        ))),
+        ("memory", None) => Ok(Arc::new(MemoryAnchor::new())),
+        ("log", path) => {
+            let key = sealing::from_config()?.key("transparency_log")?;
+            Ok(Arc::new(TransparencyLogAnchor::new(
+                path.unwrap_or_else(config::anchor_log_path),
+                key,
+            )))
+        }
+        ("evm", Some(arg)) => {
+            let (contract, rpc_url) = arg
+                .split_once('@')
+                .ok_or_else(|| anyhow::anyhow!("Anchor target '{}' must be evm=<contract>@<rpc_url>", spec))?;
+            Ok(Arc::new(EvmAnchor::new(rpc_url, contract)))
+        }
+        _ => Err(anyhow::anyhow!(
+            "Unknown anchor target '{}' (expected solana, file, memory, log or evm)",
+            spec
+        )),
+    }
+}
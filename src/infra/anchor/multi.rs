@@ -0,0 +1,443 @@
+//! Anchoring every root to several targets under a quorum policy.
+//!
+//! `MultiAnchor` writes each root to all configured targets concurrently and decides whether
+//! the write counts as anchored according to [`QuorumPolicy`]. The per-target outcome is
+//! returned in the receipt (and recorded in `anchor_history`), including targets that failed
+//! while the policy was still met. A failed write is retried as a whole by `RootManager`.
+//!
+//! A conditional write (`write_root_after`) is sent to every target as a conditional write of
+//! its own, so each backend enforces it where the root lives (on Solana, on-chain). A target
+//! is expected to hold the root this instance last confirmed on it, so a target that missed a
+//! write catches up on the next one. A target that already holds the new root counts as
+//! written; any other stale target counts as failed, and when the stale targets keep the
+//! policy from being met the whole write fails with [`StaleAnchor`].
+
+use async_trait::async_trait;
+use primitive_types::H256;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinSet;
+
+use super::{
+    AnchorReceipt, AnchorTargetRecord, AnchorTargetStatus, AnchoredState, ChargedFee, RootAnchor,
+    StaleAnchor,
+};
+
+/// Which targets must succeed for a root to count as anchored (`ANCHOR_QUORUM`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuorumPolicy {
+    /// Every target (default).
+    All,
+    /// At least this many targets.
+    Quorum(usize),
+    /// The first target; the others are best-effort.
+    Primary,
+}
+
+impl QuorumPolicy {
+    /// Parses `all`, `majority`, `quorum=<n>` or `primary` for `targets` targets.
+    pub fn parse(value: &str, targets: usize) -> anyhow::Result<Self> {
+        let value = value.trim().to_lowercase();
+        let policy = match value.as_str() {
+            "all" => Self::All,
+            "majority" => Self::Quorum(targets / 2 + 1),
+            "primary" => Self::Primary,
+            other => {
+                let n = other
+                    .strip_prefix("quorum=")
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Invalid ANCHOR_QUORUM '{}' (expected all, majority, quorum=<n> or primary)",
+                            other
+                        )
+                    })?;
+                Self::Quorum(n)
+            }
+        };
+        if let Self::Quorum(n) = policy {
+            if n == 0 || n > targets {
+                return Err(anyhow::anyhow!(
+                    "ANCHOR_QUORUM needs between 1 and {} targets (got {})",
+                    targets,
+                    n
+                ));
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Whether the outcome `ok` (one entry per target, in order) satisfies the policy.
+    fn satisfied(&self, ok: &[bool]) -> bool {
+        match self {
+            Self::All => ok.iter().all(|ok| *ok),
+            Self::Quorum(n) => ok.iter().filter(|ok| **ok).count() >= *n,
+            Self::Primary => ok.first().copied().unwrap_or(false),
+        }
+    }
+}
+
+impl fmt::Display for QuorumPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => write!(f, "all"),
+            Self::Quorum(n) => write!(f, "quorum={}", n),
+            Self::Primary => write!(f, "primary"),
+        }
+    }
+}
+
+/// One anchor target and the label it is reported under.
+#[derive(Clone)]
+pub struct AnchorTarget {
+    pub label: String,
+    pub anchor: Arc<dyn RootAnchor>,
+}
+
+impl AnchorTarget {
+    pub fn new(label: impl Into<String>, anchor: Arc<dyn RootAnchor>) -> Self {
+        Self {
+            label: label.into(),
+            anchor,
+        }
+    }
+}
+
+/// Anchors every root to all `targets`; succeeds when `policy` is met.
+pub struct MultiAnchor {
+    targets: Vec<AnchorTarget>,
+    policy: QuorumPolicy,
+    /// Root each target (by index) last confirmed to this instance.
+    held: Mutex<Vec<Option<H256>>>,
+}
+
+impl MultiAnchor {
+    pub fn new(targets: Vec<AnchorTarget>, policy: QuorumPolicy) -> anyhow::Result<Self> {
+        if targets.is_empty() {
+            return Err(anyhow::anyhow!("At least one anchor target is required"));
+        }
+        for (i, target) in targets.iter().enumerate() {
+            if targets[..i].iter().any(|t| t.label == target.label) {
+                return Err(anyhow::anyhow!("Duplicate anchor target '{}'", target.label));
+            }
+        }
+        if let QuorumPolicy::Quorum(n) = policy {
+            if n == 0 || n > targets.len() {
+                return Err(anyhow::anyhow!(
+                    "Quorum of {} is not reachable with {} targets",
+                    n,
+                    targets.len()
+                ));
+            }
+        }
+        let held = Mutex::new(vec![None; targets.len()]);
+        Ok(Self {
+            targets,
+            policy,
+            held,
+        })
+    }
+
+    pub fn policy(&self) -> QuorumPolicy {
+        self.policy
+    }
+
+    pub fn targets(&self) -> &[AnchorTarget] {
+        &self.targets
+    }
+
+    /// Runs `op` against every target (with its index) concurrently; results are in target
+    /// order.
+    async fn each<T, F, Fut>(&self, op: F) -> Vec<anyhow::Result<T>>
+    where
+        T: Send + 'static,
+        F: Fn(usize, Arc<dyn RootAnchor>) -> Fut,
+        Fut: std::future::Future<Output = anyhow::Result<T>> + Send + 'static,
+    {
+        let mut set = JoinSet::new();
+        for (i, target) in self.targets.iter().enumerate() {
+            let fut = op(i, target.anchor.clone());
+            set.spawn(async move { (i, fut.await) });
+        }
+        let mut results: Vec<Option<anyhow::Result<T>>> = self.targets.iter().map(|_| None).collect();
+        while let Some(joined) = set.join_next().await {
+            match joined {
+                Ok((i, result)) => results[i] = Some(result),
+                Err(e) => eprintln!("> Anchor: target task failed: {}", e),
+            }
+        }
+        results
+            .into_iter()
+            .map(|r| r.unwrap_or_else(|| Err(anyhow::anyhow!("target task panicked"))))
+            .collect()
+    }
+
+    /// The root reported by enough targets (`results`, in target order) to meet the policy.
+    fn agreed_root(&self, results: &[anyhow::Result<H256>]) -> anyhow::Result<H256> {
+        let mut tally: Vec<(H256, usize)> = Vec::new();
+        for root in results.iter().filter_map(|r| r.as_ref().ok()) {
+            match tally.iter_mut().find(|(r, _)| r == root) {
+                Some((_, count)) => *count += 1,
+                None => tally.push((*root, 1)),
+            }
+        }
+        // Earliest target wins ties.
+        let best = tally.iter().fold(None::<(H256, usize)>, |best, &(root, count)| match best {
+            Some((_, c)) if c >= count => best,
+            _ => Some((root, count)),
+        });
+        if let Some((root, count)) = best {
+            let ok: Vec<bool> = results
+                .iter()
+                .map(|r| matches!(r, Ok(x) if *x == root))
+                .collect();
+            if self.policy.satisfied(&ok) {
+                if count < self.targets.len() {
+                    eprintln!(
+                        "> Anchor: {} of {} targets agree on root {}",
+                        count,
+                        self.targets.len(),
+                        hex::encode(root.as_bytes())
+                    );
+                }
+                return Ok(root);
+            }
+        }
+        let seen = self
+            .targets
+            .iter()
+            .zip(results)
+            .map(|(t, r)| match r {
+                Ok(root) => format!("{}={}", t.label, hex::encode(root.as_bytes())),
+                Err(e) => format!("{}: {}", t.label, e),
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        Err(anyhow::anyhow!(
+            "Anchor targets do not agree on a root ({}): {}",
+            self.policy,
+            seen
+        ))
+    }
+
+    /// Notes `root` as held by every target whose write succeeded.
+    fn note_written(&self, root: H256, results: &[anyhow::Result<AnchorReceipt>]) {
+        let mut held = self.held.lock().unwrap();
+        for (held, result) in held.iter_mut().zip(results) {
+            if result.is_ok() {
+                *held = Some(root);
+            }
+        }
+    }
+
+    /// Checks `results` against the policy and builds the receipt. `stale` is the error to
+    /// return instead of a generic one when the policy is missed because targets were stale.
+    /// The fees every target was charged, failed or not, travel with the error as
+    /// [`ChargedFee`].
+    fn receipt(
+        &self,
+        results: Vec<anyhow::Result<AnchorReceipt>>,
+        stale: Option<StaleAnchor>,
+    ) -> anyhow::Result<AnchorReceipt> {
+        let ok: Vec<bool> = results.iter().map(|r| r.is_ok()).collect();
+        let succeeded = ok.iter().filter(|ok| **ok).count();
+        if !self.policy.satisfied(&ok) {
+            let failures = self.describe_failures(&results);
+            let charged: Vec<u64> = results
+                .iter()
+                .filter_map(|r| match r {
+                    Ok(receipt) => receipt.fee_lamports,
+                    Err(e) => ChargedFee::of(e),
+                })
+                .collect();
+            let e = match stale {
+                Some(stale) => {
+                    eprintln!("> Anchor: ✗ Stale targets ({}): {}", self.policy, failures);
+                    stale.into()
+                }
+                None => anyhow::anyhow!(
+                    "Anchor policy {} not met ({}/{} targets succeeded): {}",
+                    self.policy,
+                    succeeded,
+                    self.targets.len(),
+                    failures
+                ),
+            };
+            if charged.is_empty() {
+                return Err(e);
+            }
+            return Err(ChargedFee::attach(e, charged.iter().sum()));
+        }
+
+        let statuses: Vec<AnchorTargetStatus> = self
+            .targets
+            .iter()
+            .zip(results)
+            .map(|(target, result)| match result {
+                Ok(receipt) => AnchorTargetStatus {
+                    target: target.label.clone(),
+                    ok: true,
+                    signature: receipt.signature,
+                    slot: receipt.slot,
+                    fee_lamports: receipt.fee_lamports,
+                    error: None,
+                },
+                Err(e) => {
+                    eprintln!("> Anchor: ✗ Best-effort target {} failed: {}", target.label, e);
+                    AnchorTargetStatus {
+                        target: target.label.clone(),
+                        ok: false,
+                        signature: None,
+                        slot: None,
+                        fee_lamports: ChargedFee::of(&e),
+                        error: Some(e.to_string()),
+                    }
+                }
+            })
+            .collect();
+        // The receipt's own evidence comes from the first target that succeeded.
+        let first = statuses.iter().find(|s| s.ok);
+        let fees: Vec<u64> = statuses.iter().filter_map(|s| s.fee_lamports).collect();
+        Ok(AnchorReceipt {
+            signature: first.and_then(|s| s.signature.clone()),
+            slot: first.and_then(|s| s.slot),
+            fee_lamports: (!fees.is_empty()).then(|| fees.iter().sum()),
+            targets: statuses,
+        })
+    }
+
+    fn describe_failures<T>(&self, results: &[anyhow::Result<T>]) -> String {
+        self.targets
+            .iter()
+            .zip(results)
+            .filter_map(|(t, r)| r.as_ref().err().map(|e| format!("{}: {}", t.label, e)))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+#[async_trait]
+impl RootAnchor for MultiAnchor {
+    fn name(&self) -> &str {
+        "multi"
+    }
+
+    async fn initialize(&self) -> anyhow::Result<()> {
+        let results = self.each(|_, anchor| async move { anchor.initialize().await }).await;
+        let ok: Vec<bool> = results.iter().map(|r| r.is_ok()).collect();
+        let failures = self.describe_failures(&results);
+        if !self.policy.satisfied(&ok) {
+            return Err(anyhow::anyhow!(
+                "Anchor targets failed to initialize ({}): {}",
+                self.policy,
+                failures
+            ));
+        }
+        if !failures.is_empty() {
+            eprintln!("> Anchor: Some targets failed to initialize (policy still met): {}", failures);
+        }
+        Ok(())
+    }
+
+    /// The primary's root under `primary`; otherwise the root reported by enough targets to
+    /// meet the policy.
+    async fn read_root(&self) -> anyhow::Result<H256> {
+        if self.policy == QuorumPolicy::Primary {
+            return self.targets[0].anchor.read_root().await;
+        }
+        let results = self.each(|_, anchor| async move { anchor.read_root().await }).await;
+        self.agreed_root(&results)
+    }
+
+    /// The agreed root (as `read_root`), with what every target holds.
+    async fn read_record(&self) -> anyhow::Result<AnchoredState> {
+        let results = self.each(|_, anchor| async move { anchor.read_record().await }).await;
+        let roots: Vec<anyhow::Result<H256>> = results
+            .iter()
+            .map(|r| match r {
+                Ok(record) => Ok(record.root),
+                Err(e) => Err(anyhow::anyhow!("{}", e)),
+            })
+            .collect();
+        let root = match self.policy {
+            QuorumPolicy::Primary => match &roots[0] {
+                Ok(root) => *root,
+                Err(e) => return Err(anyhow::anyhow!("{}: {}", self.targets[0].label, e)),
+            },
+            _ => self.agreed_root(&roots)?,
+        };
+        let targets = self
+            .targets
+            .iter()
+            .zip(results)
+            .map(|(target, result)| match result {
+                Ok(record) => AnchorTargetRecord {
+                    target: target.label.clone(),
+                    record: Some(record),
+                    error: None,
+                },
+                Err(e) => AnchorTargetRecord {
+                    target: target.label.clone(),
+                    record: None,
+                    error: Some(e.to_string()),
+                },
+            })
+            .collect();
+        Ok(AnchoredState {
+            targets,
+            ..AnchoredState::root_only(self.name(), root)
+        })
+    }
+
+    async fn write_root(&self, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        let results = self
+            .each(|_, anchor| async move { anchor.write_root(new_root).await })
+            .await;
+        self.note_written(new_root, &results);
+        self.receipt(results, None)
+    }
+
+    /// Sends a conditional write to every target, expecting the root it last confirmed to this
+    /// instance (`expected_prev` if none yet). Nothing is read up front, so no target can move
+    /// between a read and the write.
+    async fn write_root_after(&self, expected_prev: H256, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        let expected: Vec<H256> = self
+            .held
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|held| held.unwrap_or(expected_prev))
+            .collect();
+        let results = self
+            .each(|i, anchor| {
+                let expected = expected[i];
+                async move { anchor.write_root_after(expected, new_root).await }
+            })
+            .await;
+
+        let mut stale = None;
+        let results: Vec<anyhow::Result<AnchorReceipt>> = results
+            .into_iter()
+            .map(|result| match result {
+                Err(e) => match e.downcast_ref::<StaleAnchor>().copied() {
+                    // An earlier attempt already landed the new root on this target.
+                    Some(s) if s.actual == Some(new_root) => Ok(AnchorReceipt {
+                        fee_lamports: ChargedFee::of(&e),
+                        ..AnchorReceipt::default()
+                    }),
+                    Some(s) => {
+                        stale.get_or_insert(StaleAnchor {
+                            expected: expected_prev,
+                            actual: s.actual,
+                        });
+                        Err(e)
+                    }
+                    None => Err(e),
+                },
+                ok => ok,
+            })
+            .collect();
+        self.note_written(new_root, &results);
+        self.receipt(results, stale)
+    }
+}
@@ -0,0 +1,83 @@
+//! Anchors roots in the Solana Merkle root account.
+
+use async_trait::async_trait;
+use primitive_types::H256;
+
+use super::{AnchorReceipt, AnchoredState, RootAnchor};
+use crate::infra::config;
+use crate::infra::solana::SolanaClient;
+
+/// Publishes roots through `infra::solana` to the root account of `namespace` (program id and
+/// payer from config). Keeps one [`SolanaClient`] for its lifetime, so every read and write
+/// reuses the same RPC connection and the payer's signer is loaded once.
+pub struct SolanaAnchor {
+    client: SolanaClient,
+    namespace: String,
+}
+
+impl SolanaAnchor {
+    /// Anchor on `rpc_url`, in the `SOLANA_NAMESPACE` root account.
+    pub fn new(rpc_url: &str) -> anyhow::Result<Self> {
+        Ok(Self::with_client(SolanaClient::new(rpc_url)?, config::solana_namespace()))
+    }
+
+    pub fn with_client(client: SolanaClient, namespace: impl Into<String>) -> Self {
+        Self {
+            client,
+            namespace: namespace.into(),
+        }
+    }
+
+    /// Anchor on `SOLANA_RPC_URL`.
+    pub fn from_config() -> anyhow::Result<Self> {
+        Self::new(&config::solana_rpc_url())
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// The client shared by all reads and writes of this anchor.
+    pub fn client(&self) -> &SolanaClient {
+        &self.client
+    }
+}
+
+#[async_trait]
+impl RootAnchor for SolanaAnchor {
+    fn name(&self) -> &str {
+        "solana"
+    }
+
+    async fn initialize(&self) -> anyhow::Result<()> {
+        self.client.initialize(&self.namespace).await
+    }
+
+    async fn read_root(&self) -> anyhow::Result<H256> {
+        self.client.read_root(&self.namespace).await
+    }
+
+    async fn read_record(&self) -> anyhow::Result<AnchoredState> {
+        let snapshot = self.client.read_snapshot(&self.namespace).await?;
+        let account = snapshot.account;
+        Ok(AnchoredState {
+            address: Some(snapshot.address.to_string()),
+            slot: Some(snapshot.slot),
+            timestamp: Some(account.timestamp),
+            sequence: Some(account.sequence),
+            authority: Some(account.authority.to_string()),
+            pending_authority: account.pending_authority.map(|p| p.to_string()),
+            ..AnchoredState::root_only(self.name(), H256::from(account.root))
+        })
+    }
+
+    async fn write_root(&self, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        self.client.write_root(&self.namespace, new_root).await
+    }
+
+    async fn write_root_after(&self, expected_prev: H256, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        self.client
+            .write_root_after(&self.namespace, expected_prev, new_root)
+            .await
+    }
+}
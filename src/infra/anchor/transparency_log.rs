@@ -0,0 +1,314 @@
+//! A local append-only transparency log of anchored roots.
+//!
+//! Every write appends one JSON line holding the root, its index and a keyed MAC that also
+//! covers the previous entry's MAC, then reseals the log head (last index and MAC) into a
+//! separate `<path>.head` file. Edited or reordered entries break the chain, and trailing
+//! entries cut off the end of the log no longer match the sealed head. Rolling back the log
+//! and its head file together to an earlier copy is not detected here; that takes a witness
+//! the host cannot roll back, such as a chain.
+//!
+//! The whole log is verified when it is first read and whenever its size or modification time
+//! changes; other reads only check the cached head against the sealed head file. The key comes
+//! from the sealing key provider (see `infra::sealing`), so this is only as independent as that
+//! key. It is a second witness next to a chain, not a replacement for one.
+
+use async_trait::async_trait;
+use primitive_types::H256;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::sync::Mutex;
+
+use super::{AnchorReceipt, AnchoredState, RootAnchor};
+use crate::infra::sealing;
+
+/// Domain separator of the entry MAC.
+const ENTRY_DOMAIN: &[u8] = b"VMTLOG1";
+/// Domain separator of the sealed head MAC.
+const HEAD_DOMAIN: &[u8] = b"VMTLOGHEAD1";
+
+#[derive(Serialize, Deserialize)]
+struct LogEntry {
+    index: u64,
+    root: String,     // Hex encoded root
+    prev_mac: String, // Hex MAC of the previous entry (empty for the first)
+    timestamp: u64,
+    mac: String,
+}
+
+/// The sealed head: index and MAC of the last entry, so truncation is detected.
+#[derive(Serialize, Deserialize)]
+struct SealedHead {
+    index: u64,
+    mac: String, // Hex MAC of the last entry
+    seal: String,
+}
+
+/// Last entry of a verified log: root, index and MAC.
+type Head = (H256, u64, Vec<u8>);
+
+/// A verified log, keyed by the file size and modification time it was verified at.
+struct Verified {
+    len: u64,
+    modified: Option<SystemTime>,
+    head: Option<Head>,
+}
+
+/// Anchors roots by appending them to a MAC-chained JSON-lines file.
+pub struct TransparencyLogAnchor {
+    path: PathBuf,
+    head_path: PathBuf,
+    key: [u8; 32],
+    /// The last verified state of the log. Also serializes appends (the index and chain come
+    /// from the last entry).
+    verified: Mutex<Option<Verified>>,
+}
+
+impl TransparencyLogAnchor {
+    pub fn new(path: impl Into<PathBuf>, key: [u8; 32]) -> Self {
+        let path = path.into();
+        let mut head_path = path.clone().into_os_string();
+        head_path.push(".head");
+        Self {
+            path,
+            head_path: head_path.into(),
+            key,
+            verified: Mutex::new(None),
+        }
+    }
+
+    /// Path of the sealed head file (`<path>.head`).
+    pub fn head_path(&self) -> &Path {
+        &self.head_path
+    }
+
+    fn head_seal_input(index: u64, mac: &[u8]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(HEAD_DOMAIN.len() + 8 + mac.len());
+        data.extend_from_slice(HEAD_DOMAIN);
+        data.extend_from_slice(&index.to_le_bytes());
+        data.extend_from_slice(mac);
+        data
+    }
+
+    /// Reads and checks the sealed head. Returns its index and MAC, or `None` if there is none.
+    fn read_head(&self) -> anyhow::Result<Option<(u64, Vec<u8>)>> {
+        if !self.head_path.exists() {
+            return Ok(None);
+        }
+        let head: SealedHead = serde_json::from_str(&fs::read_to_string(&self.head_path)?)?;
+        let mac = hex::decode(&head.mac)?;
+        let input = Self::head_seal_input(head.index, &mac);
+        sealing::verify_mac(&self.key, &input, &hex::decode(&head.seal)?)
+            .map_err(|e| anyhow::anyhow!("Transparency log head {:?}: {}", self.head_path, e))?;
+        Ok(Some((head.index, mac)))
+    }
+
+    /// Seals `index` and `mac` as the log head (write + rename).
+    fn write_head(&self, index: u64, mac: &[u8]) -> anyhow::Result<()> {
+        let head = SealedHead {
+            index,
+            mac: hex::encode(mac),
+            seal: hex::encode(sealing::mac(&self.key, &Self::head_seal_input(index, mac))),
+        };
+        let mut tmp = self.head_path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, serde_json::to_string(&head)?)?;
+        fs::rename(&tmp, &self.head_path)?;
+        Ok(())
+    }
+
+    /// Returns the file size and modification time the cache is keyed by.
+    fn file_stamp(&self) -> anyhow::Result<(u64, Option<SystemTime>)> {
+        match fs::metadata(&self.path) {
+            Ok(meta) => Ok((meta.len(), meta.modified().ok())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok((0, None)),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Returns the verified head, re-verifying the whole log only if the file changed since the
+    /// last verification. The sealed head is checked either way.
+    fn current(&self, verified: &mut Option<Verified>) -> anyhow::Result<Option<Head>> {
+        let (len, modified) = self.file_stamp()?;
+        if let Some(cached) = verified.as_ref().filter(|v| v.len == len && v.modified == modified) {
+            let sealed = self.read_head()?;
+            let matches = match (&cached.head, &sealed) {
+                (Some((_, index, mac)), Some((sealed_index, sealed_mac))) => {
+                    index == sealed_index && mac == sealed_mac
+                }
+                (None, None) => true,
+                _ => false,
+            };
+            if matches {
+                return Ok(cached.head.clone());
+            }
+        }
+        *verified = None;
+        let head = self.verify()?;
+        let (len, modified) = self.file_stamp()?;
+        *verified = Some(Verified {
+            len,
+            modified,
+            head: head.clone(),
+        });
+        Ok(head)
+    }
+
+    fn entry_mac(&self, index: u64, root: &H256, prev_mac: &[u8], timestamp: u64) -> [u8; 32] {
+        let mut data = Vec::with_capacity(ENTRY_DOMAIN.len() + 8 + prev_mac.len() + 32 + 8);
+        data.extend_from_slice(ENTRY_DOMAIN);
+        data.extend_from_slice(&index.to_le_bytes());
+        data.extend_from_slice(prev_mac);
+        data.extend_from_slice(root.as_bytes());
+        data.extend_from_slice(&timestamp.to_le_bytes());
+        sealing::mac(&self.key, &data)
+    }
+
+    /// Reads and verifies the whole log against the sealed head. Returns the last entry's root,
+    /// index and MAC.
+    fn verify(&self) -> anyhow::Result<Option<Head>> {
+        let mut last: Option<Head> = None;
+        let mut last_prev_mac = Vec::new();
+        let contents = if self.path.exists() {
+            fs::read_to_string(&self.path)?
+        } else {
+            String::new()
+        };
+        for (line_no, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: LogEntry = serde_json::from_str(line)
+                .map_err(|e| anyhow::anyhow!("Transparency log line {}: {}", line_no + 1, e))?;
+            let (expected_index, expected_prev) = match &last {
+                Some((_, index, mac)) => (index + 1, mac.clone()),
+                None => (0, Vec::new()),
+            };
+            let prev_mac = hex::decode(&entry.prev_mac)?;
+            if entry.index != expected_index || prev_mac != expected_prev {
+                return Err(anyhow::anyhow!(
+                    "Transparency log {:?} is broken at entry {} (missing or reordered entries)",
+                    self.path,
+                    entry.index
+                ));
+            }
+            let bytes = hex::decode(&entry.root)?;
+            if bytes.len() != 32 {
+                return Err(anyhow::anyhow!("Invalid root length in transparency log entry {}", entry.index));
+            }
+            let root = H256::from_slice(&bytes);
+            let data_mac = hex::decode(&entry.mac)?;
+            if self.entry_mac(entry.index, &root, &prev_mac, entry.timestamp).as_slice() != data_mac {
+                return Err(anyhow::anyhow!(
+                    "Transparency log {:?} entry {} failed MAC verification",
+                    self.path,
+                    entry.index
+                ));
+            }
+            last = Some((root, entry.index, data_mac));
+            last_prev_mac = prev_mac;
+        }
+
+        match (&last, self.read_head()?) {
+            (None, None) => {}
+            (Some((_, index, mac)), Some((head_index, head_mac)))
+                if *index == head_index && *mac == head_mac => {}
+            // A crash between appending an entry and resealing the head leaves exactly one
+            // entry past the head, chained to it.
+            (Some((_, index, mac)), Some((head_index, head_mac)))
+                if *index == head_index + 1 && last_prev_mac == head_mac =>
+            {
+                self.write_head(*index, mac)?;
+            }
+            (last, head) => {
+                return Err(anyhow::anyhow!(
+                    "Transparency log {:?} ends at entry {} but its sealed head is at entry {} \
+                     (truncated or rolled back)",
+                    self.path,
+                    last.as_ref().map_or("none".to_string(), |(_, index, _)| index.to_string()),
+                    head.map_or("none".to_string(), |(index, _)| index.to_string())
+                ));
+            }
+        }
+        Ok(last)
+    }
+}
+
+#[async_trait]
+impl RootAnchor for TransparencyLogAnchor {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    async fn initialize(&self) -> anyhow::Result<()> {
+        if !self.path.exists() {
+            fs::write(&self.path, "")?;
+            println!("> Anchor: Initialized transparency log at {:?}", self.path);
+        }
+        let mut verified = self.verified.lock().await;
+        *verified = None;
+        self.current(&mut verified)?;
+        Ok(())
+    }
+
+    async fn read_root(&self) -> anyhow::Result<H256> {
+        let mut verified = self.verified.lock().await;
+        Ok(self.current(&mut verified)?.map(|(root, _, _)| root).unwrap_or_else(H256::zero))
+    }
+
+    /// The sequence is the number of entries, so it moves with every append.
+    async fn read_record(&self) -> anyhow::Result<AnchoredState> {
+        let mut verified = self.verified.lock().await;
+        let head = self.current(&mut verified)?;
+        let root = head.as_ref().map(|(root, _, _)| *root).unwrap_or_else(H256::zero);
+        Ok(AnchoredState {
+            address: Some(self.path.display().to_string()),
+            sequence: Some(head.map_or(0, |(_, index, _)| index + 1)),
+            ..AnchoredState::root_only(self.name(), root)
+        })
+    }
+
+    async fn write_root(&self, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        let mut verified = self.verified.lock().await;
+        let (index, prev_mac) = match self.current(&mut verified)? {
+            Some((_, index, mac)) => (index + 1, mac),
+            None => (0, Vec::new()),
+        };
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        let mac = self.entry_mac(index, &new_root, &prev_mac, timestamp);
+        let entry = LogEntry {
+            index,
+            root: hex::encode(new_root.as_bytes()),
+            prev_mac: hex::encode(&prev_mac),
+            timestamp,
+            mac: hex::encode(mac),
+        };
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        file.sync_all()?;
+        drop(file);
+        self.write_head(index, &mac)?;
+        let (len, modified) = self.file_stamp()?;
+        *verified = Some(Verified {
+            len,
+            modified,
+            head: Some((new_root, index, mac.to_vec())),
+        });
+        println!(
+            "> Anchor: Appended root to transparency log {:?} (entry {}): {}",
+            self.path,
+            index,
+            hex::encode(new_root.as_bytes())
+        );
+        Ok(AnchorReceipt {
+            signature: Some(entry.mac),
+            slot: Some(index),
+            ..Default::default()
+        })
+    }
+}
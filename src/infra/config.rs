@@ -12,6 +12,96 @@ pub fn solana_program_id() -> String {
     std::env::var("SOLANA_PROGRAM_ID").expect("SOLANA_PROGRAM_ID must be set")
 }
 
+/// Namespace selecting this database's root account in the Solana program (optional, default
+/// `default`). Databases sharing one deployed program each need their own; 1 to 32 bytes.
+pub fn solana_namespace() -> String {
+    std::env::var("SOLANA_NAMESPACE")
+        .map(|v| v.trim().to_string())
+        .unwrap_or_else(|_| "default".to_string())
+}
+
+/// Where the Solana transaction signer comes from: `file` (default), `env` or `remote` (see
+/// `infra::solana::signer`).
+pub fn solana_signer() -> String {
+    std::env::var("SOLANA_SIGNER")
+        .map(|v| v.trim().to_lowercase())
+        .unwrap_or_else(|_| "file".to_string())
+}
+
+/// Keypair file used by `SOLANA_SIGNER=file` (optional, default `~/.config/solana/id.json`).
+pub fn solana_keypair_path() -> String {
+    let path = std::env::var("SOLANA_KEYPAIR_PATH").unwrap_or_else(|_| "~/.config/solana/id.json".to_string());
+    shellexpand::tilde(&path).to_string()
+}
+
+/// Base58 keypair used by `SOLANA_SIGNER=env`.
+pub fn solana_signer_secret() -> Option<String> {
+    std::env::var("SOLANA_SIGNER_SECRET")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+}
+
+/// Unix socket of the external signer used by `SOLANA_SIGNER=remote`.
+pub fn solana_signer_socket() -> Option<String> {
+    std::env::var("SOLANA_SIGNER_SOCKET")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+}
+
+/// Seconds to wait for each request to the external signer (connect, send and reply) before
+/// failing (optional, default 30).
+pub fn solana_signer_timeout_secs() -> u64 {
+    std::env::var("SOLANA_SIGNER_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(30)
+}
+
+/// Priority fee for Solana transactions: `0` (default), micro-lamports per compute unit, or
+/// `auto` (see `infra::solana::transaction`).
+pub fn solana_priority_fee() -> String {
+    std::env::var("SOLANA_PRIORITY_FEE").unwrap_or_else(|_| "0".to_string())
+}
+
+/// Cap on the `auto` priority fee in micro-lamports per compute unit (optional, default
+/// 1_000_000).
+pub fn solana_priority_fee_max() -> u64 {
+    std::env::var("SOLANA_PRIORITY_FEE_MAX")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(1_000_000)
+}
+
+/// Compute units requested per Solana transaction (optional; unset keeps the runtime default).
+pub fn solana_compute_unit_limit() -> Option<u32> {
+    std::env::var("SOLANA_COMPUTE_UNIT_LIMIT")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .filter(|v| *v > 0)
+}
+
+/// Commitment level for Solana reads and confirmations: `processed`, `confirmed` (default) or
+/// `finalized`.
+pub fn solana_commitment() -> String {
+    std::env::var("SOLANA_COMMITMENT").unwrap_or_else(|_| "confirmed".to_string())
+}
+
+/// Times a Solana transaction is re-sent after its blockhash expired unconfirmed (optional,
+/// default 3).
+pub fn solana_tx_resubmits() -> u32 {
+    std::env::var("SOLANA_TX_RESUBMITS")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(3)
+}
+
+/// Cluster used for explorer links (optional; derived from `SOLANA_RPC_URL` when unset).
+pub fn solana_cluster() -> Option<String> {
+    std::env::var("SOLANA_CLUSTER")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+}
+
 /// Batch commit size (required).
 pub fn batch_commit_size() -> u64 {
     let v = std::env::var("BATCH_COMMIT_SIZE").expect("BATCH_COMMIT_SIZE must be set");
@@ -25,3 +115,171 @@ pub fn database_url() -> String {
     std::env::var("DATABASE_URL").expect("DATABASE_URL must be set")
 }
 
+/// Rows fetched per cursor round-trip (and hashed in parallel) during SMT rebuilds.
+///
+/// Optional, defaults to 10_000. Bounds rebuild memory regardless of table size.
+pub fn rebuild_batch_size() -> usize {
+    std::env::var("REBUILD_BATCH_SIZE")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(10_000)
+        .max(1)
+}
+
+/// Path of the authenticated SMT checkpoint file (optional, default `smt_checkpoint.bin`).
+pub fn smt_checkpoint_path() -> String {
+    std::env::var("SMT_CHECKPOINT_PATH").unwrap_or_else(|_| "smt_checkpoint.bin".to_string())
+}
+
+/// Seconds between SMT checkpoints (optional, default 300; 0 disables checkpointing).
+pub fn smt_checkpoint_interval_secs() -> u64 {
+    std::env::var("SMT_CHECKPOINT_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(300)
+}
+
+/// Root anchoring backend: `solana` (default), `file` or `memory` (see `infra::anchor`).
+pub fn anchor_backend() -> String {
+    std::env::var("ANCHOR_BACKEND")
+        .map(|v| v.trim().to_lowercase())
+        .unwrap_or_else(|_| "solana".to_string())
+}
+
+/// Path of the root file used by `ANCHOR_BACKEND=file` (optional, default `anchor_root.json`).
+pub fn anchor_file_path() -> String {
+    std::env::var("ANCHOR_FILE_PATH").unwrap_or_else(|_| "anchor_root.json".to_string())
+}
+
+/// Path of the transparency log used by the `log` anchor (optional, default `anchor_log.jsonl`).
+pub fn anchor_log_path() -> String {
+    std::env::var("ANCHOR_LOG_PATH").unwrap_or_else(|_| "anchor_log.jsonl".to_string())
+}
+
+/// Comma-separated anchor targets (optional). When set, every root is anchored to all of them
+/// and `ANCHOR_BACKEND` is ignored (see `infra::anchor`).
+pub fn anchor_targets() -> Option<String> {
+    std::env::var("ANCHOR_TARGETS")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+}
+
+/// Which of the `ANCHOR_TARGETS` must succeed: `all` (default), `majority`, `quorum=<n>` or
+/// `primary` (the first target; the others are best-effort).
+pub fn anchor_quorum() -> String {
+    std::env::var("ANCHOR_QUORUM").unwrap_or_else(|_| "all".to_string())
+}
+
+/// Seconds to wait for in-flight writes on SIGTERM/SIGINT before the final anchor
+/// (optional, default 30).
+pub fn shutdown_drain_secs() -> u64 {
+    std::env::var("SHUTDOWN_DRAIN_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(30)
+}
+
+/// Sealing key provider for local trusted state: `file` (default, see `infra::sealing`).
+pub fn sealing_key_provider() -> String {
+    std::env::var("SEALING_KEY_PROVIDER")
+        .map(|v| v.trim().to_lowercase())
+        .unwrap_or_else(|_| "file".to_string())
+}
+
+/// Path of the master key used by `SEALING_KEY_PROVIDER=file` (optional, default `sealing.key`).
+///
+/// Generated on first use if missing.
+pub fn sealing_key_path() -> String {
+    std::env::var("SEALING_KEY_PATH").unwrap_or_else(|_| "sealing.key".to_string())
+}
+
+/// Maximum age in seconds of an un-anchored write before it is committed regardless of
+/// `BATCH_COMMIT_SIZE` (optional, default 0: time-based commits are disabled).
+pub fn commit_max_age_secs() -> u64 {
+    std::env::var("COMMIT_MAX_AGE_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0)
+}
+
+/// Delay in milliseconds before the first retry of a failed anchor commit (optional, default
+/// 1000). Doubles with every further failure, up to `ANCHOR_RETRY_MAX_MS`.
+pub fn anchor_retry_base_ms() -> u64 {
+    std::env::var("ANCHOR_RETRY_BASE_MS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(1_000)
+}
+
+/// Maximum delay in milliseconds between anchor retries (optional, default 60000).
+pub fn anchor_retry_max_ms() -> u64 {
+    std::env::var("ANCHOR_RETRY_MAX_MS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(60_000)
+}
+
+/// Consecutive anchor failures after which the service reports itself degraded (optional,
+/// default 5).
+pub fn anchor_breaker_threshold() -> u32 {
+    std::env::var("ANCHOR_BREAKER_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(5)
+        .max(1)
+}
+
+/// Seconds between checks of the anchored root for writes by someone else (optional, default
+/// 30; 0 disables the watch, see `app::chain_watch`).
+pub fn chain_watch_interval_secs() -> u64 {
+    std::env::var("CHAIN_WATCH_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(30)
+}
+
+/// Startup reconciliation action override for one situation (e.g. `RECONCILE_DB_TAMPERED`).
+///
+/// Optional; see `app::reconcile` for the accepted values and defaults.
+pub fn reconcile_action(var: &str) -> Option<String> {
+    std::env::var(var).ok().map(|v| v.trim().to_lowercase())
+}
+
+/// Lamports that anchoring may spend per `ANCHOR_BUDGET_PERIOD` (optional; unset means no
+/// budget, see `domain::commitment::budget`). A value that is not a plain number of lamports is
+/// an error rather than no budget.
+pub fn anchor_budget_lamports() -> anyhow::Result<Option<u64>> {
+    match std::env::var("ANCHOR_BUDGET_LAMPORTS") {
+        Ok(v) if !v.trim().is_empty() => v.trim().parse::<u64>().map(Some).map_err(|_| {
+            anyhow::anyhow!(
+                "ANCHOR_BUDGET_LAMPORTS must be a whole number of lamports (got {:?})",
+                v
+            )
+        }),
+        _ => Ok(None),
+    }
+}
+
+/// Calendar period of the anchoring budget: hour, day, week or month (optional, default day).
+pub fn anchor_budget_period() -> String {
+    std::env::var("ANCHOR_BUDGET_PERIOD")
+        .map(|v| v.trim().to_lowercase())
+        .unwrap_or_else(|_| "day".to_string())
+}
+
+/// What to do once the anchoring budget is spent: widen or refuse (optional, default widen).
+pub fn anchor_budget_action() -> String {
+    std::env::var("ANCHOR_BUDGET_ACTION")
+        .map(|v| v.trim().to_lowercase())
+        .unwrap_or_else(|_| "widen".to_string())
+}
+
+/// Factor applied to `BATCH_COMMIT_SIZE` and `COMMIT_MAX_AGE_SECS` while the budget is spent
+/// and the action is `widen` (optional, default 4).
+pub fn anchor_budget_widen_factor() -> u64 {
+    std::env::var("ANCHOR_BUDGET_WIDEN_FACTOR")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(4)
+        .max(1)
+}
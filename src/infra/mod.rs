@@ -1,3 +1,4 @@
+pub mod anchor;
+pub mod sealing;
 pub mod solana;
 pub mod config;
-
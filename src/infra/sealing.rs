@@ -0,0 +1,109 @@
+//! Sealing keys for local state the host must not be able to forge or roll back.
+//!
+//! Files such as `trusted_state.json` live on host-controlled disk. They are authenticated with
+//! a keyed BLAKE2b MAC whose key comes from a [`KeyProvider`]. Inside a TEE the provider should
+//! derive keys from the enclave sealing key; `SEALING_KEY_PROVIDER` picks the backend:
+//!
+//! - `file` (default): a random master key in a local 0600 file (`SEALING_KEY_PATH`). This is a
+//!   development stand-in: anyone who can read the file can forge sealed state.
+
+use blake2::digest::{KeyInit, Mac};
+use blake2::Blake2bMac;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::infra::config;
+
+type SealMac = Blake2bMac<sha2::digest::consts::U32>;
+
+/// Source of 32-byte sealing keys, one per purpose.
+pub trait KeyProvider: Send + Sync {
+    /// Short backend name used in logs.
+    fn name(&self) -> &str;
+
+    /// Returns the key for `purpose` (e.g. `"trusted_state"`). Stable across restarts.
+    fn key(&self, purpose: &str) -> anyhow::Result<[u8; 32]>;
+}
+
+/// Master key stored in a local file, generated on first use.
+pub struct FileKeyProvider {
+    path: PathBuf,
+}
+
+impl FileKeyProvider {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl KeyProvider for FileKeyProvider {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn key(&self, purpose: &str) -> anyhow::Result<[u8; 32]> {
+        let master = load_or_create_key_file(&self.path)?;
+        Ok(mac(&master, purpose.as_bytes()))
+    }
+}
+
+/// Builds the key provider selected by `SEALING_KEY_PROVIDER`.
+pub fn from_config() -> anyhow::Result<Arc<dyn KeyProvider>> {
+    let provider = config::sealing_key_provider();
+    match provider.as_str() {
+        "file" => Ok(Arc::new(FileKeyProvider::new(config::sealing_key_path()))),
+        other => Err(anyhow::anyhow!(
+            "Unknown SEALING_KEY_PROVIDER '{}' (expected file)",
+            other
+        )),
+    }
+}
+
+/// Keyed BLAKE2b-256 MAC of `data`.
+pub fn mac(key: &[u8; 32], data: &[u8]) -> [u8; 32] {
+    let mut mac = <SealMac as KeyInit>::new_from_slice(key).expect("32-byte key is valid");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+/// Checks `tag` against the MAC of `data` in constant time.
+pub fn verify_mac(key: &[u8; 32], data: &[u8], tag: &[u8]) -> anyhow::Result<()> {
+    let mut mac = <SealMac as KeyInit>::new_from_slice(key)?;
+    mac.update(data);
+    mac.verify_slice(tag)
+        .map_err(|_| anyhow::anyhow!("MAC verification failed"))
+}
+
+/// Loads a hex-encoded 32-byte key from `path`, generating a random one (mode 0600) on first use.
+///
+/// An empty file (e.g. a placeholder created for a bind mount) counts as first use.
+pub fn load_or_create_key_file(path: &Path) -> anyhow::Result<[u8; 32]> {
+    let existing = path.exists();
+    if existing {
+        let content = fs::read_to_string(path)?;
+        if !content.trim().is_empty() {
+            let bytes = hex::decode(content.trim())?;
+            return bytes
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Key in {:?} must be 32 bytes (hex)", path));
+        }
+    }
+
+    let key: [u8; 32] = rand::random();
+    let mut opts = fs::OpenOptions::new();
+    if existing {
+        opts.write(true).truncate(true);
+    } else {
+        opts.write(true).create_new(true);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    let mut file = opts.open(path)?;
+    file.write_all(hex::encode(key).as_bytes())?;
+    Ok(key)
+}
@@ -1,145 +1,654 @@
 // Responsible for all communication with the Solana blockchain.
 
 use primitive_types::H256;
-use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::{client_error::ClientError, nonblocking::rpc_client::RpcClient};
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
 };
-use solana_sdk::{
-    commitment_config::CommitmentConfig,
-    signer::{keypair::read_keypair_file, Signer},
-    transaction::Transaction,
-};
+use solana_sdk::{instruction::InstructionError, signature::Signature, transaction::TransactionError};
 use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
 
+use crate::infra::anchor::{AnchorReceipt, ChargedFee, StaleAnchor};
 use crate::infra::config;
+use crate::infra::solana::signer::{self, TransactionSigner};
+use crate::infra::solana::transaction::{self, TxOptions};
+
+// Anchor discriminators: the first 8 bytes of sha256("global:<instruction>") and
+// sha256("account:<Account>").
+const INITIALIZE_DISCRIMINATOR: [u8; 8] = [175, 175, 109, 31, 13, 152, 155, 237];
+const UPDATE_ROOT_DISCRIMINATOR: [u8; 8] = [58, 195, 57, 246, 116, 198, 170, 138];
+const SET_AUTHORITY_DISCRIMINATOR: [u8; 8] = [133, 250, 37, 21, 110, 163, 26, 121];
+const PROPOSE_AUTHORITY_DISCRIMINATOR: [u8; 8] = [20, 148, 236, 198, 76, 119, 99, 142];
+const ACCEPT_AUTHORITY_DISCRIMINATOR: [u8; 8] = [107, 86, 198, 91, 33, 12, 107, 160];
+const ACCOUNT_DISCRIMINATOR: [u8; 8] = [248, 12, 63, 117, 38, 72, 89, 66];
+const ROOT_HISTORY_DISCRIMINATOR: [u8; 8] = [46, 188, 113, 21, 220, 164, 176, 214];
+
+/// Entries kept by the on-chain `root_history` ring buffer (`ROOT_HISTORY_LEN` in the program).
+pub const ROOT_HISTORY_LEN: usize = 64;
+const ROOT_HISTORY_ENTRY_SIZE: usize = 8 + 32 + 8;
+
+// Custom error codes of `VerifiableDbError` (Anchor numbers them from 6000).
+const ERR_UNAUTHORIZED: u32 = 6000;
+const ERR_NO_PENDING_AUTHORITY: u32 = 6001;
+const ERR_NOT_PENDING_AUTHORITY: u32 = 6002;
+const ERR_STALE_ROOT: u32 = 6003;
+const ERR_STALE_SEQUENCE: u32 = 6004;
+const ERR_INVALID_NAMESPACE: u32 = 6005;
 
 // Define the structure of the on-chain account that stores the Merkle root.
 // This must match the struct in the smart contract.
-#[allow(dead_code)] // Reserved for future use (e.g., reading account data)
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MerkleRootAccount {
     pub root: [u8; 32],
     pub timestamp: i64,
+    /// Number of root updates applied; each `update_root` must carry the next one.
+    pub sequence: u64,
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
 }
 
-// Helper function to get the RPC client and payer keypair.
-async fn get_client_and_payer() -> anyhow::Result<(RpcClient, solana_sdk::signer::keypair::Keypair)>
-{
-    let rpc_url = config::solana_rpc_url();
-    let payer = read_keypair_file(&*shellexpand::tilde("~/.config/solana/id.json"))
-        .map_err(|e| anyhow::anyhow!("Failed to read keypair file: {}", e))?;
+impl MerkleRootAccount {
+    /// Discriminator + root + timestamp + sequence + authority + pending authority
+    /// (`Option<Pubkey>`).
+    pub const SPACE: usize = 8 + 32 + 8 + 8 + 32 + (1 + 32);
+
+    /// Decodes an account fetched from the chain, refusing it unless `owner` is `program_id`
+    /// (anyone can create an account with matching bytes under another program).
+    pub fn decode_owned(data: &[u8], owner: &Pubkey, program_id: &Pubkey) -> anyhow::Result<Self> {
+        check_owner("Merkle root account", owner, program_id)?;
+        Self::decode(data)
+    }
+
+    /// Decodes the raw account data written by the program.
+    pub fn decode(data: &[u8]) -> anyhow::Result<Self> {
+        if data.len() < 8 || data[..8] != ACCOUNT_DISCRIMINATOR {
+            return Err(anyhow::anyhow!("Not a MerkleRootAccount (discriminator mismatch)"));
+        }
+        if data.len() < 8 + 32 + 8 + 8 + 32 + 1 {
+            return Err(anyhow::anyhow!(
+                "Merkle root account is {} bytes, expected {}; it was written by an older program \
+                 version. Deploy the program under a new program id and initialize a fresh account.",
+                data.len(),
+                Self::SPACE
+            ));
+        }
+        let mut root = [0u8; 32];
+        root.copy_from_slice(&data[8..40]);
+        let timestamp = i64::from_le_bytes(data[40..48].try_into()?);
+        let sequence = u64::from_le_bytes(data[48..56].try_into()?);
+        let authority = Pubkey::try_from(&data[56..88])?;
+        let pending_authority = match data[88] {
+            0 => None,
+            1 if data.len() >= Self::SPACE => Some(Pubkey::try_from(&data[89..121])?),
+            tag => return Err(anyhow::anyhow!("Invalid pending_authority tag {}", tag)),
+        };
+        Ok(Self {
+            root,
+            timestamp,
+            sequence,
+            authority,
+            pending_authority,
+        })
+    }
 
-    let client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
-    Ok((client, payer))
+    /// Fails unless `signer` is the account's authority, i.e. unless its root updates would be
+    /// accepted.
+    pub fn ensure_authority(&self, signer: &Pubkey) -> anyhow::Result<()> {
+        if self.authority == *signer {
+            return Ok(());
+        }
+        let hint = if self.pending_authority == Some(*signer) {
+            " The signer is the pending authority; run preflight --accept-authority."
+        } else {
+            ""
+        };
+        Err(anyhow::anyhow!(
+            "Signer {} is not the root authority {}; root updates would be rejected.{}",
+            signer,
+            self.authority,
+            hint
+        ))
+    }
 }
 
-// We need a predictable address for our Merkle root account using a PDA.
-fn get_merkle_root_account_pubkey() -> anyhow::Result<(Pubkey, u8)> {
-    let program_id = Pubkey::from_str(&config::solana_program_id())?;
-    let seeds = b"merkle_root_account";
-    let (pda, bump) = Pubkey::find_program_address(&[seeds], &program_id);
-    Ok((pda, bump))
+fn check_owner(what: &str, owner: &Pubkey, program_id: &Pubkey) -> anyhow::Result<()> {
+    if owner != program_id {
+        return Err(anyhow::anyhow!(
+            "{} is owned by {}, not by SOLANA_PROGRAM_ID {}",
+            what,
+            owner,
+            program_id
+        ));
+    }
+    Ok(())
 }
 
-/// Initializes the on-chain Merkle root account.
-/// This only needs to be called once.
-pub async fn initialize() -> anyhow::Result<()> {
-    let (client, payer) = get_client_and_payer().await?;
-    let (merkle_root_account_pubkey, _bump) = get_merkle_root_account_pubkey()?;
-    let program_id = Pubkey::from_str(&config::solana_program_id())?;
+/// A root account as read from the chain: where it lives and the slot it was read at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootAccountSnapshot {
+    pub address: Pubkey,
+    /// Slot of the RPC node's view when the account was read.
+    pub slot: u64,
+    pub account: MerkleRootAccount,
+}
 
-    // Check if the account already exists.
-    if client.get_account(&merkle_root_account_pubkey).await.is_ok() {
-        println!("Merkle root account already initialized.");
-        return Ok(());
-    }
+/// One root recorded in the on-chain history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootHistoryEntry {
+    /// The `update_root` sequence that anchored `root` (0 for the initial root).
+    pub sequence: u64,
+    pub root: [u8; 32],
+    pub timestamp: i64,
+}
 
-    println!("Initializing Merkle root account...");
-    let initial_root = H256::zero();
+/// The on-chain `root_history` ring buffer: the last [`ROOT_HISTORY_LEN`] anchored roots.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RootHistory {
+    /// Oldest first.
+    pub entries: Vec<RootHistoryEntry>,
+}
 
-    // Build the instruction manually
-    // Discriminator for initialize: [175, 175, 109, 31, 13, 152, 155, 237]
-    let accounts = vec![
-        AccountMeta::new(merkle_root_account_pubkey, false),
-        AccountMeta::new(payer.pubkey(), true),
-        AccountMeta::new_readonly(solana_program::system_program::ID, false),
-    ];
+impl RootHistory {
+    /// Discriminator + head + len + entries (zero-copy, little-endian `repr(C)`).
+    pub const SPACE: usize = 8 + 8 + 8 + ROOT_HISTORY_LEN * ROOT_HISTORY_ENTRY_SIZE;
 
-    let mut instruction_data = vec![175, 175, 109, 31, 13, 152, 155, 237]; // initialize discriminator
-    instruction_data.extend_from_slice(&initial_root.to_fixed_bytes());
+    /// Decodes an account fetched from the chain, refusing it unless `owner` is `program_id`.
+    pub fn decode_owned(data: &[u8], owner: &Pubkey, program_id: &Pubkey) -> anyhow::Result<Self> {
+        check_owner("Root history account", owner, program_id)?;
+        Self::decode(data)
+    }
 
-    let instruction = Instruction {
-        program_id,
-        accounts,
-        data: instruction_data,
-    };
+    /// Decodes the raw account data written by the program.
+    pub fn decode(data: &[u8]) -> anyhow::Result<Self> {
+        if data.len() < 8 || data[..8] != ROOT_HISTORY_DISCRIMINATOR {
+            return Err(anyhow::anyhow!("Not a RootHistory account (discriminator mismatch)"));
+        }
+        if data.len() < Self::SPACE {
+            return Err(anyhow::anyhow!(
+                "Root history account is {} bytes, expected {}",
+                data.len(),
+                Self::SPACE
+            ));
+        }
+        let head = u64::from_le_bytes(data[8..16].try_into()?) as usize;
+        let len = u64::from_le_bytes(data[16..24].try_into()?) as usize;
+        if head >= ROOT_HISTORY_LEN || len > ROOT_HISTORY_LEN {
+            return Err(anyhow::anyhow!("Corrupt root history (head {}, len {})", head, len));
+        }
+        let entry = |slot: usize| -> anyhow::Result<RootHistoryEntry> {
+            let at = 24 + slot * ROOT_HISTORY_ENTRY_SIZE;
+            let mut root = [0u8; 32];
+            root.copy_from_slice(&data[at + 8..at + 40]);
+            Ok(RootHistoryEntry {
+                sequence: u64::from_le_bytes(data[at..at + 8].try_into()?),
+                root,
+                timestamp: i64::from_le_bytes(data[at + 40..at + 48].try_into()?),
+            })
+        };
+        // Once full, the oldest entry is the one `head` overwrites next.
+        let first = if len < ROOT_HISTORY_LEN { 0 } else { head };
+        let entries = (0..len)
+            .map(|i| entry((first + i) % ROOT_HISTORY_LEN))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self { entries })
+    }
 
-    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    /// The most recent time `root` was anchored, if it is still in the buffer.
+    pub fn find(&self, root: H256) -> Option<&RootHistoryEntry> {
+        self.entries.iter().rev().find(|e| e.root == root.to_fixed_bytes())
+    }
 
-    let recent_blockhash = client.get_latest_blockhash().await?;
-    transaction.sign(&[&payer], recent_blockhash);
-    client.send_and_confirm_transaction(&transaction).await?;
+    /// Sequence of the oldest root still held. Roots anchored before it have rotated out, so
+    /// not finding one says nothing about whether it was ever anchored.
+    pub fn oldest_sequence(&self) -> Option<u64> {
+        self.entries.first().map(|e| e.sequence)
+    }
+}
 
-    println!("Successfully initialized Merkle root account on-chain.");
-    Ok(())
+/// A long-lived connection to one cluster: the RPC client (which keeps its HTTP connections
+/// open), the transaction settings and the payer's signer (`SOLANA_SIGNER`), loaded on the first
+/// write and kept from then on. Clones share all three.
+///
+/// The service holds one per anchor (see `infra::anchor::SolanaAnchor`); the free functions
+/// below build a one-off client for each call, which is fine for CLI tools.
+#[derive(Clone)]
+pub struct SolanaClient {
+    rpc: Arc<RpcClient>,
+    options: TxOptions,
+    payer: Arc<OnceCell<Arc<dyn TransactionSigner>>>,
 }
 
-/// Reads the trusted Merkle root from the Solana blockchain.
-pub async fn read_root() -> anyhow::Result<H256> {
-    let (client, _payer) = get_client_and_payer().await?;
-    let (merkle_root_account_pubkey, _bump) = get_merkle_root_account_pubkey()?;
+impl SolanaClient {
+    /// Client for `rpc_url`, reading and confirming at `SOLANA_COMMITMENT`.
+    pub fn new(rpc_url: &str) -> anyhow::Result<Self> {
+        let options = TxOptions::from_config(rpc_url)?;
+        Ok(Self {
+            rpc: Arc::new(RpcClient::new_with_commitment(rpc_url.to_string(), options.commitment)),
+            options,
+            payer: Arc::new(OnceCell::new()),
+        })
+    }
+
+    /// Client for `SOLANA_RPC_URL`.
+    pub fn from_config() -> anyhow::Result<Self> {
+        Self::new(&config::solana_rpc_url())
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    pub fn url(&self) -> String {
+        self.rpc.url()
+    }
+
+    pub fn options(&self) -> &TxOptions {
+        &self.options
+    }
+
+    /// The payer's signer, loaded once. A failed load is not cached, so a signer that comes up
+    /// later (e.g. a remote signer socket) is picked up by the next write.
+    pub async fn payer(&self) -> anyhow::Result<Arc<dyn TransactionSigner>> {
+        self.payer
+            .get_or_try_init(signer::from_config)
+            .await
+            .cloned()
+    }
+
+    /// Initializes the on-chain Merkle root account for `namespace`. This only needs to be
+    /// called once per cluster and namespace.
+    pub async fn initialize(&self, namespace: &str) -> anyhow::Result<()> {
+        let payer = self.payer().await?;
+        let (merkle_root_account_pubkey, _bump) = get_merkle_root_account_pubkey(namespace)?;
+        let (root_history_pubkey, _bump) = get_root_history_pubkey(namespace)?;
+        let program_id = Pubkey::from_str(&config::solana_program_id())?;
+
+        // An existing account is only usable if this signer may update it.
+        let existing = self
+            .rpc
+            .get_account_with_commitment(&merkle_root_account_pubkey, self.rpc.commitment())
+            .await?
+            .value;
+        if let Some(existing) = existing {
+            MerkleRootAccount::decode_owned(&existing.data, &existing.owner, &program_id)?
+                .ensure_authority(&payer.pubkey())
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Merkle root account for namespace '{}' is already initialized: {}",
+                        namespace,
+                        e
+                    )
+                })?;
+            println!("Merkle root account for namespace '{}' already initialized.", namespace);
+            return Ok(());
+        }
+
+        println!("Initializing Merkle root account for namespace '{}'...", namespace);
+        let initial_root = H256::zero();
+
+        // Build the instruction manually. The payer becomes the account's authority.
+        let accounts = vec![
+            AccountMeta::new(merkle_root_account_pubkey, false),
+            AccountMeta::new(root_history_pubkey, false),
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(solana_program::system_program::ID, false),
+        ];
+
+        let mut instruction_data = encode_instruction(INITIALIZE_DISCRIMINATOR, namespace);
+        instruction_data.extend_from_slice(&initial_root.to_fixed_bytes());
+
+        let instruction = Instruction {
+            program_id,
+            accounts,
+            data: instruction_data,
+        };
+        self.send(payer.as_ref(), instruction).await?;
+
+        println!(
+            "Successfully initialized Merkle root account on-chain (authority {}).",
+            payer.pubkey()
+        );
+        Ok(())
+    }
+
+    /// Reads and decodes the on-chain Merkle root account of `namespace`.
+    pub async fn read_account(&self, namespace: &str) -> anyhow::Result<MerkleRootAccount> {
+        Ok(self.read_snapshot(namespace).await?.account)
+    }
+
+    /// Reads the on-chain Merkle root account of `namespace` with its address and the slot it
+    /// was read at. The account must be owned by `SOLANA_PROGRAM_ID` and carry the account
+    /// discriminator.
+    pub async fn read_snapshot(&self, namespace: &str) -> anyhow::Result<RootAccountSnapshot> {
+        let (merkle_root_account_pubkey, _bump) = get_merkle_root_account_pubkey(namespace)?;
+        let program_id = Pubkey::from_str(&config::solana_program_id())?;
+        let response = self
+            .rpc
+            .get_account_with_commitment(&merkle_root_account_pubkey, self.rpc.commitment())
+            .await?;
+        let account_info = response.value.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Merkle root account {} (namespace '{}') does not exist; run initialize first",
+                merkle_root_account_pubkey,
+                namespace
+            )
+        })?;
+        Ok(RootAccountSnapshot {
+            address: merkle_root_account_pubkey,
+            slot: response.context.slot,
+            account: MerkleRootAccount::decode_owned(&account_info.data, &account_info.owner, &program_id)?,
+        })
+    }
+
+    /// Reads the on-chain root history of `namespace` (the last [`ROOT_HISTORY_LEN`] anchored
+    /// roots).
+    pub async fn read_history(&self, namespace: &str) -> anyhow::Result<RootHistory> {
+        let (root_history_pubkey, _bump) = get_root_history_pubkey(namespace)?;
+        let program_id = Pubkey::from_str(&config::solana_program_id())?;
+        let account_info = self.rpc.get_account(&root_history_pubkey).await?;
+        RootHistory::decode_owned(&account_info.data, &account_info.owner, &program_id)
+    }
+
+    /// Reads the trusted Merkle root of `namespace`.
+    pub async fn read_root(&self, namespace: &str) -> anyhow::Result<H256> {
+        let account = self.read_account(namespace).await?;
+        Ok(H256::from(account.root))
+    }
 
-    let account_info = client.get_account(&merkle_root_account_pubkey).await?;
-    let account_data = account_info.data;
+    /// Writes a new Merkle root for `namespace`, on top of whatever root it holds now.
+    ///
+    /// Returns the confirmed transaction signature and the slot it landed in.
+    pub async fn write_root(&self, namespace: &str, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        self.update_root(namespace, None, new_root).await
+    }
+
+    /// Writes `new_root` only if the account still holds `expected_prev`. Fails with
+    /// [`StaleAnchor`] if it does not, whether that is seen before sending or the program
+    /// rejects the transaction because another update landed first.
+    pub async fn write_root_after(
+        &self,
+        namespace: &str,
+        expected_prev: H256,
+        new_root: H256,
+    ) -> anyhow::Result<AnchorReceipt> {
+        self.update_root(namespace, Some(expected_prev), new_root).await
+    }
+
+    async fn update_root(
+        &self,
+        namespace: &str,
+        expected_prev: Option<H256>,
+        new_root: H256,
+    ) -> anyhow::Result<AnchorReceipt> {
+        let payer = self.payer().await?;
+        let (merkle_root_account_pubkey, _bump) = get_merkle_root_account_pubkey(namespace)?;
+        let (root_history_pubkey, _bump) = get_root_history_pubkey(namespace)?;
+        let program_id = Pubkey::from_str(&config::solana_program_id())?;
+
+        // The program only applies an update built on the current root and the next sequence,
+        // so a delayed or replayed transaction cannot roll the root back.
+        let current = self.read_account(namespace).await?;
+        let prev_root = H256::from(current.root);
+        let expected = expected_prev.unwrap_or(prev_root);
+        if prev_root != expected {
+            return Err(StaleAnchor {
+                expected,
+                actual: Some(prev_root),
+            }
+            .into());
+        }
+        let sequence = current.sequence + 1;
+
+        // Build the instruction manually. The payer signs as the account's authority; the
+        // program also appends the root to the history.
+        let accounts = vec![
+            AccountMeta::new(merkle_root_account_pubkey, false),
+            AccountMeta::new(root_history_pubkey, false),
+            AccountMeta::new_readonly(payer.pubkey(), true),
+        ];
+
+        let mut instruction_data = encode_instruction(UPDATE_ROOT_DISCRIMINATOR, namespace);
+        instruction_data.extend_from_slice(&new_root.to_fixed_bytes());
+        instruction_data.extend_from_slice(&prev_root.to_fixed_bytes());
+        instruction_data.extend_from_slice(&sequence.to_le_bytes());
 
-    // Account structure: 8-byte discriminator + 32-byte root + 8-byte timestamp
-    if account_data.len() < 48 {
-        return Err(anyhow::anyhow!("Account data too short"));
+        let instruction = Instruction {
+            program_id,
+            accounts,
+            data: instruction_data,
+        };
+        let confirmed = match transaction::submit(&self.rpc, payer.as_ref(), instruction, &self.options).await {
+            Ok(confirmed) => confirmed,
+            Err(e) if matches!(program_error(&e), Some(ERR_STALE_ROOT | ERR_STALE_SEQUENCE)) => {
+                // Another update landed between our read and this transaction.
+                let actual = self.read_root(namespace).await.ok();
+                return Err(keep_fee(&e, StaleAnchor { expected, actual }.into()));
+            }
+            Err(e) => return Err(explain(e, payer.as_ref())),
+        };
+
+        println!(
+            "Successfully wrote new root to the Solana blockchain (sequence {}, fee {} lamports): {}",
+            sequence,
+            confirmed
+                .fee_lamports
+                .map_or_else(|| "unknown".to_string(), |fee| fee.to_string()),
+            hex::encode(new_root.as_bytes())
+        );
+        println!(
+            "Transaction Signature: {}",
+            self.options.cluster.explorer_tx_url(&confirmed.signature)
+        );
+
+        Ok(AnchorReceipt {
+            signature: Some(confirmed.signature.to_string()),
+            slot: Some(confirmed.slot),
+            fee_lamports: confirmed.fee_lamports,
+            ..Default::default()
+        })
+    }
+
+    /// See [`set_authority`].
+    pub async fn set_authority(&self, namespace: &str, new_authority: &Pubkey) -> anyhow::Result<Signature> {
+        self.authority_instruction(namespace, SET_AUTHORITY_DISCRIMINATOR, Some(new_authority))
+            .await
     }
 
-    // Skip the 8-byte discriminator and read the 32-byte root
-    let mut root_bytes = [0u8; 32];
-    root_bytes.copy_from_slice(&account_data[8..40]);
-    Ok(H256::from(root_bytes))
+    /// See [`propose_authority`].
+    pub async fn propose_authority(&self, namespace: &str, new_authority: &Pubkey) -> anyhow::Result<Signature> {
+        self.authority_instruction(namespace, PROPOSE_AUTHORITY_DISCRIMINATOR, Some(new_authority))
+            .await
+    }
+
+    /// See [`accept_authority`].
+    pub async fn accept_authority(&self, namespace: &str) -> anyhow::Result<Signature> {
+        self.authority_instruction(namespace, ACCEPT_AUTHORITY_DISCRIMINATOR, None)
+            .await
+    }
+
+    async fn authority_instruction(
+        &self,
+        namespace: &str,
+        discriminator: [u8; 8],
+        new_authority: Option<&Pubkey>,
+    ) -> anyhow::Result<Signature> {
+        let payer = self.payer().await?;
+        let (merkle_root_account_pubkey, _bump) = get_merkle_root_account_pubkey(namespace)?;
+        let program_id = Pubkey::from_str(&config::solana_program_id())?;
+
+        let mut instruction_data = encode_instruction(discriminator, namespace);
+        if let Some(new_authority) = new_authority {
+            instruction_data.extend_from_slice(new_authority.as_ref());
+        }
+        let instruction = Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(merkle_root_account_pubkey, false),
+                AccountMeta::new_readonly(payer.pubkey(), true),
+            ],
+            data: instruction_data,
+        };
+        self.send(payer.as_ref(), instruction).await
+    }
+
+    // Signs `instruction` with the payer, sends it and waits for confirmation (see
+    // `infra::solana::transaction`). The program's custom errors are turned into readable
+    // messages.
+    async fn send(&self, payer: &dyn TransactionSigner, instruction: Instruction) -> anyhow::Result<Signature> {
+        match transaction::submit(&self.rpc, payer, instruction, &self.options).await {
+            Ok(confirmed) => Ok(confirmed.signature),
+            Err(e) => Err(explain(e, payer)),
+        }
+    }
 }
 
-/// Writes a new Merkle root to the Solana blockchain.
-pub async fn write_root(new_root: H256) -> anyhow::Result<()> {
-    let (client, payer) = get_client_and_payer().await?;
-    let (merkle_root_account_pubkey, _bump) = get_merkle_root_account_pubkey()?;
+/// Longest namespace the program accepts (a PDA seed is at most 32 bytes).
+pub const MAX_NAMESPACE_LEN: usize = 32;
+
+/// The predictable address (PDA) of the Merkle root account for `namespace`. Each database
+/// anchored through the same program uses its own namespace (`SOLANA_NAMESPACE`).
+pub fn get_merkle_root_account_pubkey(namespace: &str) -> anyhow::Result<(Pubkey, u8)> {
+    namespaced_pda(b"merkle_root_account", namespace)
+}
+
+/// The address of the root history ring buffer for `namespace`.
+pub fn get_root_history_pubkey(namespace: &str) -> anyhow::Result<(Pubkey, u8)> {
+    namespaced_pda(b"root_history", namespace)
+}
+
+fn namespaced_pda(prefix: &[u8], namespace: &str) -> anyhow::Result<(Pubkey, u8)> {
+    if namespace.is_empty() || namespace.len() > MAX_NAMESPACE_LEN {
+        return Err(anyhow::anyhow!(
+            "Solana namespace '{}' must be 1 to {} bytes",
+            namespace,
+            MAX_NAMESPACE_LEN
+        ));
+    }
     let program_id = Pubkey::from_str(&config::solana_program_id())?;
+    Ok(Pubkey::find_program_address(&[prefix, namespace.as_bytes()], &program_id))
+}
+
+// Instruction data: the discriminator, then the namespace every instruction takes first
+// (Borsh `String`: u32 length + UTF-8 bytes).
+fn encode_instruction(discriminator: [u8; 8], namespace: &str) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    data.extend_from_slice(&(namespace.len() as u32).to_le_bytes());
+    data.extend_from_slice(namespace.as_bytes());
+    data
+}
 
-    // Build the instruction manually
-    // Discriminator for update_root: [58, 195, 57, 246, 116, 198, 170, 138]
-    let accounts = vec![
-        AccountMeta::new(merkle_root_account_pubkey, false),
-        AccountMeta::new(payer.pubkey(), true),
-    ];
+/// Initializes the on-chain Merkle root account for `namespace` on the cluster behind `rpc_url`.
+/// This only needs to be called once per cluster and namespace.
+pub async fn initialize(rpc_url: &str, namespace: &str) -> anyhow::Result<()> {
+    SolanaClient::new(rpc_url)?.initialize(namespace).await
+}
 
-    let mut instruction_data = vec![58, 195, 57, 246, 116, 198, 170, 138]; // update_root discriminator
-    instruction_data.extend_from_slice(&new_root.to_fixed_bytes());
+/// Reads and decodes the on-chain Merkle root account of `namespace`.
+pub async fn read_account(rpc_url: &str, namespace: &str) -> anyhow::Result<MerkleRootAccount> {
+    SolanaClient::new(rpc_url)?.read_account(namespace).await
+}
 
-    let instruction = Instruction {
-        program_id,
-        accounts,
-        data: instruction_data,
-    };
+/// Reads the on-chain Merkle root account of `namespace` with its address and the slot it was
+/// read at (see [`SolanaClient::read_snapshot`]).
+pub async fn read_snapshot(rpc_url: &str, namespace: &str) -> anyhow::Result<RootAccountSnapshot> {
+    SolanaClient::new(rpc_url)?.read_snapshot(namespace).await
+}
+
+/// Reads the on-chain root history of `namespace` (the last [`ROOT_HISTORY_LEN`] anchored
+/// roots).
+pub async fn read_history(rpc_url: &str, namespace: &str) -> anyhow::Result<RootHistory> {
+    SolanaClient::new(rpc_url)?.read_history(namespace).await
+}
 
-    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
-    let recent_blockhash = client.get_latest_blockhash().await?;
-    transaction.sign(&[&payer], recent_blockhash);
-    let signature = client.send_and_confirm_transaction(&transaction).await?;
+/// Checks with one account read whether `root` was anchored recently: the latest history entry
+/// holding it, or `None` if it is not among the last [`ROOT_HISTORY_LEN`] roots.
+pub async fn find_anchored_root(
+    rpc_url: &str,
+    namespace: &str,
+    root: H256,
+) -> anyhow::Result<Option<RootHistoryEntry>> {
+    Ok(read_history(rpc_url, namespace).await?.find(root).copied())
+}
 
-    println!(
-        "Successfully wrote new root to the Solana blockchain: {}",
-        hex::encode(new_root.as_bytes())
-    );
-    println!(
-        "Transaction Signature: https://explorer.solana.com/tx/{}?cluster=devnet",
-        signature
-    );
+/// Reads the trusted Merkle root from the Solana blockchain.
+pub async fn read_root(rpc_url: &str, namespace: &str) -> anyhow::Result<H256> {
+    SolanaClient::new(rpc_url)?.read_root(namespace).await
+}
 
-    Ok(())
+/// Writes a new Merkle root to the Solana blockchain, on top of whatever root it holds now.
+///
+/// Returns the confirmed transaction signature and the slot it landed in.
+pub async fn write_root(rpc_url: &str, namespace: &str, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+    SolanaClient::new(rpc_url)?.write_root(namespace, new_root).await
 }
 
+/// Writes `new_root` only if the account still holds `expected_prev`. Fails with
+/// [`StaleAnchor`] if it does not, whether that is seen before sending or the program rejects
+/// the transaction because another update landed first.
+pub async fn write_root_after(
+    rpc_url: &str,
+    namespace: &str,
+    expected_prev: H256,
+    new_root: H256,
+) -> anyhow::Result<AnchorReceipt> {
+    SolanaClient::new(rpc_url)?
+        .write_root_after(namespace, expected_prev, new_root)
+        .await
+}
+
+/// Hands the authority straight to `new_authority` (signed by the current authority, the payer).
+/// `new_authority` does not sign, so a wrong key locks the account; prefer
+/// [`propose_authority`] + [`accept_authority`].
+pub async fn set_authority(rpc_url: &str, namespace: &str, new_authority: &Pubkey) -> anyhow::Result<Signature> {
+    SolanaClient::new(rpc_url)?.set_authority(namespace, new_authority).await
+}
+
+/// Proposes `new_authority` as the next authority (signed by the current authority, the payer).
+/// Proposing the current authority cancels a pending transfer.
+pub async fn propose_authority(
+    rpc_url: &str,
+    namespace: &str,
+    new_authority: &Pubkey,
+) -> anyhow::Result<Signature> {
+    SolanaClient::new(rpc_url)?.propose_authority(namespace, new_authority).await
+}
+
+/// Completes a transfer: the payer, which must be the pending authority, takes over.
+pub async fn accept_authority(rpc_url: &str, namespace: &str) -> anyhow::Result<Signature> {
+    SolanaClient::new(rpc_url)?.accept_authority(namespace).await
+}
+
+// The program's custom error code, if the transaction failed with one.
+fn program_error(e: &anyhow::Error) -> Option<u32> {
+    match e.downcast_ref::<ClientError>()?.get_transaction_error() {
+        Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) => Some(code),
+        _ => None,
+    }
+}
+
+fn explain(e: anyhow::Error, payer: &dyn TransactionSigner) -> anyhow::Error {
+    let message = match program_error(&e) {
+        Some(ERR_UNAUTHORIZED) => format!(
+            "payer {} is not the authority of the Merkle root account",
+            payer.pubkey()
+        ),
+        Some(ERR_NO_PENDING_AUTHORITY) => "no authority transfer is pending".to_string(),
+        Some(ERR_NOT_PENDING_AUTHORITY) => format!("payer {} is not the pending authority", payer.pubkey()),
+        Some(ERR_STALE_ROOT) => "the stored root is not the expected previous root".to_string(),
+        Some(ERR_STALE_SEQUENCE) => "the update sequence is not the next one".to_string(),
+        Some(ERR_INVALID_NAMESPACE) => format!("namespace must be 1 to {} bytes", MAX_NAMESPACE_LEN),
+        _ => return e,
+    };
+    keep_fee(&e, anyhow::anyhow!("Solana program rejected the transaction: {}", message))
+}
+
+// Carries the fee a failed transaction was charged (if any) over to the error replacing it.
+fn keep_fee(from: &anyhow::Error, to: anyhow::Error) -> anyhow::Error {
+    match ChargedFee::of(from) {
+        Some(fee) => ChargedFee::attach(to, fee),
+        None => to,
+    }
+}
@@ -0,0 +1,106 @@
+//! Events logged by the Solana program.
+//!
+//! Anchor's `emit!` logs an event as `Program data: <base64>`, where the payload is the event
+//! discriminator followed by the Borsh-encoded fields. The line belongs to whichever program is
+//! executing at that point, so logs are attributed by following the `invoke` / `success` /
+//! `failed` lines rather than trusting any `Program data:` line (another program in the same
+//! transaction could log look-alike bytes).
+
+use base64::Engine;
+use primitive_types::H256;
+use solana_program::pubkey::Pubkey;
+
+/// First 8 bytes of sha256("event:RootUpdated").
+pub const ROOT_UPDATED_DISCRIMINATOR: [u8; 8] = [94, 53, 22, 128, 141, 113, 98, 231];
+
+/// One applied `update_root` (the program's `RootUpdated` event).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootUpdated {
+    pub namespace: String,
+    pub sequence: u64,
+    pub old_root: H256,
+    pub new_root: H256,
+    /// Unix time of the update (the cluster clock).
+    pub timestamp: i64,
+}
+
+impl RootUpdated {
+    /// Decodes the event payload (discriminator included).
+    pub fn decode(data: &[u8]) -> anyhow::Result<Self> {
+        let rest = data
+            .strip_prefix(&ROOT_UPDATED_DISCRIMINATOR)
+            .ok_or_else(|| anyhow::anyhow!("Not a RootUpdated event"))?;
+        let too_short = || anyhow::anyhow!("RootUpdated event is truncated ({} bytes)", data.len());
+        let len = u32::from_le_bytes(rest.get(0..4).ok_or_else(too_short)?.try_into()?) as usize;
+        let namespace = std::str::from_utf8(rest.get(4..4 + len).ok_or_else(too_short)?)?.to_string();
+        let fields = rest.get(4 + len..4 + len + 8 + 32 + 32 + 8).ok_or_else(too_short)?;
+        Ok(Self {
+            namespace,
+            sequence: u64::from_le_bytes(fields[0..8].try_into()?),
+            old_root: H256::from_slice(&fields[8..40]),
+            new_root: H256::from_slice(&fields[40..72]),
+            timestamp: i64::from_le_bytes(fields[72..80].try_into()?),
+        })
+    }
+
+    /// The payload as the program logs it (discriminator + Borsh fields).
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = ROOT_UPDATED_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&(self.namespace.len() as u32).to_le_bytes());
+        data.extend_from_slice(self.namespace.as_bytes());
+        data.extend_from_slice(&self.sequence.to_le_bytes());
+        data.extend_from_slice(self.old_root.as_bytes());
+        data.extend_from_slice(self.new_root.as_bytes());
+        data.extend_from_slice(&self.timestamp.to_le_bytes());
+        data
+    }
+
+    /// The `Program data:` log line carrying this event.
+    pub fn to_log_line(&self) -> String {
+        format!(
+            "Program data: {}",
+            base64::engine::general_purpose::STANDARD.encode(self.encode())
+        )
+    }
+}
+
+/// Payloads of the `Program data:` lines logged by `program_id` itself (not by programs it
+/// calls, nor by other programs in the transaction).
+pub fn program_data(logs: &[String], program_id: &Pubkey) -> Vec<Vec<u8>> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut data = Vec::new();
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(payload) = rest.strip_prefix("data: ") {
+            if stack.last() == Some(&program_id.as_str()) {
+                if let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(payload.trim()) {
+                    data.push(bytes);
+                }
+            }
+            continue;
+        }
+        let mut words = rest.split_whitespace();
+        let (Some(id), Some(status)) = (words.next(), words.next()) else {
+            continue;
+        };
+        match status {
+            "invoke" => stack.push(id),
+            "success" | "failed:" if stack.last() == Some(&id) => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    data
+}
+
+/// The `RootUpdated` events `program_id` logged, in log order.
+pub fn root_updates(logs: &[String], program_id: &Pubkey) -> Vec<RootUpdated> {
+    program_data(logs, program_id)
+        .iter()
+        .filter_map(|data| RootUpdated::decode(data).ok())
+        .collect()
+}
@@ -0,0 +1,211 @@
+//! Rebuilds the anchored-root timeline from the program's transaction logs.
+//!
+//! Every applied `update_root` logs a `RootUpdated` event (see `infra::solana::events`). Reading
+//! them back from the chain, or from a saved set of transactions, gives the full history of a
+//! namespace without trusting this service's database: each update must carry the next
+//! sequence and build on the root before it, so a missing or altered transaction shows up as a
+//! gap or break in the timeline.
+
+use primitive_types::H256;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::str::FromStr;
+
+use crate::infra::solana::client::{MerkleRootAccount, SolanaClient};
+use crate::infra::solana::events;
+
+/// Signatures requested per page (the RPC maximum).
+const SIGNATURE_PAGE: usize = 1000;
+
+/// A transaction as the indexer needs it; also the format of fixture files (a JSON array).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    #[serde(default)]
+    pub block_time: Option<i64>,
+    /// Failed transactions change nothing on chain; their logs are ignored.
+    #[serde(default)]
+    pub failed: bool,
+    pub logs: Vec<String>,
+}
+
+/// One root update.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    pub sequence: u64,
+    pub old_root: String,
+    pub new_root: String,
+    /// Unix time from the program's clock.
+    pub timestamp: i64,
+    pub signature: String,
+    pub slot: u64,
+}
+
+/// The anchored-root timeline of one namespace, oldest update first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnchorTimeline {
+    pub program_id: String,
+    pub namespace: String,
+    pub entries: Vec<TimelineEntry>,
+    /// Gaps, breaks and mismatches found while rebuilding. Empty for a complete timeline.
+    pub issues: Vec<String>,
+}
+
+impl AnchorTimeline {
+    /// Builds the timeline of `namespace` from `transactions` (any order) and checks that it is
+    /// continuous: sequences 1, 2, 3, ... each built on the previous root.
+    pub fn build(program_id: &Pubkey, namespace: &str, transactions: &[IndexedTransaction]) -> Self {
+        let mut entries: Vec<TimelineEntry> = transactions
+            .iter()
+            .filter(|tx| !tx.failed)
+            .flat_map(|tx| {
+                events::root_updates(&tx.logs, program_id)
+                    .into_iter()
+                    .filter(|event| event.namespace == namespace)
+                    .map(|event| TimelineEntry {
+                        sequence: event.sequence,
+                        old_root: hex::encode(event.old_root.as_bytes()),
+                        new_root: hex::encode(event.new_root.as_bytes()),
+                        timestamp: event.timestamp,
+                        signature: tx.signature.clone(),
+                        slot: tx.slot,
+                    })
+            })
+            .collect();
+        entries.sort_by_key(|e| (e.sequence, e.slot));
+        // The same transaction can appear twice (overlapping fixture files).
+        entries.dedup_by(|b, a| a.sequence == b.sequence && a.signature == b.signature);
+
+        let mut issues = Vec::new();
+        if let Some(first) = entries.first() {
+            if first.sequence != 1 {
+                issues.push(format!("Updates 1..={} are missing", first.sequence - 1));
+            }
+        }
+        for pair in entries.windows(2) {
+            let (prev, next) = (&pair[0], &pair[1]);
+            if next.sequence == prev.sequence {
+                issues.push(format!(
+                    "Sequence {} was applied twice ({} and {})",
+                    next.sequence, prev.signature, next.signature
+                ));
+            } else if next.sequence != prev.sequence + 1 {
+                issues.push(format!(
+                    "Updates {}..={} are missing",
+                    prev.sequence + 1,
+                    next.sequence - 1
+                ));
+            } else if next.old_root != prev.new_root {
+                issues.push(format!(
+                    "Update {} replaced {} but update {} had anchored {}",
+                    next.sequence, next.old_root, prev.sequence, prev.new_root
+                ));
+            }
+        }
+
+        Self {
+            program_id: program_id.to_string(),
+            namespace: namespace.to_string(),
+            entries,
+            issues,
+        }
+    }
+
+    /// Checks that the timeline ends at the root account's current state.
+    pub fn check_head(&mut self, account: &MerkleRootAccount) {
+        let root = hex::encode(account.root);
+        match self.entries.last() {
+            None if account.sequence == 0 => {}
+            Some(last) if last.sequence == account.sequence && last.new_root == root => {}
+            last => self.issues.push(format!(
+                "The root account is at update {} ({}) but the timeline ends at {}",
+                account.sequence,
+                root,
+                last.map(|e| format!("update {} ({})", e.sequence, e.new_root))
+                    .unwrap_or_else(|| "no updates".to_string())
+            )),
+        }
+    }
+
+    /// The root anchored at `sequence`, if the timeline has it.
+    pub fn root_at(&self, sequence: u64) -> Option<H256> {
+        let entry = self.entries.iter().find(|e| e.sequence == sequence)?;
+        Some(H256::from_slice(&hex::decode(&entry.new_root).ok()?))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Fetches every successful transaction that touched `address`, oldest first. Walks the
+/// signature history back to the first transaction; `limit` stops after that many signatures
+/// (newest first) for a quick look at a long history.
+pub async fn fetch_transactions(
+    client: &SolanaClient,
+    address: &Pubkey,
+    limit: Option<usize>,
+) -> anyhow::Result<Vec<IndexedTransaction>> {
+    let rpc = client.rpc();
+    let mut signatures = Vec::new();
+    let mut before = None;
+    loop {
+        let page = rpc
+            .get_signatures_for_address_with_config(
+                address,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until: None,
+                    limit: Some(SIGNATURE_PAGE),
+                    commitment: Some(rpc.commitment()),
+                },
+            )
+            .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature)?);
+        let full = page.len() == SIGNATURE_PAGE;
+        signatures.extend(page.into_iter().filter(|s| s.err.is_none()));
+        if !full || limit.is_some_and(|limit| signatures.len() >= limit) {
+            break;
+        }
+    }
+    if let Some(limit) = limit {
+        signatures.truncate(limit);
+    }
+
+    let config = RpcTransactionConfig {
+        commitment: Some(rpc.commitment()),
+        max_supported_transaction_version: Some(0),
+        ..Default::default()
+    };
+    let mut transactions = Vec::with_capacity(signatures.len());
+    for status in signatures.iter().rev() {
+        let signature = Signature::from_str(&status.signature)?;
+        let tx = rpc.get_transaction_with_config(&signature, config).await?;
+        let meta = tx.transaction.meta.ok_or_else(|| {
+            anyhow::anyhow!("Transaction {} has no status meta (RPC history pruned?)", signature)
+        })?;
+        transactions.push(IndexedTransaction {
+            signature: status.signature.clone(),
+            slot: tx.slot,
+            block_time: tx.block_time,
+            failed: meta.err.is_some(),
+            logs: Option::<Vec<String>>::from(meta.log_messages).unwrap_or_default(),
+        });
+    }
+    Ok(transactions)
+}
+
+/// Reads transactions saved as a JSON array of [`IndexedTransaction`].
+pub fn load_transactions(path: &std::path::Path) -> anyhow::Result<Vec<IndexedTransaction>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Could not read transactions from {}: {}", path.display(), e))?;
+    serde_json::from_str(&text)
+        .map_err(|e| anyhow::anyhow!("{} is not a JSON array of transactions: {}", path.display(), e))
+}
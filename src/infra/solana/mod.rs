@@ -1,4 +1,12 @@
 pub mod client;
+pub mod events;
+pub mod indexer;
+pub mod signer;
+pub mod transaction;
 
-pub use client::{initialize, read_root, write_root};
-
+pub use client::{
+    accept_authority, find_anchored_root, get_merkle_root_account_pubkey, get_root_history_pubkey, initialize,
+    propose_authority, read_account, read_history, read_root, read_snapshot, set_authority, write_root,
+    write_root_after, MerkleRootAccount, RootAccountSnapshot, RootHistory, RootHistoryEntry, SolanaClient,
+    MAX_NAMESPACE_LEN, ROOT_HISTORY_LEN,
+};
@@ -0,0 +1,246 @@
+//! Keys that sign Solana transactions (the payer, which is also the root account's authority).
+//!
+//! `SOLANA_SIGNER` picks where the key comes from:
+//!
+//! - `file` (default): a keypair JSON file (`SOLANA_KEYPAIR_PATH`, default
+//!   `~/.config/solana/id.json`). Point it at a sealed-storage mount inside a TEE.
+//! - `env`: a base58 keypair (64 bytes, as exported by wallets) in `SOLANA_SIGNER_SECRET`.
+//! - `remote`: an external signer process listening on a local Unix socket
+//!   (`SOLANA_SIGNER_SOCKET`); the key never enters this process.
+//!
+//! The remote protocol is one JSON object per line and one request per connection:
+//! `{"method":"pubkey"}` answers `{"pubkey":"<base58>"}`, and
+//! `{"method":"sign","message":"<base64>"}` answers `{"signature":"<base58>"}`. Either may
+//! answer `{"error":"<reason>"}` instead. A request that gets no answer within
+//! `SOLANA_SIGNER_TIMEOUT_SECS` fails.
+
+use async_trait::async_trait;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{
+    bs58,
+    hash::Hash,
+    signature::Signature,
+    signer::{
+        keypair::{read_keypair_file, Keypair},
+        Signer,
+    },
+    transaction::Transaction,
+};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+use crate::infra::config;
+
+/// Signs transaction messages for one key.
+#[async_trait]
+pub trait TransactionSigner: Send + Sync {
+    /// Short backend name used in logs.
+    fn name(&self) -> &str;
+
+    fn pubkey(&self) -> Pubkey;
+
+    /// Signs the serialized transaction message.
+    async fn sign_message(&self, message: &[u8]) -> anyhow::Result<Signature>;
+}
+
+/// Signs `transaction` (which must need only this signer) with `recent_blockhash`. The signature
+/// is checked before it is used, so a misbehaving remote signer fails here, not on-chain.
+pub async fn sign_transaction(
+    signer: &dyn TransactionSigner,
+    transaction: &mut Transaction,
+    recent_blockhash: Hash,
+) -> anyhow::Result<()> {
+    let pubkey = signer.pubkey();
+    let header = &transaction.message.header;
+    if header.num_required_signatures != 1 || transaction.message.account_keys.first() != Some(&pubkey) {
+        return Err(anyhow::anyhow!(
+            "Transaction must be signed by {} alone",
+            pubkey
+        ));
+    }
+    transaction.message.recent_blockhash = recent_blockhash;
+    let message = transaction.message_data();
+    let signature = signer.sign_message(&message).await?;
+    if !signature.verify(pubkey.as_ref(), &message) {
+        return Err(anyhow::anyhow!(
+            "Signer '{}' returned a signature that does not verify for {}",
+            signer.name(),
+            pubkey
+        ));
+    }
+    transaction.signatures = vec![signature];
+    Ok(())
+}
+
+/// A keypair held in this process (loaded from a file or the environment).
+pub struct KeypairSigner {
+    name: &'static str,
+    keypair: Keypair,
+}
+
+impl KeypairSigner {
+    pub fn new(keypair: Keypair) -> Self {
+        Self { name: "keypair", keypair }
+    }
+
+    /// Reads a Solana CLI keypair file (a JSON array of 64 bytes).
+    pub fn from_file(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let keypair = read_keypair_file(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read keypair file {:?}: {}", path, e))?;
+        Ok(Self { name: "file", keypair })
+    }
+
+    /// Decodes a base58 keypair (64 bytes: secret then public key).
+    pub fn from_base58(secret: &str) -> anyhow::Result<Self> {
+        let bytes = bs58::decode(secret.trim())
+            .into_vec()
+            .map_err(|e| anyhow::anyhow!("Signer secret is not valid base58: {}", e))?;
+        let keypair = Keypair::try_from(bytes.as_slice())
+            .map_err(|e| anyhow::anyhow!("Signer secret is not a 64-byte keypair: {}", e))?;
+        Ok(Self { name: "env", keypair })
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for KeypairSigner {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> anyhow::Result<Signature> {
+        Ok(self.keypair.sign_message(message))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+enum RemoteRequest<'a> {
+    Pubkey,
+    Sign { message: &'a str },
+}
+
+#[derive(Deserialize)]
+struct RemoteResponse {
+    pubkey: Option<String>,
+    signature: Option<String>,
+    error: Option<String>,
+}
+
+/// An external signer process reached over a local Unix socket (protocol in the module docs).
+pub struct RemoteSigner {
+    socket_path: PathBuf,
+    pubkey: Pubkey,
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    /// Connects to the signer at `socket_path` and asks for its public key. Every request
+    /// times out after `SOLANA_SIGNER_TIMEOUT_SECS`.
+    pub async fn connect(socket_path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let socket_path = socket_path.into();
+        let timeout = Duration::from_secs(config::solana_signer_timeout_secs());
+        let response = request(&socket_path, timeout, &RemoteRequest::Pubkey).await?;
+        let pubkey = response
+            .pubkey
+            .ok_or_else(|| anyhow::anyhow!("Remote signer sent no pubkey"))?;
+        let pubkey = Pubkey::from_str(&pubkey)
+            .map_err(|e| anyhow::anyhow!("Remote signer sent an invalid pubkey: {}", e))?;
+        Ok(Self {
+            socket_path,
+            pubkey,
+            timeout,
+        })
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for RemoteSigner {
+    fn name(&self) -> &str {
+        "remote"
+    }
+
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> anyhow::Result<Signature> {
+        let message = base64::engine::general_purpose::STANDARD.encode(message);
+        let sign = RemoteRequest::Sign { message: &message };
+        let response = request(&self.socket_path, self.timeout, &sign).await?;
+        let signature = response
+            .signature
+            .ok_or_else(|| anyhow::anyhow!("Remote signer sent no signature"))?;
+        Signature::from_str(&signature)
+            .map_err(|e| anyhow::anyhow!("Remote signer sent an invalid signature: {}", e))
+    }
+}
+
+/// Sends one request to the signer at `socket_path`, failing if the connect, write and reply
+/// together take longer than `timeout`.
+async fn request(
+    socket_path: &Path,
+    timeout: Duration,
+    request: &RemoteRequest<'_>,
+) -> anyhow::Result<RemoteResponse> {
+    tokio::time::timeout(timeout, exchange(socket_path, request))
+        .await
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Remote signer at {:?} did not answer within {:?}",
+                socket_path,
+                timeout
+            )
+        })?
+}
+
+async fn exchange(socket_path: &Path, request: &RemoteRequest<'_>) -> anyhow::Result<RemoteResponse> {
+    let mut stream = UnixStream::connect(socket_path)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to connect to remote signer at {:?}: {}", socket_path, e))?;
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes()).await?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).await?;
+    if reply.trim().is_empty() {
+        return Err(anyhow::anyhow!("Remote signer closed the connection without a reply"));
+    }
+    let response: RemoteResponse = serde_json::from_str(reply.trim())?;
+    if let Some(error) = response.error {
+        return Err(anyhow::anyhow!("Remote signer refused: {}", error));
+    }
+    Ok(response)
+}
+
+/// Builds the signer selected by `SOLANA_SIGNER`.
+pub async fn from_config() -> anyhow::Result<Arc<dyn TransactionSigner>> {
+    match config::solana_signer().as_str() {
+        "file" => Ok(Arc::new(KeypairSigner::from_file(config::solana_keypair_path())?)),
+        "env" => {
+            let secret = config::solana_signer_secret()
+                .ok_or_else(|| anyhow::anyhow!("SOLANA_SIGNER=env needs SOLANA_SIGNER_SECRET"))?;
+            Ok(Arc::new(KeypairSigner::from_base58(&secret)?))
+        }
+        "remote" => {
+            let socket = config::solana_signer_socket()
+                .ok_or_else(|| anyhow::anyhow!("SOLANA_SIGNER=remote needs SOLANA_SIGNER_SOCKET"))?;
+            Ok(Arc::new(RemoteSigner::connect(socket).await?))
+        }
+        other => Err(anyhow::anyhow!(
+            "Unknown SOLANA_SIGNER '{}' (expected file, env or remote)",
+            other
+        )),
+    }
+}
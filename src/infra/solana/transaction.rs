@@ -0,0 +1,331 @@
+//! Sending and confirming Solana transactions.
+//!
+//! Every transaction the service sends goes through [`submit`], configured by [`TxOptions`]:
+//!
+//! - `SOLANA_PRIORITY_FEE`: `0` (default, none), a fixed price in micro-lamports per compute
+//!   unit, or `auto` to pay the 75th percentile of recent fees for the accounts written
+//!   (capped by `SOLANA_PRIORITY_FEE_MAX`).
+//! - `SOLANA_COMPUTE_UNIT_LIMIT`: compute units requested (unset: the runtime default). A tight
+//!   limit keeps the priority fee, which is charged per requested unit, low.
+//! - `SOLANA_COMMITMENT`: `processed`, `confirmed` (default) or `finalized`.
+//! - `SOLANA_TX_RESUBMITS`: how often a transaction whose blockhash expired before it landed is
+//!   re-signed with a fresh blockhash and sent again (default 3). Resending is safe: the program
+//!   applies an update only on top of the expected root and sequence.
+//! - `SOLANA_CLUSTER`: cluster for explorer links (`mainnet-beta`, `devnet`, `testnet` or
+//!   `custom`); derived from `SOLANA_RPC_URL` when unset.
+
+use reqwest::Url;
+use solana_client::{client_error::ClientError, nonblocking::rpc_client::RpcClient};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_client::rpc_config::{RpcSendTransactionConfig, RpcTransactionConfig};
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+};
+use std::fmt;
+use std::time::Duration;
+
+use crate::infra::anchor::ChargedFee;
+use crate::infra::config;
+use crate::infra::solana::signer::{self, TransactionSigner};
+
+/// How often the signature status is polled while waiting for confirmation.
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Priority fee attached to each transaction (`SOLANA_PRIORITY_FEE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityFee {
+    None,
+    /// Micro-lamports per compute unit.
+    Fixed(u64),
+    /// Estimated from recent fees, at most this many micro-lamports per compute unit.
+    Auto { max: u64 },
+}
+
+impl PriorityFee {
+    /// Parses `0`, `none`, a number of micro-lamports or `auto` (capped at `max`).
+    pub fn parse(value: &str, max: u64) -> anyhow::Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "none" | "0" => Ok(Self::None),
+            "auto" => Ok(Self::Auto { max }),
+            other => other.parse::<u64>().map(Self::Fixed).map_err(|_| {
+                anyhow::anyhow!(
+                    "Invalid SOLANA_PRIORITY_FEE '{}' (expected 0, auto or micro-lamports per compute unit)",
+                    other
+                )
+            }),
+        }
+    }
+}
+
+/// The cluster behind the RPC endpoint, for explorer links.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cluster {
+    MainnetBeta,
+    Devnet,
+    Testnet,
+    /// A local validator or private cluster, linked through the explorer's custom RPC URL.
+    Custom(String),
+}
+
+impl Cluster {
+    /// `SOLANA_CLUSTER` if given, otherwise guessed from the RPC URL (anything unrecognised is
+    /// treated as a custom cluster).
+    pub fn detect(explicit: Option<&str>, rpc_url: &str) -> Self {
+        let name = explicit.map(|c| c.trim().to_lowercase()).unwrap_or_else(|| {
+            let url = rpc_url.to_lowercase();
+            if url.contains("devnet") {
+                "devnet".to_string()
+            } else if url.contains("testnet") {
+                "testnet".to_string()
+            } else if url.contains("mainnet") {
+                "mainnet-beta".to_string()
+            } else {
+                "custom".to_string()
+            }
+        });
+        match name.as_str() {
+            "mainnet" | "mainnet-beta" => Self::MainnetBeta,
+            "devnet" => Self::Devnet,
+            "testnet" => Self::Testnet,
+            _ => Self::Custom(rpc_url.to_string()),
+        }
+    }
+
+    /// Explorer link for a transaction on this cluster.
+    pub fn explorer_tx_url(&self, signature: &Signature) -> String {
+        let base = format!("https://explorer.solana.com/tx/{}", signature);
+        let params: Vec<(&str, &str)> = match self {
+            Self::MainnetBeta => Vec::new(),
+            Self::Devnet => vec![("cluster", "devnet")],
+            Self::Testnet => vec![("cluster", "testnet")],
+            Self::Custom(rpc_url) => vec![("cluster", "custom"), ("customUrl", rpc_url.as_str())],
+        };
+        match Url::parse_with_params(&base, &params) {
+            Ok(url) if !params.is_empty() => url.to_string(),
+            _ => base,
+        }
+    }
+}
+
+impl fmt::Display for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MainnetBeta => write!(f, "mainnet-beta"),
+            Self::Devnet => write!(f, "devnet"),
+            Self::Testnet => write!(f, "testnet"),
+            Self::Custom(rpc_url) => write!(f, "custom ({})", rpc_url),
+        }
+    }
+}
+
+/// How transactions are priced, confirmed and resubmitted (see the module docs).
+#[derive(Debug, Clone)]
+pub struct TxOptions {
+    pub priority_fee: PriorityFee,
+    pub compute_unit_limit: Option<u32>,
+    pub commitment: CommitmentConfig,
+    pub resubmits: u32,
+    pub cluster: Cluster,
+}
+
+impl TxOptions {
+    /// Options for `rpc_url` from the `SOLANA_*` settings.
+    pub fn from_config(rpc_url: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            priority_fee: PriorityFee::parse(&config::solana_priority_fee(), config::solana_priority_fee_max())?,
+            compute_unit_limit: config::solana_compute_unit_limit(),
+            commitment: parse_commitment(&config::solana_commitment())?,
+            resubmits: config::solana_tx_resubmits(),
+            cluster: Cluster::detect(config::solana_cluster().as_deref(), rpc_url),
+        })
+    }
+}
+
+/// Parses `processed`, `confirmed` or `finalized`.
+pub fn parse_commitment(value: &str) -> anyhow::Result<CommitmentConfig> {
+    match value.trim().to_lowercase().as_str() {
+        "processed" => Ok(CommitmentConfig::processed()),
+        "confirmed" => Ok(CommitmentConfig::confirmed()),
+        "finalized" => Ok(CommitmentConfig::finalized()),
+        other => Err(anyhow::anyhow!(
+            "Invalid SOLANA_COMMITMENT '{}' (expected processed, confirmed or finalized)",
+            other
+        )),
+    }
+}
+
+/// Priority fee to pay given recent fees for the written accounts: the 75th percentile, capped
+/// at `max`. Slots without competition (fee 0) count, so a quiet cluster stays cheap.
+pub fn estimate_priority_fee(recent_fees: &[u64], max: u64) -> u64 {
+    if recent_fees.is_empty() {
+        return 0;
+    }
+    let mut fees = recent_fees.to_vec();
+    fees.sort_unstable();
+    let index = (fees.len() * 3).div_ceil(4).saturating_sub(1);
+    fees[index].min(max)
+}
+
+/// Compute budget instructions to prepend for `limit` and a price of `micro_lamports`.
+pub fn compute_budget_instructions(limit: Option<u32>, micro_lamports: u64) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    if let Some(limit) = limit {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+    }
+    if micro_lamports > 0 {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_price(micro_lamports));
+    }
+    instructions
+}
+
+/// A confirmed transaction.
+#[derive(Debug, Clone, Copy)]
+pub struct Confirmed {
+    pub signature: Signature,
+    pub slot: u64,
+    /// Fee charged for the transaction (base fee plus priority fee), if the RPC reported it.
+    pub fee_lamports: Option<u64>,
+}
+
+/// How many times the fee of a landed transaction is looked up before giving up (the RPC may
+/// not serve `getTransaction` for it right away).
+const FEE_LOOKUP_ATTEMPTS: usize = 5;
+
+/// Signs `instruction` with `payer`, sends it and waits for `options.commitment`. A transaction
+/// whose blockhash expires before it lands is re-signed and sent again, up to
+/// `options.resubmits` times. Failures the chain reports are returned as [`ClientError`] inside
+/// the `anyhow::Error`, so callers can inspect the transaction error. A transaction that landed
+/// but failed was still charged its fee, which is attached as [`ChargedFee`].
+pub async fn submit(
+    client: &RpcClient,
+    payer: &dyn TransactionSigner,
+    instruction: Instruction,
+    options: &TxOptions,
+) -> anyhow::Result<Confirmed> {
+    let micro_lamports = match options.priority_fee {
+        PriorityFee::None => 0,
+        PriorityFee::Fixed(fee) => fee,
+        PriorityFee::Auto { max } => {
+            let writable: Vec<Pubkey> = instruction
+                .accounts
+                .iter()
+                .filter(|a| a.is_writable)
+                .map(|a| a.pubkey)
+                .collect();
+            match client.get_recent_prioritization_fees(&writable).await {
+                Ok(recent) => {
+                    let fees: Vec<u64> = recent.iter().map(|f| f.prioritization_fee).collect();
+                    estimate_priority_fee(&fees, max)
+                }
+                Err(e) => {
+                    eprintln!("Warning: could not estimate the priority fee, paying {}: {}", max, e);
+                    max
+                }
+            }
+        }
+    };
+    let mut instructions = compute_budget_instructions(options.compute_unit_limit, micro_lamports);
+    instructions.push(instruction);
+
+    let send_config = RpcSendTransactionConfig {
+        preflight_commitment: Some(options.commitment.commitment),
+        ..Default::default()
+    };
+    for attempt in 0..=options.resubmits {
+        let (blockhash, last_valid_block_height) = client
+            .get_latest_blockhash_with_commitment(options.commitment)
+            .await?;
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+        signer::sign_transaction(payer, &mut transaction, blockhash).await?;
+        let signature = client
+            .send_transaction_with_config(&transaction, send_config)
+            .await?;
+
+        if let Some((slot, err)) = confirm(client, &signature, last_valid_block_height, options.commitment).await? {
+            let fee_lamports = landed_fee(client, &signature, options.commitment).await;
+            if let Some(err) = err {
+                let e = ClientError::from(err).into();
+                return Err(match fee_lamports {
+                    Some(fee) => ChargedFee::attach(e, fee),
+                    None => e,
+                });
+            }
+            return Ok(Confirmed {
+                signature,
+                slot,
+                fee_lamports,
+            });
+        }
+        eprintln!(
+            "Warning: transaction {} expired before confirmation (attempt {} of {}), resubmitting",
+            signature,
+            attempt + 1,
+            options.resubmits + 1
+        );
+    }
+    Err(anyhow::anyhow!(
+        "Transaction did not land before its blockhash expired ({} attempts)",
+        options.resubmits + 1
+    ))
+}
+
+// Polls until `signature` reaches `commitment` (`Some` with its slot, and the error if it
+// failed on-chain), or its blockhash expires (`None`).
+async fn confirm(
+    client: &RpcClient,
+    signature: &Signature,
+    last_valid_block_height: u64,
+    commitment: CommitmentConfig,
+) -> anyhow::Result<Option<(u64, Option<TransactionError>)>> {
+    loop {
+        tokio::time::sleep(CONFIRM_POLL_INTERVAL).await;
+        // Sampled before the status: a transaction not seen once the chain is past the
+        // blockhash's last valid height never will be.
+        let expired = client.get_block_height_with_commitment(commitment).await? > last_valid_block_height;
+        let status = client
+            .get_signature_statuses(&[*signature])
+            .await?
+            .value
+            .into_iter()
+            .next()
+            .flatten();
+        match status {
+            // A failed transaction landed all the same; it will not get any further.
+            Some(status) if status.err.is_some() => return Ok(Some((status.slot, status.err))),
+            Some(status) if status.satisfies_commitment(commitment) => return Ok(Some((status.slot, None))),
+            Some(_) => {}
+            None if expired => return Ok(None),
+            None => {}
+        }
+    }
+}
+
+/// The fee `signature` was charged, from the `meta.fee` of `getTransaction` (at least
+/// `confirmed`, which that method requires). `None` if the RPC does not report it.
+async fn landed_fee(client: &RpcClient, signature: &Signature, commitment: CommitmentConfig) -> Option<u64> {
+    let commitment = if commitment == CommitmentConfig::processed() {
+        CommitmentConfig::confirmed()
+    } else {
+        commitment
+    };
+    let config = RpcTransactionConfig {
+        commitment: Some(commitment),
+        max_supported_transaction_version: Some(0),
+        ..Default::default()
+    };
+    let mut last_error = None;
+    for _ in 0..FEE_LOOKUP_ATTEMPTS {
+        match client.get_transaction_with_config(signature, config).await {
+            Ok(tx) => return tx.transaction.meta.map(|meta| meta.fee),
+            Err(e) => last_error = Some(e),
+        }
+        tokio::time::sleep(CONFIRM_POLL_INTERVAL).await;
+    }
+    if let Some(e) = last_error {
+        eprintln!("Warning: could not look up the fee of {}: {}", signature, e);
+    }
+    None
+}
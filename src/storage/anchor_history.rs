@@ -0,0 +1,381 @@
+//! Persistent record of every root anchored by the service (`anchor_history`).
+//!
+//! Each row is one successful anchor write: the root, the range of temporary_root update
+//! sequence numbers it covers, and the chain evidence (transaction signature + slot) when the
+//! backend provides it. `anchor_covered_roots` maps every intermediate temporary_root to the
+//! anchor that first covered it, so auditors can go from any root a client saw to the on-chain
+//! transaction that committed it. When roots go to several targets, `anchor_targets` holds the
+//! outcome per target (evidence or error).
+//!
+//! The fee of each anchor transaction is kept with it (`fee_lamports`, when the backend reports
+//! one), which is what `spend_since` and `spend_by_period` add up for cost reporting.
+
+use chrono::{DateTime, Utc};
+use primitive_types::H256;
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+
+use crate::infra::anchor::AnchorTargetStatus;
+
+/// A successful anchor write, as recorded by `RootManager`.
+#[derive(Debug, Clone)]
+pub struct AnchorRecord {
+    pub root: H256,
+    /// First and last temporary_root update sequence numbers covered by this anchor.
+    pub first_seq: u64,
+    pub last_seq: u64,
+    pub update_count: u64,
+    pub reason: String,
+    pub backend: String,
+    pub signature: Option<String>,
+    pub slot: Option<u64>,
+    /// Fee paid for the anchor transaction(s), in lamports.
+    pub fee_lamports: Option<u64>,
+    pub confirmed_at: DateTime<Utc>,
+    /// `(seq, root)` of every temporary_root update covered by this anchor.
+    pub covered_roots: Vec<(u64, H256)>,
+    /// Outcome per target (empty for single-target backends).
+    pub targets: Vec<AnchorTargetStatus>,
+}
+
+/// One row of `anchor_history` (as returned by `GET /api/anchors`).
+#[derive(Debug, Clone, Serialize)]
+pub struct AnchorHistoryEntry {
+    pub id: i64,
+    pub root: String,
+    pub first_seq: i64,
+    pub last_seq: i64,
+    pub update_count: i64,
+    pub reason: String,
+    pub backend: String,
+    pub signature: Option<String>,
+    pub slot: Option<i64>,
+    pub fee_lamports: Option<i64>,
+    pub confirmed_at: DateTime<Utc>,
+    /// Outcome per target (empty for single-target backends).
+    pub targets: Vec<AnchorTargetStatus>,
+}
+
+/// Anchoring spend over a time range (`GET /api/anchors/costs`).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AnchorSpend {
+    /// Start of the period (`spend_by_period`) or of the range (`spend_since`).
+    pub since: Option<DateTime<Utc>>,
+    /// First anchor confirmed in it.
+    pub first_confirmed_at: Option<DateTime<Utc>>,
+    pub anchors: i64,
+    /// temporary_root updates covered by those anchors.
+    pub updates: i64,
+    /// Anchors whose fee is known (older rows and non-Solana backends have none).
+    pub priced_anchors: i64,
+    pub fee_lamports: i64,
+}
+
+/// Read/write access to `anchor_history`.
+#[derive(Clone)]
+pub struct AnchorHistory {
+    pool: PgPool,
+}
+
+impl AnchorHistory {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates the history tables if missing (called by `DatabaseService` on connect).
+    pub async fn ensure_schema(pool: &PgPool) -> anyhow::Result<()> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS anchor_history (
+                id BIGSERIAL PRIMARY KEY,
+                root BYTEA NOT NULL,
+                first_seq BIGINT NOT NULL,
+                last_seq BIGINT NOT NULL,
+                update_count BIGINT NOT NULL,
+                reason TEXT NOT NULL,
+                backend TEXT NOT NULL,
+                signature TEXT,
+                slot BIGINT,
+                confirmed_at TIMESTAMPTZ NOT NULL
+            )",
+        )
+        .execute(pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS anchor_history_root_idx ON anchor_history (root)")
+            .execute(pool)
+            .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS anchor_covered_roots (
+                seq BIGINT NOT NULL,
+                root BYTEA NOT NULL,
+                anchor_id BIGINT NOT NULL REFERENCES anchor_history (id) ON DELETE CASCADE
+            )",
+        )
+        .execute(pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS anchor_covered_roots_root_idx ON anchor_covered_roots (root)",
+        )
+        .execute(pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS anchor_targets (
+                anchor_id BIGINT NOT NULL REFERENCES anchor_history (id) ON DELETE CASCADE,
+                position INT NOT NULL,
+                target TEXT NOT NULL,
+                ok BOOLEAN NOT NULL,
+                signature TEXT,
+                slot BIGINT,
+                error TEXT,
+                PRIMARY KEY (anchor_id, position)
+            )",
+        )
+        .execute(pool)
+        .await?;
+        sqlx::query("ALTER TABLE anchor_history ADD COLUMN IF NOT EXISTS fee_lamports BIGINT")
+            .execute(pool)
+            .await?;
+        sqlx::query("ALTER TABLE anchor_targets ADD COLUMN IF NOT EXISTS fee_lamports BIGINT")
+            .execute(pool)
+            .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS anchor_history_confirmed_at_idx ON anchor_history (confirmed_at)",
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Stores `record` and its covered roots in one transaction. Returns the new row id.
+    pub async fn record(&self, record: &AnchorRecord) -> anyhow::Result<i64> {
+        let mut tx = self.pool.begin().await?;
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO anchor_history
+                (root, first_seq, last_seq, update_count, reason, backend, signature, slot,
+                 fee_lamports, confirmed_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             RETURNING id",
+        )
+        .bind(record.root.as_bytes())
+        .bind(record.first_seq as i64)
+        .bind(record.last_seq as i64)
+        .bind(record.update_count as i64)
+        .bind(&record.reason)
+        .bind(&record.backend)
+        .bind(&record.signature)
+        .bind(record.slot.map(|s| s as i64))
+        .bind(record.fee_lamports.map(|f| f as i64))
+        .bind(record.confirmed_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if !record.covered_roots.is_empty() {
+            let seqs: Vec<i64> = record.covered_roots.iter().map(|(s, _)| *s as i64).collect();
+            let roots: Vec<Vec<u8>> = record
+                .covered_roots
+                .iter()
+                .map(|(_, r)| r.as_bytes().to_vec())
+                .collect();
+            sqlx::query(
+                "INSERT INTO anchor_covered_roots (seq, root, anchor_id)
+                 SELECT s, r, $3 FROM UNNEST($1::bigint[], $2::bytea[]) AS t(s, r)",
+            )
+            .bind(seqs)
+            .bind(roots)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for (position, target) in record.targets.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO anchor_targets
+                    (anchor_id, position, target, ok, signature, slot, fee_lamports, error)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            )
+            .bind(id)
+            .bind(position as i32)
+            .bind(&target.target)
+            .bind(target.ok)
+            .bind(&target.signature)
+            .bind(target.slot.map(|s| s as i64))
+            .bind(target.fee_lamports.map(|f| f as i64))
+            .bind(&target.error)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    /// Newest-first page of anchors, optionally only those with `id < before_id`.
+    pub async fn list(&self, limit: i64, before_id: Option<i64>) -> anyhow::Result<Vec<AnchorHistoryEntry>> {
+        let rows = sqlx::query(
+            "SELECT * FROM anchor_history
+             WHERE ($2::bigint IS NULL OR id < $2)
+             ORDER BY id DESC
+             LIMIT $1",
+        )
+        .bind(limit)
+        .bind(before_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let entries = rows.into_iter().map(Self::decode).collect::<anyhow::Result<_>>()?;
+        self.with_targets(entries).await
+    }
+
+    /// The earliest anchor that covered `root`, either as the anchored root itself or as an
+    /// intermediate temporary_root of its batch.
+    pub async fn first_covering(&self, root: H256) -> anyhow::Result<Option<AnchorHistoryEntry>> {
+        let row = sqlx::query(
+            "SELECT * FROM anchor_history
+             WHERE id = (
+                 SELECT min(id) FROM (
+                     SELECT id FROM anchor_history WHERE root = $1
+                     UNION ALL
+                     SELECT anchor_id FROM anchor_covered_roots WHERE root = $1
+                 ) AS t
+             )",
+        )
+        .bind(root.as_bytes())
+        .fetch_optional(&self.pool)
+        .await?;
+        self.one_with_targets(row).await
+    }
+
+    /// The earliest anchor that covered update `seq`.
+    pub async fn covering_seq(&self, seq: u64) -> anyhow::Result<Option<AnchorHistoryEntry>> {
+        let row = sqlx::query(
+            "SELECT * FROM anchor_history
+             WHERE last_seq >= $1
+             ORDER BY id
+             LIMIT 1",
+        )
+        .bind(seq as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+        self.one_with_targets(row).await
+    }
+
+    /// Highest update sequence number recorded (0 when empty).
+    pub async fn max_seq(&self) -> anyhow::Result<u64> {
+        let seq: i64 = sqlx::query_scalar("SELECT COALESCE(max(last_seq), 0) FROM anchor_history")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(seq.max(0) as u64)
+    }
+
+    /// Total spend of the anchors confirmed at or after `since` (`None`: all of them).
+    pub async fn spend_since(&self, since: Option<DateTime<Utc>>) -> anyhow::Result<AnchorSpend> {
+        let row = sqlx::query(
+            "SELECT count(*) AS anchors,
+                    COALESCE(sum(update_count), 0)::bigint AS updates,
+                    count(fee_lamports) AS priced_anchors,
+                    COALESCE(sum(fee_lamports), 0)::bigint AS fee_lamports,
+                    min(confirmed_at) AS first_confirmed_at
+             FROM anchor_history
+             WHERE ($1::timestamptz IS NULL OR confirmed_at >= $1)",
+        )
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?;
+        let first_confirmed_at = row.try_get("first_confirmed_at")?;
+        Ok(AnchorSpend {
+            since: since.or(first_confirmed_at),
+            first_confirmed_at,
+            anchors: row.try_get("anchors")?,
+            updates: row.try_get("updates")?,
+            priced_anchors: row.try_get("priced_anchors")?,
+            fee_lamports: row.try_get("fee_lamports")?,
+        })
+    }
+
+    /// Spend per `period` (a `date_trunc` unit: hour, day, week or month), newest period first,
+    /// for the last `limit` periods that had anchors.
+    pub async fn spend_by_period(&self, period: &str, limit: i64) -> anyhow::Result<Vec<AnchorSpend>> {
+        let rows = sqlx::query(
+            "SELECT date_trunc($1, confirmed_at, 'UTC') AS period_start,
+                    count(*) AS anchors,
+                    COALESCE(sum(update_count), 0)::bigint AS updates,
+                    count(fee_lamports) AS priced_anchors,
+                    COALESCE(sum(fee_lamports), 0)::bigint AS fee_lamports,
+                    min(confirmed_at) AS first_confirmed_at
+             FROM anchor_history
+             GROUP BY period_start
+             ORDER BY period_start DESC
+             LIMIT $2",
+        )
+        .bind(period)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|row| {
+                Ok(AnchorSpend {
+                    since: Some(row.try_get("period_start")?),
+                    first_confirmed_at: row.try_get("first_confirmed_at")?,
+                    anchors: row.try_get("anchors")?,
+                    updates: row.try_get("updates")?,
+                    priced_anchors: row.try_get("priced_anchors")?,
+                    fee_lamports: row.try_get("fee_lamports")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Fills in `targets` for `entries` (one query).
+    async fn with_targets(&self, mut entries: Vec<AnchorHistoryEntry>) -> anyhow::Result<Vec<AnchorHistoryEntry>> {
+        let ids: Vec<i64> = entries.iter().map(|e| e.id).collect();
+        let rows = sqlx::query(
+            "SELECT * FROM anchor_targets WHERE anchor_id = ANY($1) ORDER BY anchor_id, position",
+        )
+        .bind(&ids)
+        .fetch_all(&self.pool)
+        .await?;
+        for row in rows {
+            let anchor_id: i64 = row.try_get("anchor_id")?;
+            let slot: Option<i64> = row.try_get("slot")?;
+            let fee: Option<i64> = row.try_get("fee_lamports")?;
+            let status = AnchorTargetStatus {
+                target: row.try_get("target")?,
+                ok: row.try_get("ok")?,
+                signature: row.try_get("signature")?,
+                slot: slot.map(|s| s as u64),
+                fee_lamports: fee.map(|f| f as u64),
+                error: row.try_get("error")?,
+            };
+            if let Some(entry) = entries.iter_mut().find(|e| e.id == anchor_id) {
+                entry.targets.push(status);
+            }
+        }
+        Ok(entries)
+    }
+
+    async fn one_with_targets(
+        &self,
+        row: Option<sqlx::postgres::PgRow>,
+    ) -> anyhow::Result<Option<AnchorHistoryEntry>> {
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        Ok(self.with_targets(vec![Self::decode(row)?]).await?.pop())
+    }
+
+    fn decode(row: sqlx::postgres::PgRow) -> anyhow::Result<AnchorHistoryEntry> {
+        let root: Vec<u8> = row.try_get("root")?;
+        Ok(AnchorHistoryEntry {
+            id: row.try_get("id")?,
+            root: hex::encode(root),
+            first_seq: row.try_get("first_seq")?,
+            last_seq: row.try_get("last_seq")?,
+            update_count: row.try_get("update_count")?,
+            reason: row.try_get("reason")?,
+            backend: row.try_get("backend")?,
+            signature: row.try_get("signature")?,
+            slot: row.try_get("slot")?,
+            fee_lamports: row.try_get("fee_lamports")?,
+            confirmed_at: row.try_get("confirmed_at")?,
+            targets: Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,175 @@
+//! Durable queue of anchor commits that failed and are waiting to be retried (`anchor_jobs`).
+//!
+//! A failed commit leaves its updates pending in memory, and `trusted_state.json` keeps the latest
+//! root. The retry bookkeeping would still be lost on restart: attempt count, backoff deadline,
+//! last error and the intermediate roots the commit covers. One row per outstanding job, keyed by
+//! the first update sequence number it covers, keeps that across restarts. Rows are deleted once
+//! an anchor covers them.
+
+use chrono::{DateTime, Utc};
+use primitive_types::H256;
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+
+/// An anchor commit that failed at least once.
+#[derive(Debug, Clone)]
+pub struct AnchorJob {
+    /// First and last temporary_root update sequence numbers the job covers.
+    pub first_seq: u64,
+    pub last_seq: u64,
+    /// Root of the latest attempt.
+    pub root: H256,
+    pub reason: String,
+    pub attempts: u32,
+    pub last_error: String,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    /// `(seq, root)` of every temporary_root update covered by the job.
+    pub covered_roots: Vec<(u64, H256)>,
+}
+
+/// Summary of a queued job (as reported by `GET /health`).
+#[derive(Debug, Clone, Serialize)]
+pub struct AnchorJobSummary {
+    pub first_seq: u64,
+    pub last_seq: u64,
+    pub root: String,
+    pub attempts: u32,
+    pub last_error: String,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&AnchorJob> for AnchorJobSummary {
+    fn from(job: &AnchorJob) -> Self {
+        Self {
+            first_seq: job.first_seq,
+            last_seq: job.last_seq,
+            root: hex::encode(job.root.as_bytes()),
+            attempts: job.attempts,
+            last_error: job.last_error.clone(),
+            next_attempt_at: job.next_attempt_at,
+            created_at: job.created_at,
+        }
+    }
+}
+
+/// Read/write access to `anchor_jobs`.
+#[derive(Clone)]
+pub struct AnchorJobs {
+    pool: PgPool,
+}
+
+impl AnchorJobs {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates the job table if missing (called by `DatabaseService` on connect).
+    pub async fn ensure_schema(pool: &PgPool) -> anyhow::Result<()> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS anchor_jobs (
+                first_seq BIGINT PRIMARY KEY,
+                last_seq BIGINT NOT NULL,
+                root BYTEA NOT NULL,
+                reason TEXT NOT NULL,
+                attempts INT NOT NULL,
+                last_error TEXT NOT NULL,
+                next_attempt_at TIMESTAMPTZ NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                covered_seqs BIGINT[] NOT NULL,
+                covered_roots BYTEA[] NOT NULL
+            )",
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Inserts `job`, or updates the queued job with the same `first_seq` (keeping `created_at`).
+    pub async fn record_failure(&self, job: &AnchorJob) -> anyhow::Result<()> {
+        let seqs: Vec<i64> = job.covered_roots.iter().map(|(s, _)| *s as i64).collect();
+        let roots: Vec<Vec<u8>> = job
+            .covered_roots
+            .iter()
+            .map(|(_, r)| r.as_bytes().to_vec())
+            .collect();
+        sqlx::query(
+            "INSERT INTO anchor_jobs
+                (first_seq, last_seq, root, reason, attempts, last_error, next_attempt_at,
+                 created_at, covered_seqs, covered_roots)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             ON CONFLICT (first_seq) DO UPDATE
+             SET last_seq = EXCLUDED.last_seq,
+                 root = EXCLUDED.root,
+                 reason = EXCLUDED.reason,
+                 attempts = EXCLUDED.attempts,
+                 last_error = EXCLUDED.last_error,
+                 next_attempt_at = EXCLUDED.next_attempt_at,
+                 updated_at = now(),
+                 covered_seqs = EXCLUDED.covered_seqs,
+                 covered_roots = EXCLUDED.covered_roots",
+        )
+        .bind(job.first_seq as i64)
+        .bind(job.last_seq as i64)
+        .bind(job.root.as_bytes())
+        .bind(&job.reason)
+        .bind(job.attempts as i32)
+        .bind(&job.last_error)
+        .bind(job.next_attempt_at)
+        .bind(job.created_at)
+        .bind(seqs)
+        .bind(roots)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Removes jobs fully covered by an anchor through update `seq`. Returns how many were removed.
+    pub async fn complete_through(&self, seq: u64) -> anyhow::Result<u64> {
+        let done = sqlx::query("DELETE FROM anchor_jobs WHERE last_seq <= $1")
+            .bind(seq as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(done.rows_affected())
+    }
+
+    /// Removes every queued job (after the roots were reset).
+    pub async fn clear(&self) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM anchor_jobs").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Queued jobs, oldest first.
+    pub async fn pending(&self) -> anyhow::Result<Vec<AnchorJob>> {
+        let rows = sqlx::query("SELECT * FROM anchor_jobs ORDER BY first_seq")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.into_iter()
+            .map(|row| {
+                let root: Vec<u8> = row.try_get("root")?;
+                let seqs: Vec<i64> = row.try_get("covered_seqs")?;
+                let roots: Vec<Vec<u8>> = row.try_get("covered_roots")?;
+                let first_seq: i64 = row.try_get("first_seq")?;
+                let last_seq: i64 = row.try_get("last_seq")?;
+                let attempts: i32 = row.try_get("attempts")?;
+                Ok(AnchorJob {
+                    first_seq: first_seq as u64,
+                    last_seq: last_seq as u64,
+                    root: H256::from_slice(&root),
+                    reason: row.try_get("reason")?,
+                    attempts: attempts.max(0) as u32,
+                    last_error: row.try_get("last_error")?,
+                    next_attempt_at: row.try_get("next_attempt_at")?,
+                    created_at: row.try_get("created_at")?,
+                    covered_roots: seqs
+                        .into_iter()
+                        .zip(roots)
+                        .map(|(s, r)| (s as u64, H256::from_slice(&r)))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+}
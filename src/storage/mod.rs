@@ -1,2 +1,3 @@
+pub mod anchor_history;
+pub mod anchor_jobs;
 pub mod smt;
-
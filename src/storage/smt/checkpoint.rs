@@ -0,0 +1,174 @@
+//! Authenticated on-disk checkpoints of the materialized SMT.
+//!
+//! Rebuilding the in-memory tree from `merkle_nodes` re-hashes every branch on every restart.
+//! A checkpoint stores the materialized tree (leaves + branch nodes + root) together with the
+//! highest `merkle_nodes.change_seq` it covers, so startup only has to replay leaves changed
+//! since then (see `SmtStore::from_checkpoint`).
+//!
+//! The file is authenticated with a keyed BLAKE2b MAC whose key comes from the sealing key
+//! provider (`infra::sealing`), like the other sealed local state. A checkpoint that fails the MAC, is
+//! stale, or does not reproduce the trusted root is ignored and the tree is rebuilt in full.
+
+use crate::infra::sealing;
+use crate::storage::smt::postgres::SmtValue;
+use blake2::digest::{KeyInit, Mac};
+use blake2::Blake2bMac;
+use primitive_types::H256;
+use sparse_merkle_tree::default_store::DefaultStore;
+use sparse_merkle_tree::merge::MergeValue;
+use sparse_merkle_tree::traits::StoreWriteOps;
+use sparse_merkle_tree::tree::{BranchKey, BranchNode};
+use sparse_merkle_tree::H256 as SmtH256;
+use std::fs;
+use std::path::Path;
+
+type CheckpointMac = Blake2bMac<sha2::digest::consts::U32>;
+
+const MAGIC: &[u8; 8] = b"VMSMTCK1";
+const MAC_LEN: usize = 32;
+
+/// A materialized copy of the SMT at a known `merkle_nodes` change sequence.
+pub struct SmtCheckpoint {
+    pub root: H256,
+    /// Highest `merkle_nodes.change_seq` reflected in the checkpoint.
+    pub change_seq: i64,
+    pub(crate) store: DefaultStore<SmtValue>,
+}
+
+impl SmtCheckpoint {
+    pub fn leaf_count(&self) -> u64 {
+        self.store.leaves_map().len() as u64
+    }
+
+    pub(crate) fn contains_leaf(&self, key: &SmtH256) -> bool {
+        self.store.leaves_map().contains_key(key)
+    }
+}
+
+/// The checkpoint MAC key, from the `SEALING_KEY_PROVIDER` key provider.
+pub fn checkpoint_key() -> anyhow::Result<[u8; 32]> {
+    sealing::from_config()?.key("smt_checkpoint")
+}
+
+/// Serializes, authenticates and atomically writes `checkpoint` to `path` (write + rename).
+pub fn write_checkpoint(path: &Path, key: &[u8; 32], checkpoint: &SmtCheckpoint) -> anyhow::Result<()> {
+    let leaves = checkpoint.store.leaves_map();
+    let branches = checkpoint.store.branches_map();
+
+    let mut buf = Vec::with_capacity(64 + leaves.len() * 64 + branches.len() * 100);
+    buf.extend_from_slice(MAGIC);
+    buf.extend_from_slice(checkpoint.root.as_bytes());
+    buf.extend_from_slice(&checkpoint.change_seq.to_le_bytes());
+    buf.extend_from_slice(&(leaves.len() as u64).to_le_bytes());
+    buf.extend_from_slice(&(branches.len() as u64).to_le_bytes());
+
+    for (k, v) in leaves {
+        buf.extend_from_slice(k.as_slice());
+        buf.extend_from_slice(v.0.as_slice());
+    }
+    for (k, node) in branches {
+        buf.push(k.height);
+        buf.extend_from_slice(k.node_key.as_slice());
+        encode_merge_value(&mut buf, &node.left);
+        encode_merge_value(&mut buf, &node.right);
+    }
+
+    let mut mac = <CheckpointMac as KeyInit>::new_from_slice(key)?;
+    mac.update(&buf);
+    buf.extend_from_slice(&mac.finalize().into_bytes());
+
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, &buf)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Reads `path` and returns the checkpoint if its MAC verifies under `key`.
+pub fn read_checkpoint(path: &Path, key: &[u8; 32]) -> anyhow::Result<SmtCheckpoint> {
+    let buf = fs::read(path)?;
+    if buf.len() < MAGIC.len() + 56 + MAC_LEN || &buf[..MAGIC.len()] != MAGIC {
+        return Err(anyhow::anyhow!("Not an SMT checkpoint file"));
+    }
+
+    let (body, tag) = buf.split_at(buf.len() - MAC_LEN);
+    let mut mac = <CheckpointMac as KeyInit>::new_from_slice(key)?;
+    mac.update(body);
+    mac.verify_slice(tag)
+        .map_err(|_| anyhow::anyhow!("SMT checkpoint MAC verification failed"))?;
+
+    let mut r = Reader { buf: body, pos: MAGIC.len() };
+    let root = H256::from_slice(r.take(32)?);
+    let change_seq = i64::from_le_bytes(r.take(8)?.try_into()?);
+    let leaf_count = u64::from_le_bytes(r.take(8)?.try_into()?);
+    let branch_count = u64::from_le_bytes(r.take(8)?.try_into()?);
+
+    let mut store = DefaultStore::<SmtValue>::default();
+    for _ in 0..leaf_count {
+        let k = r.h256()?;
+        let v = r.h256()?;
+        store.insert_leaf(k, SmtValue(v))?;
+    }
+    for _ in 0..branch_count {
+        let height = r.take(1)?[0];
+        let node_key = r.h256()?;
+        let left = r.merge_value()?;
+        let right = r.merge_value()?;
+        store.insert_branch(BranchKey::new(height, node_key), BranchNode { left, right })?;
+    }
+    if r.pos != body.len() {
+        return Err(anyhow::anyhow!("Trailing bytes in SMT checkpoint"));
+    }
+
+    Ok(SmtCheckpoint { root, change_seq, store })
+}
+
+fn encode_merge_value(buf: &mut Vec<u8>, v: &MergeValue) {
+    match v {
+        MergeValue::Value(h) => {
+            buf.push(0);
+            buf.extend_from_slice(h.as_slice());
+        }
+        MergeValue::MergeWithZero { base_node, zero_bits, zero_count } => {
+            buf.push(1);
+            buf.extend_from_slice(base_node.as_slice());
+            buf.extend_from_slice(zero_bits.as_slice());
+            buf.push(*zero_count);
+        }
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> anyhow::Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|end| *end <= self.buf.len())
+            .ok_or_else(|| anyhow::anyhow!("Truncated SMT checkpoint"))?;
+        let out = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn h256(&mut self) -> anyhow::Result<SmtH256> {
+        let bytes: [u8; 32] = self.take(32)?.try_into()?;
+        Ok(bytes.into())
+    }
+
+    fn merge_value(&mut self) -> anyhow::Result<MergeValue> {
+        match self.take(1)?[0] {
+            0 => Ok(MergeValue::Value(self.h256()?)),
+            1 => {
+                let base_node = self.h256()?;
+                let zero_bits = self.h256()?;
+                let zero_count = self.take(1)?[0];
+                Ok(MergeValue::MergeWithZero { base_node, zero_bits, zero_count })
+            }
+            t => Err(anyhow::anyhow!("Invalid merge value tag {} in SMT checkpoint", t)),
+        }
+    }
+}
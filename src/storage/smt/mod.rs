@@ -1,6 +1,10 @@
+pub mod checkpoint;
 pub mod postgres;
 pub mod store;
 
-pub use postgres::{PostgresSmtStore, SmtValue};
-pub use store::{h256_to_smt, smt_to_h256, SmtBlake2bHasher, SmtStore};
+pub use checkpoint::{checkpoint_key, read_checkpoint, write_checkpoint, SmtCheckpoint};
+pub use postgres::{PostgresSmtStore, SmtValue, MERKLE_NODES_SHADOW_TABLE, MERKLE_NODES_TABLE};
+pub use store::{
+    compute_root, h256_to_smt, smt_to_h256, tree_order_sql, RootBuilder, SmtBlake2bHasher, SmtStore,
+};
 
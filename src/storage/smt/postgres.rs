@@ -3,6 +3,7 @@
 use anyhow::Result;
 use sparse_merkle_tree::{traits::Value, H256 as SmtH256};
 use sqlx::{PgPool, Row};
+use std::collections::HashMap;
 
 /// SMT value wrapper for the underlying `sparse-merkle-tree` crate.
 #[derive(Clone, Default, Debug, PartialEq, Eq)]
@@ -18,21 +19,67 @@ impl Value for SmtValue {
     }
 }
 
+/// Table holding the live SMT leaves.
+pub const MERKLE_NODES_TABLE: &str = "merkle_nodes";
+
+/// Table used to build a replacement tree next to the live one (see `app::rebuild`).
+pub const MERKLE_NODES_SHADOW_TABLE: &str = "merkle_nodes_shadow";
+
 /// A persistent SMT store that uses a PostgreSQL connection pool.
 #[derive(Clone)]
 pub struct PostgresSmtStore {
     pool: PgPool,
+    table: &'static str,
 }
 
 impl PostgresSmtStore {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self::with_table(pool, MERKLE_NODES_TABLE)
+    }
+
+    /// Persists leaves into `table` instead of `merkle_nodes` (same column layout).
+    pub fn with_table(pool: PgPool, table: &'static str) -> Self {
+        Self { pool, table }
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    pub fn table(&self) -> &'static str {
+        self.table
     }
 
     pub async fn get_all(&self) -> Result<Vec<(SmtH256, SmtValue)>> {
-        let rows = sqlx::query("SELECT node_hash, node_value FROM merkle_nodes")
-            .fetch_all(&self.pool)
-            .await?;
+        let sql = format!("SELECT node_hash, node_value FROM {}", self.table);
+        let rows = sqlx::query(&sql).fetch_all(&self.pool).await?;
+        Self::decode_pairs(rows)
+    }
+
+    /// Leaves written after `change_seq` (see the `change_seq` trigger on `merkle_nodes`).
+    pub async fn get_changed_since(&self, change_seq: i64) -> Result<Vec<(SmtH256, SmtValue)>> {
+        let sql = format!(
+            "SELECT node_hash, node_value FROM {} WHERE change_seq > $1",
+            self.table
+        );
+        let rows = sqlx::query(&sql).bind(change_seq).fetch_all(&self.pool).await?;
+        Self::decode_pairs(rows)
+    }
+
+    /// Number of leaves whose latest write is at or before `change_seq`.
+    pub async fn count_unchanged_since(&self, change_seq: i64) -> Result<u64> {
+        let sql = format!("SELECT count(*) FROM {} WHERE change_seq <= $1", self.table);
+        let n: i64 = sqlx::query_scalar(&sql).bind(change_seq).fetch_one(&self.pool).await?;
+        Ok(n as u64)
+    }
+
+    /// Highest change sequence currently stored (0 when empty).
+    pub async fn max_change_seq(&self) -> Result<i64> {
+        let sql = format!("SELECT COALESCE(max(change_seq), 0) FROM {}", self.table);
+        Ok(sqlx::query_scalar(&sql).fetch_one(&self.pool).await?)
+    }
+
+    fn decode_pairs(rows: Vec<sqlx::postgres::PgRow>) -> Result<Vec<(SmtH256, SmtValue)>> {
         let mut pairs = Vec::with_capacity(rows.len());
         for row in rows {
             let key_bytes: Vec<u8> = row.try_get("node_hash")?;
@@ -54,34 +101,96 @@ impl PostgresSmtStore {
         let key_bytes = key.as_slice();
         let value_h256 = value.to_h256();
         let value_bytes = value_h256.as_slice();
-        sqlx::query(
-            "INSERT INTO merkle_nodes (node_hash, node_value) VALUES ($1, $2)
+        let sql = format!(
+            "INSERT INTO {} (node_hash, node_value) VALUES ($1, $2)
              ON CONFLICT (node_hash) DO UPDATE SET node_value = $2",
-        )
-        .bind(key_bytes)
-        .bind(value_bytes)
-        .execute(&self.pool)
-        .await?;
+            self.table
+        );
+        sqlx::query(&sql)
+            .bind(key_bytes)
+            .bind(value_bytes)
+            .execute(&self.pool)
+            .await?;
         Ok(())
     }
 
+    /// Upserts many leaves owned by `table_name` with a single statement (used for bulk loads
+    /// during rebuilds). Each leaf carries the primary key of the row it hashes.
+    ///
+    /// Later entries win when `leaves` contains the same key more than once.
+    pub async fn set_many(&self, leaves: &[(SmtH256, SmtValue, String)], table_name: &str) -> Result<()> {
+        let mut latest: HashMap<SmtH256, (&SmtValue, &str)> = HashMap::with_capacity(leaves.len());
+        for (k, v, pk) in leaves {
+            latest.insert(*k, (v, pk));
+        }
+        if latest.is_empty() {
+            return Ok(());
+        }
+        let mut keys: Vec<Vec<u8>> = Vec::with_capacity(latest.len());
+        let mut values: Vec<Vec<u8>> = Vec::with_capacity(latest.len());
+        let mut pks: Vec<&str> = Vec::with_capacity(latest.len());
+        for (k, (v, pk)) in latest {
+            keys.push(k.as_slice().to_vec());
+            values.push(v.to_h256().as_slice().to_vec());
+            pks.push(pk);
+        }
+        let sql = format!(
+            "INSERT INTO {} (node_hash, node_value, table_name, pk)
+             SELECT k, v, $3, p FROM UNNEST($1::bytea[], $2::bytea[], $4::text[]) AS t(k, v, p)
+             ON CONFLICT (node_hash)
+             DO UPDATE SET node_value = EXCLUDED.node_value, table_name = EXCLUDED.table_name,
+                           pk = EXCLUDED.pk",
+            self.table
+        );
+        sqlx::query(&sql)
+            .bind(keys)
+            .bind(values)
+            .bind(table_name)
+            .bind(pks)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Upserts the leaf of row `pk` of `table_name` within `tx`.
     pub async fn set_in_tx(
         &self,
         tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
         key: SmtH256,
         value: SmtValue,
+        table_name: &str,
+        pk: &str,
     ) -> Result<()> {
         let key_bytes = key.as_slice();
         let value_h256 = value.to_h256();
         let value_bytes = value_h256.as_slice();
-        sqlx::query(
-            "INSERT INTO merkle_nodes (node_hash, node_value) VALUES ($1, $2)
-             ON CONFLICT (node_hash) DO UPDATE SET node_value = $2",
-        )
-        .bind(key_bytes)
-        .bind(value_bytes)
-        .execute(tx.as_mut())
-        .await?;
+        let sql = format!(
+            "INSERT INTO {} (node_hash, node_value, table_name, pk) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (node_hash) DO UPDATE SET node_value = $2, table_name = $3, pk = $4",
+            self.table
+        );
+        sqlx::query(&sql)
+            .bind(key_bytes)
+            .bind(value_bytes)
+            .bind(table_name)
+            .bind(pk)
+            .execute(tx.as_mut())
+            .await?;
+        Ok(())
+    }
+
+    /// Deletes the given leaves within `tx`.
+    pub async fn delete_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        keys: &[SmtH256],
+    ) -> Result<()> {
+        let key_bytes: Vec<Vec<u8>> = keys.iter().map(|k| k.as_slice().to_vec()).collect();
+        let sql = format!("DELETE FROM {} WHERE node_hash = ANY($1)", self.table);
+        sqlx::query(&sql)
+            .bind(key_bytes)
+            .execute(tx.as_mut())
+            .await?;
         Ok(())
     }
 }
@@ -1,8 +1,10 @@
 //! Sparse Merkle Tree (SMT) wrapper and hashing utilities.
 
+use crate::storage::smt::checkpoint::SmtCheckpoint;
 use crate::storage::smt::postgres::{PostgresSmtStore, SmtValue};
 use blake2::{Blake2b, Digest};
 use primitive_types::H256;
+use sparse_merkle_tree::merge::{merge, MergeValue};
 use sparse_merkle_tree::{default_store::DefaultStore, MerkleProof, SparseMerkleTree, H256 as SmtH256};
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
@@ -17,7 +19,7 @@ impl sparse_merkle_tree::traits::Hasher for SmtBlake2bHasher {
         self.0.update(h.as_slice());
     }
     fn write_byte(&mut self, b: u8) {
-        self.0.update(&[b]);
+        self.0.update([b]);
     }
     fn finish(self) -> SmtH256 {
         let mut hash_bytes = [0u8; 32];
@@ -37,6 +39,107 @@ pub fn smt_to_h256(h: &SmtH256) -> H256 {
     H256::from_slice(h.as_slice())
 }
 
+/// Computes the SMT root over an arbitrary set of leaves without touching `merkle_nodes`.
+///
+/// Used by read-only integrity checks that must not disturb the live tree.
+pub fn compute_root<I>(leaves: I) -> anyhow::Result<H256>
+where
+    I: IntoIterator<Item = (H256, H256)>,
+{
+    let mut tree: SparseMerkleTree<SmtBlake2bHasher, SmtValue, DefaultStore<SmtValue>> =
+        SparseMerkleTree::default();
+    for (k, v) in leaves {
+        tree.update(h256_to_smt(k), SmtValue(h256_to_smt(v)))?;
+    }
+    Ok(smt_to_h256(tree.root()))
+}
+
+/// Computes the same root as `compute_root` from leaves streamed in tree key order (see
+/// `tree_order_sql`), keeping only one pending subtree per level instead of the whole tree.
+#[derive(Default)]
+pub struct RootBuilder {
+    /// Subtrees still waiting for their right sibling: `(path, node, level)`, deepest last.
+    pending: Vec<(SmtH256, MergeValue, u16)>,
+    last_key: Option<SmtH256>,
+}
+
+impl RootBuilder {
+    /// Adds a leaf; keys must be strictly increasing in tree order. Zero values are skipped,
+    /// as they are absent leaves.
+    pub fn push(&mut self, key: H256, value: H256) -> anyhow::Result<()> {
+        let key = h256_to_smt(key);
+        if let Some(last) = self.last_key {
+            if key <= last {
+                return Err(anyhow::anyhow!("Leaves are not in ascending tree order"));
+            }
+        }
+        self.last_key = Some(key);
+        if value.is_zero() {
+            return Ok(());
+        }
+        if let Some((path, _, _)) = self.pending.last() {
+            // The new leaf is the right-hand neighbour of everything below the fork.
+            let fork = path.fork_height(&key) as u16;
+            self.collapse(fork);
+        }
+        self.pending.push((key, MergeValue::from_h256(h256_to_smt(value)), 0));
+        Ok(())
+    }
+
+    pub fn root(mut self) -> H256 {
+        self.collapse(256);
+        match self.pending.pop() {
+            Some((_, node, _)) => smt_to_h256(&node.hash::<SmtBlake2bHasher>()),
+            None => H256::zero(),
+        }
+    }
+
+    /// Merges every pending subtree that joins below `level` and lifts the result to `level`.
+    fn collapse(&mut self, level: u16) {
+        let Some(mut current) = self.pending.pop() else {
+            return;
+        };
+        while let Some(left) = self.pending.last() {
+            let join = left.0.fork_height(&current.0) as u16;
+            if join >= level {
+                break;
+            }
+            let left = self.pending.pop().expect("pending is not empty");
+            let (left_path, left_node) = Self::lift(left, join);
+            let (_, right_node) = Self::lift(current, join);
+            let parent = left_path.parent_path(join as u8);
+            let node = merge::<SmtBlake2bHasher>(join as u8, &parent, &left_node, &right_node);
+            current = (parent, node, join + 1);
+        }
+        let (path, node) = Self::lift(current, level);
+        self.pending.push((path, node, level));
+    }
+
+    /// Lifts a subtree with empty siblings up to `level`.
+    fn lift((mut path, mut node, from): (SmtH256, MergeValue, u16), level: u16) -> (SmtH256, MergeValue) {
+        for height in from..level {
+            let height = height as u8;
+            let parent = path.parent_path(height);
+            node = if path.is_right(height) {
+                merge::<SmtBlake2bHasher>(height, &parent, &MergeValue::zero(), &node)
+            } else {
+                merge::<SmtBlake2bHasher>(height, &parent, &node, &MergeValue::zero())
+            };
+            path = parent;
+        }
+        (path, node)
+    }
+}
+
+/// SQL `ORDER BY` list sorting the 32-byte key in `column` in tree order (last byte first).
+pub fn tree_order_sql(column: &str) -> String {
+    (0..32)
+        .rev()
+        .map(|i| format!("get_byte({}, {})", column, i))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 // --- SMT Store Wrapper ---
 pub struct SmtStore {
     tree: SparseMerkleTree<SmtBlake2bHasher, SmtValue, DefaultStore<SmtValue>>,
@@ -64,6 +167,64 @@ impl SmtStore {
         Ok(Self { tree, db_store })
     }
 
+    /// Restores the tree from `checkpoint` and replays leaves written to `merkle_nodes` since.
+    ///
+    /// Fails if leaves covered by the checkpoint have since disappeared from `merkle_nodes`
+    /// (e.g. after `clear-data`); the caller should then fall back to `new_with_pool`. The
+    /// resulting root must still be checked against the trusted root by the caller.
+    pub async fn from_checkpoint(pool: PgPool, checkpoint: SmtCheckpoint) -> anyhow::Result<Self> {
+        let db_store = PostgresSmtStore::new(pool);
+        let changed = db_store.get_changed_since(checkpoint.change_seq).await?;
+        let unchanged = db_store.count_unchanged_since(checkpoint.change_seq).await?;
+
+        // Every checkpointed leaf must still exist, either untouched or rewritten since.
+        let rewritten = changed
+            .iter()
+            .filter(|(k, _)| checkpoint.contains_leaf(k))
+            .count() as u64;
+        if unchanged + rewritten != checkpoint.leaf_count() {
+            return Err(anyhow::anyhow!(
+                "SMT checkpoint is stale: {} leaves checkpointed, {} still present",
+                checkpoint.leaf_count(),
+                unchanged + rewritten
+            ));
+        }
+
+        let mut tree = SparseMerkleTree::new(h256_to_smt(checkpoint.root), checkpoint.store);
+        tree.update_all(changed)?;
+        Ok(Self { tree, db_store })
+    }
+
+    /// Copies the materialized tree for checkpointing at `change_seq`.
+    ///
+    /// Caller must ensure no leaf write is in flight (hold `RootManager::lock_root()`).
+    pub fn checkpoint(&self, change_seq: i64) -> SmtCheckpoint {
+        SmtCheckpoint {
+            root: smt_to_h256(self.tree.root()),
+            change_seq,
+            store: self.tree.store().clone(),
+        }
+    }
+
+    pub async fn max_change_seq(&self) -> anyhow::Result<i64> {
+        self.db_store.max_change_seq().await
+    }
+
+    /// Creates an empty tree that persists into `table` instead of `merkle_nodes`.
+    ///
+    /// Used to build a shadow tree next to the live one without loading any leaves.
+    pub fn new_empty_with_table(pool: PgPool, table: &'static str) -> Self {
+        Self {
+            tree: SparseMerkleTree::default(),
+            db_store: PostgresSmtStore::with_table(pool, table),
+        }
+    }
+
+    /// Points leaf persistence at `table` while keeping the in-memory tree as-is.
+    pub fn retarget(&mut self, table: &'static str) {
+        self.db_store = PostgresSmtStore::with_table(self.db_store.pool().clone(), table);
+    }
+
     pub async fn get_root(&self) -> anyhow::Result<H256> {
         Ok(smt_to_h256(self.tree.root()))
     }
@@ -76,25 +237,57 @@ impl SmtStore {
         Ok(())
     }
 
+    /// Applies many `(key, value, pk)` updates to leaves of `table_name` at once: one bulk tree
+    /// update and one bulk upsert into the store.
+    pub async fn update_all(
+        &mut self,
+        updates: &[(H256, H256, String)],
+        table_name: &str,
+    ) -> anyhow::Result<()> {
+        let leaves: Vec<(SmtH256, SmtValue, String)> = updates
+            .iter()
+            .map(|(k, v, pk)| (h256_to_smt(*k), SmtValue(h256_to_smt(*v)), pk.clone()))
+            .collect();
+        self.tree
+            .update_all(leaves.iter().map(|(k, v, _)| (*k, v.clone())).collect())?;
+        self.db_store.set_many(&leaves, table_name).await?;
+        Ok(())
+    }
+
     pub async fn generate_proof(&self, keys: Vec<H256>) -> anyhow::Result<MerkleProof> {
         let smt_keys: Vec<SmtH256> = keys.into_iter().map(h256_to_smt).collect();
         let proof = self.tree.merkle_proof(smt_keys)?;
         Ok(proof)
     }
 
-    /// Applies updates to the in-memory tree AND persists them into `merkle_nodes` within `tx`.
+    /// Applies `(key, value, pk)` updates to the in-memory tree AND persists them into
+    /// `merkle_nodes` within `tx`, recording `table_name` and the row's pk as the owner of each leaf.
     pub async fn apply_updates_in_tx(
         &mut self,
         tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
-        updates: &[(H256, H256)],
+        table_name: &str,
+        updates: &[(H256, H256, String)],
     ) -> anyhow::Result<()> {
-        for (k, v) in updates {
+        for (k, v, pk) in updates {
             let key_smt = h256_to_smt(*k);
             let value_smt = SmtValue(h256_to_smt(*v));
             self.tree.update(key_smt, value_smt.clone())?;
-            self.db_store.set_in_tx(tx, key_smt, value_smt).await?;
+            self.db_store.set_in_tx(tx, key_smt, value_smt, table_name, pk).await?;
         }
         Ok(())
     }
+
+    /// Removes leaves from the in-memory tree AND deletes them from `merkle_nodes` within `tx`.
+    pub async fn remove_leaves_in_tx(
+        &mut self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        keys: &[H256],
+    ) -> anyhow::Result<()> {
+        let keys_smt: Vec<SmtH256> = keys.iter().copied().map(h256_to_smt).collect();
+        self.tree
+            .update_all(keys_smt.iter().map(|k| (*k, SmtValue::default())).collect())?;
+        self.db_store.delete_in_tx(tx, &keys_smt).await?;
+        Ok(())
+    }
 }
 
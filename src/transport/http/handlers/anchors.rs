@@ -0,0 +1,393 @@
+use crate::domain::commitment::budget::{self, BudgetPeriod};
+use crate::storage::anchor_history::{AnchorHistory, AnchorSpend};
+use crate::transport::http::handlers::common::{anchor_status_json, parse_h256_hex};
+use crate::transport::http::types::{
+    AnchorCostsQuery, AnchorListQuery, AnchorWaitQuery, ApiResponse, AppState,
+};
+use chrono::Utc;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use std::time::Duration;
+
+const DEFAULT_ANCHOR_PAGE: i64 = 50;
+const MAX_ANCHOR_PAGE: i64 = 1000;
+const MAX_ANCHOR_WAIT_SECS: u64 = 60;
+const DEFAULT_COST_PERIODS: i64 = 30;
+const DEFAULT_COST_WINDOW_DAYS: u64 = 7;
+const MAX_COST_WINDOW_DAYS: u64 = 3650;
+const MAX_FORECAST_BATCH_SIZES: usize = 20;
+/// Shortest history the write rate is measured over, so one fresh anchor cannot make it spike.
+const MIN_RATE_WINDOW_SECS: f64 = 60.0;
+
+fn history_unavailable() -> Response {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(ApiResponse {
+            success: false,
+            data: None,
+            error: Some("Anchor history is not attached to this service".to_string()),
+        }),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/anchors",
+    params(
+        ("limit" = Option<i64>, Query, description = "Max entries to return (default 50, max 1000)"),
+        ("before_id" = Option<i64>, Query, description = "Only anchors with id < before_id (pagination)")
+    ),
+    responses(
+        (status = 200, description = "Anchors, newest first (root, update seq range, signature, slot, confirmed time)", body = ApiResponse),
+        (status = 503, description = "Anchor history not attached", body = ApiResponse)
+    )
+)]
+pub async fn list_anchors_handler(
+    State(state): State<AppState>,
+    Query(query): Query<AnchorListQuery>,
+) -> impl IntoResponse {
+    let Some(history) = state.root_manager.history() else {
+        return history_unavailable();
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_ANCHOR_PAGE).clamp(1, MAX_ANCHOR_PAGE);
+
+    match history.list(limit, query.before_id).await {
+        Ok(anchors) => {
+            let next_before_id = if anchors.len() as i64 == limit {
+                anchors.last().map(|a| a.id)
+            } else {
+                None
+            };
+            (
+                StatusCode::OK,
+                Json(ApiResponse {
+                    success: true,
+                    data: Some(serde_json::json!({
+                        "anchors": anchors,
+                        "next_before_id": next_before_id,
+                    })),
+                    error: None,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(format!("Failed reading anchor history: {}", e)),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+fn bad_request(error: String) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ApiResponse {
+            success: false,
+            data: None,
+            error: Some(error),
+        }),
+    )
+        .into_response()
+}
+
+/// Average fee of the anchors in `spend` whose fee is known.
+fn fee_per_anchor(spend: &AnchorSpend) -> Option<f64> {
+    (spend.priced_anchors > 0).then(|| spend.fee_lamports as f64 / spend.priced_anchors as f64)
+}
+
+/// Totals, per-period spend and the write rate and fee of the last `window_days`.
+async fn anchor_costs(
+    history: &AnchorHistory,
+    period: BudgetPeriod,
+    limit: i64,
+    window_days: u64,
+) -> anyhow::Result<(AnchorSpend, Vec<AnchorSpend>, AnchorSpend, f64)> {
+    let total = history.spend_since(None).await?;
+    let periods = history.spend_by_period(period.as_str(), limit).await?;
+    let now = Utc::now();
+    let window_start = now - chrono::Duration::days(window_days as i64);
+    let window = history.spend_since(Some(window_start)).await?;
+    // Measured from the first anchor ever when the history is shorter than the window.
+    let measured_from = total.first_confirmed_at.map_or(window_start, |t| t.max(window_start));
+    let secs = ((now - measured_from).num_milliseconds() as f64 / 1000.0).max(MIN_RATE_WINDOW_SECS);
+    let updates_per_sec = window.updates as f64 / secs;
+    Ok((total, periods, window, updates_per_sec))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/anchors/costs",
+    params(
+        ("period" = Option<String>, Query, description = "hour, day, week or month (default: the budget period, else day)"),
+        ("limit" = Option<i64>, Query, description = "Periods to list, newest first (default 30, max 1000)"),
+        ("window_days" = Option<u64>, Query, description = "Days of history the averages and write rate are taken from (default 7, max 3650)"),
+        ("batch_sizes" = Option<String>, Query, description = "Comma-separated BATCH_COMMIT_SIZE values to forecast (default 1,10,100,1000 and the current one)")
+    ),
+    responses(
+        (status = 200, description = "Cumulative and per-period anchoring spend (lamports), average fee per anchor and per update, budget status, and a daily cost forecast per batch size", body = ApiResponse),
+        (status = 400, description = "Invalid period or batch sizes", body = ApiResponse),
+        (status = 503, description = "Anchor history not attached", body = ApiResponse)
+    )
+)]
+pub async fn anchor_costs_handler(
+    State(state): State<AppState>,
+    Query(query): Query<AnchorCostsQuery>,
+) -> impl IntoResponse {
+    let budget = state.root_manager.budget_status();
+    let period = match query.period.as_deref() {
+        Some(p) => match BudgetPeriod::parse(p) {
+            Some(period) => period,
+            None => return bad_request(format!("Invalid period {:?} (hour, day, week or month)", p)),
+        },
+        None => budget.as_ref().map_or(BudgetPeriod::Day, |b| b.policy.period),
+    };
+    let policy = state.root_manager.policy();
+    let mut batch_sizes = match query.batch_sizes.as_deref() {
+        Some(list) => match list
+            .split(',')
+            .filter(|v| !v.trim().is_empty())
+            .map(|v| v.trim().parse::<u64>().map(|b| b.max(1)))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(sizes) => sizes,
+            Err(e) => return bad_request(format!("Invalid batch_sizes: {}", e)),
+        },
+        None => vec![1, 10, 100, 1000, policy.batch_size],
+    };
+    batch_sizes.sort_unstable();
+    batch_sizes.dedup();
+    if batch_sizes.len() > MAX_FORECAST_BATCH_SIZES {
+        return bad_request(format!(
+            "At most {} batch sizes can be forecast at once",
+            MAX_FORECAST_BATCH_SIZES
+        ));
+    }
+    let Some(history) = state.root_manager.history() else {
+        return history_unavailable();
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_COST_PERIODS).clamp(1, MAX_ANCHOR_PAGE);
+    let window_days = query
+        .window_days
+        .unwrap_or(DEFAULT_COST_WINDOW_DAYS)
+        .clamp(1, MAX_COST_WINDOW_DAYS);
+
+    let (total, periods, window, updates_per_sec) =
+        match anchor_costs(&history, period, limit, window_days).await {
+            Ok(costs) => costs,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse {
+                        success: false,
+                        data: None,
+                        error: Some(format!("Failed reading anchor history: {}", e)),
+                    }),
+                )
+                    .into_response()
+            }
+        };
+    // Prefer the recent fee (priority fees move); fall back to all of history.
+    let avg_fee = fee_per_anchor(&window).or_else(|| fee_per_anchor(&total));
+    let avg_fee_per_update = (window.updates > 0 && window.priced_anchors > 0)
+        .then(|| window.fee_lamports as f64 / window.updates as f64);
+    let forecast = avg_fee.map(|fee| {
+        batch_sizes
+            .iter()
+            .map(|&b| budget::forecast(fee, updates_per_sec, b, policy.max_age))
+            .collect::<Vec<_>>()
+    });
+
+    (
+        StatusCode::OK,
+        Json(ApiResponse {
+            success: true,
+            data: Some(serde_json::json!({
+                "total": total,
+                "period": period,
+                "periods": periods,
+                "window": {
+                    "days": window_days,
+                    "spend": window,
+                    "updates_per_sec": updates_per_sec,
+                },
+                "average_fee_lamports_per_anchor": avg_fee,
+                "average_fee_lamports_per_update": avg_fee_per_update,
+                "forecast": {
+                    "max_age_secs": policy.max_age.map(|d| d.as_secs()),
+                    "batch_sizes": forecast,
+                },
+                "budget": budget,
+            })),
+            error: None,
+        }),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/anchors/covering/{root}",
+    params(
+        ("root" = String, Path, description = "32-byte root (hex, optional 0x prefix)")
+    ),
+    responses(
+        (status = 200, description = "The first anchor that covered this root", body = ApiResponse),
+        (status = 400, description = "Invalid root", body = ApiResponse),
+        (status = 404, description = "Root not covered by any recorded anchor (yet)", body = ApiResponse),
+        (status = 503, description = "Anchor history not attached", body = ApiResponse)
+    )
+)]
+pub async fn anchor_covering_root_handler(
+    State(state): State<AppState>,
+    Path(root): Path<String>,
+) -> impl IntoResponse {
+    let root = match parse_h256_hex(&root) {
+        Ok(r) => r,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(format!("Invalid root: {}", e)),
+                }),
+            )
+                .into_response();
+        }
+    };
+    let Some(history) = state.root_manager.history() else {
+        return history_unavailable();
+    };
+
+    match history.first_covering(root).await {
+        Ok(Some(anchor)) => (
+            StatusCode::OK,
+            Json(ApiResponse {
+                success: true,
+                data: Some(serde_json::json!(anchor)),
+                error: None,
+            }),
+        )
+            .into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(format!(
+                    "Root {} is not covered by any recorded anchor",
+                    hex::encode(root.as_bytes())
+                )),
+            }),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(format!("Failed reading anchor history: {}", e)),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/anchors/seq/{seq}",
+    params(
+        ("seq" = u64, Path, description = "Write sequence number (`meta.seq` of a write response)"),
+        ("wait_secs" = Option<u64>, Query, description = "Long-poll: wait up to this many seconds for the write to be anchored (default 0, max 60)")
+    ),
+    responses(
+        (status = 200, description = "Whether the write is anchored, with the covering anchor (root, signature, slot) once it is", body = ApiResponse),
+        (status = 404, description = "No write with this sequence number yet", body = ApiResponse)
+    )
+)]
+pub async fn anchor_status_handler(
+    State(state): State<AppState>,
+    Path(seq): Path<u64>,
+    Query(query): Query<AnchorWaitQuery>,
+) -> impl IntoResponse {
+    let update_seq = state.root_manager.update_seq().await;
+    if seq > update_seq {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(format!("No write #{} yet (latest is #{})", seq, update_seq)),
+            }),
+        )
+            .into_response();
+    }
+
+    let wait_secs = query.wait_secs.unwrap_or(0).min(MAX_ANCHOR_WAIT_SECS);
+    if wait_secs > 0 {
+        state
+            .root_manager
+            .wait_for_anchor_timeout(seq, Duration::from_secs(wait_secs))
+            .await;
+    }
+
+    (
+        StatusCode::OK,
+        Json(ApiResponse {
+            success: true,
+            data: Some(anchor_status_json(&state, seq).await),
+            error: None,
+        }),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/roots",
+    responses(
+        (status = 200, description = "Local main/temporary roots and the anchored state (root, timestamp, read slot, address, authority and sequence where the backend keeps them)", body = ApiResponse),
+        (status = 502, description = "The anchor could not be read (local roots are still returned)", body = ApiResponse)
+    )
+)]
+pub async fn roots_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let main_root = state.root_manager.get_main_root().await;
+    let temporary_root = state.root_manager.get_temporary_root().await;
+    let local = serde_json::json!({
+        "main_root": hex::encode(main_root.as_bytes()),
+        "temporary_root": hex::encode(temporary_root.as_bytes()),
+    });
+
+    match state.anchor.read_record().await {
+        Ok(record) => (
+            StatusCode::OK,
+            Json(ApiResponse {
+                success: true,
+                data: Some(serde_json::json!({
+                    "local": local,
+                    "anchored": record,
+                    "main_root_anchored": record.root == main_root,
+                })),
+                error: None,
+            }),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_GATEWAY,
+            Json(ApiResponse {
+                success: false,
+                data: Some(serde_json::json!({ "local": local })),
+                error: Some(format!("Failed reading the anchored root: {}", e)),
+            }),
+        )
+            .into_response(),
+    }
+}
@@ -1,19 +1,27 @@
+use crate::app::drift::scan_drift;
+use crate::app::shutdown::WriteGuard;
+use crate::app::rebuild::{rebuild_smt_from_db, RebuildPhase, ShadowSnapshot};
 use crate::crypto::hashing::hash_value;
-use crate::domain::model::{DynamicModel, ModelRegistry};
-use crate::infra::solana;
-use crate::transport::http::handlers::common::{column_type_to_sql, pk_kind_to_sql, validate_ident};
+use crate::domain::model::{DynamicModel, ModelRegistry, VerifiableModel};
+use crate::infra::anchor::StaleAnchor;
+use crate::transport::http::handlers::common::{
+    begin_write, column_type_to_sql, pk_kind_to_sql, validate_ident,
+};
 use crate::transport::http::types::{
-    ApiResponse, AppState, BootstrapRequest, ClearDataRequest, MigrateRequest, RepairRootsRequest,
+    ApiResponse, AppState, BootstrapRequest, ClearDataRequest, GcLeavesRequest, MigrateRequest,
+    RemoveModelRequest, RepairRootsRequest, INTERNAL_TABLES,
 };
 use axum::extract::State;
 use axum::extract::rejection::JsonRejection;
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use primitive_types::H256;
-use sqlx::Row;
+use sqlx::{PgPool, Row};
 use std::collections::HashMap;
+use std::future::Future;
 use std::path::Path;
+use std::sync::Arc;
 
 #[utoipa::path(
     post,
@@ -22,13 +30,19 @@ use std::path::Path;
     responses(
         (status = 200, description = "Schema applied", body = ApiResponse),
         (status = 400, description = "Bad request", body = ApiResponse),
-        (status = 500, description = "Internal server error", body = ApiResponse)
+        (status = 409, description = "Reset refused: another writer moved the anchor (retry with force_reset)", body = ApiResponse),
+        (status = 500, description = "Internal server error", body = ApiResponse),
+        (status = 502, description = "Reset refused: the anchor could not be written", body = ApiResponse)
     )
 )]
 pub async fn bootstrap_apply_schema_handler(
     State(state): State<AppState>,
     request: Result<Json<BootstrapRequest>, JsonRejection>,
 ) -> impl IntoResponse {
+    let _write_guard = match begin_write(&state) {
+        Ok(guard) => guard,
+        Err(resp) => return resp.into_response(),
+    };
     // Prevent any interleaving with background commits / other writes.
     let _root_guard = state.root_manager.lock_root().await;
 
@@ -115,12 +129,12 @@ pub async fn bootstrap_apply_schema_handler(
         }
     }
 
-    let schema_json = serde_json::to_value(&normalized_tables).unwrap_or_else(|_| serde_json::Value::Null);
+    let schema_json = serde_json::to_value(&normalized_tables).unwrap_or(serde_json::Value::Null);
     let schema_hash_h256 = hash_value(&schema_json);
     let schema_hash = hex::encode(schema_hash_h256.as_bytes());
 
     // Decide if we need a reset (single-tenant, reset-on-changes).
-    let blockchain_root = solana::read_root().await.unwrap_or_else(|_| H256::zero());
+    let blockchain_root = state.anchor.read_root().await.unwrap_or_else(|_| H256::zero());
 
     let mut db_service = state.db_service.lock().await;
     let pool = db_service.pool().clone();
@@ -159,10 +173,25 @@ pub async fn bootstrap_apply_schema_handler(
         || (blockchain_root != H256::zero() && current_hash.is_none());
 
     if needs_reset {
-        // Reset on-chain + in-memory roots first.
-        let _ = solana::write_root(H256::zero()).await;
-        state.root_manager.clear_trusted_state_file();
-        state.root_manager.reset_roots(H256::zero()).await;
+        // Reset on-chain + in-memory roots first. Only an explicit `force_reset` overwrites an
+        // anchor another writer moved, or goes on resetting the data when the anchor cannot be
+        // written; otherwise nothing is touched.
+        let anchored = if request.force_reset {
+            state.root_manager.clear_trusted_state_file();
+            match state.root_manager.force_anchor_and_reset_roots(H256::zero()).await {
+                Ok(()) => true,
+                Err(e) => {
+                    eprintln!("> Apply-schema: Failed resetting on-chain root (continuing): {}", e);
+                    state.root_manager.reset_roots(H256::zero()).await;
+                    false
+                }
+            }
+        } else {
+            match state.root_manager.anchor_and_reset_roots(H256::zero()).await {
+                Ok(()) => true,
+                Err(e) => return reset_refused(e),
+            }
+        };
 
         // Reset DB state for all managed tables AND the requested tables (covers first-run drift).
         let mut tables_to_drop = existing_tables.clone();
@@ -192,6 +221,9 @@ pub async fn bootstrap_apply_schema_handler(
 
         // Reset SMT store in memory
         let _ = db_service.reset_smt_store().await;
+        if anchored {
+            state.reconcile.resolve("apply-schema");
+        }
     }
 
     // Apply tables + persist registry.
@@ -243,7 +275,7 @@ pub async fn bootstrap_apply_schema_handler(
         }
 
         let columns_json =
-            serde_json::to_value(&t.columns).unwrap_or_else(|_| serde_json::Value::Null);
+            serde_json::to_value(&t.columns).unwrap_or(serde_json::Value::Null);
         let _ = sqlx::query(
             "INSERT INTO verifiable_models (table_name, primary_key_field, primary_key_kind, columns, create_table_sql)
              VALUES ($1, $2, $3, $4, $5)
@@ -360,6 +392,10 @@ pub async fn bootstrap_clear_data_handler(
     State(state): State<AppState>,
     request: Result<Json<ClearDataRequest>, JsonRejection>,
 ) -> impl IntoResponse {
+    let _write_guard = match begin_write(&state) {
+        Ok(guard) => guard,
+        Err(resp) => return resp.into_response(),
+    };
     let Json(request) = match request {
         Ok(v) => v,
         Err(e) => {
@@ -407,8 +443,9 @@ pub async fn bootstrap_clear_data_handler(
             .into_response();
     }
 
-    // Reset roots to zero: write chain root first, then sync in-memory/trusted file.
-    if let Err(e) = solana::write_root(H256::zero()).await {
+    // Reset roots to zero: write chain root first, then sync in-memory/trusted file. This is an
+    // explicit operator reset, so it overwrites the anchor even if another writer moved it.
+    if let Err(e) = state.root_manager.force_anchor_and_reset_roots(H256::zero()).await {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ApiResponse {
@@ -419,8 +456,7 @@ pub async fn bootstrap_clear_data_handler(
         )
             .into_response();
     }
-
-    state.root_manager.reset_roots(H256::zero()).await;
+    state.reconcile.resolve("clear-data");
 
     let response_data = serde_json::json!({
         "cleared": true,
@@ -445,6 +481,7 @@ pub async fn bootstrap_clear_data_handler(
     responses(
         (status = 200, description = "Migrations applied + SMT rebuilt + roots updated", body = ApiResponse),
         (status = 400, description = "Bad request", body = ApiResponse),
+        (status = 409, description = "Another rebuild is already in progress", body = ApiResponse),
         (status = 422, description = "Unprocessable entity (invalid JSON body)", body = ApiResponse),
         (status = 500, description = "Internal server error", body = ApiResponse)
     )
@@ -453,6 +490,10 @@ pub async fn bootstrap_migrate_handler(
     State(state): State<AppState>,
     request: Result<Json<MigrateRequest>, JsonRejection>,
 ) -> impl IntoResponse {
+    let write_guard = match begin_write(&state) {
+        Ok(guard) => guard,
+        Err(resp) => return resp.into_response(),
+    };
     let Json(request) = match request {
         Ok(v) => v,
         Err(e) => {
@@ -483,11 +524,18 @@ pub async fn bootstrap_migrate_handler(
             .into_response();
     }
 
-    // Serialize all DB + SMT work under the db_service mutex to avoid concurrent reads/writes
-    // while schema/data are changing.
-    // Prevent any interleaving with background commits / other writes.
-    let _root_guard = state.root_manager.lock_root().await;
-    let mut db_service = state.db_service.lock().await;
+    if let Err(e) = state.rebuild.try_begin("migrate") {
+        return rebuild_conflict(e);
+    }
+    run_detached(&state, migrate(state.clone(), write_guard)).await
+}
+
+/// Body of `bootstrap_migrate_handler` once the rebuild is claimed (see `run_detached`).
+async fn migrate(state: AppState, _write_guard: WriteGuard) -> Response {
+    // Phase 1 (writes paused): apply migrations, refresh the registry, pin a snapshot and start
+    // capturing writes. The SMT itself is rebuilt afterwards without holding any lock.
+    let root_guard = state.root_manager.lock_root().await;
+    let db_service = state.db_service.lock().await;
     let pool = db_service.pool().clone();
 
     let old_temp_root = state.root_manager.get_temporary_root().await;
@@ -497,28 +545,12 @@ pub async fn bootstrap_migrate_handler(
     let migrator = match sqlx::migrate::Migrator::new(Path::new("./migrations")).await {
         Ok(m) => m,
         Err(e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse {
-                    success: false,
-                    data: None,
-                    error: Some(format!("Failed initializing migrator: {}", e)),
-                }),
-            )
-                .into_response();
+            return rebuild_failed(&state, format!("Failed initializing migrator: {}", e));
         }
     };
 
     if let Err(e) = migrator.run(&pool).await {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse {
-                success: false,
-                data: None,
-                error: Some(format!("Failed applying migrations: {}", e)),
-            }),
-        )
-            .into_response();
+        return rebuild_failed(&state, format!("Failed applying migrations: {}", e));
     }
 
     // 2) Schema drift handling for client-table migrations:
@@ -600,15 +632,7 @@ pub async fn bootstrap_migrate_handler(
     let new_registry = match ModelRegistry::load_from_db(&pool).await {
         Ok(r) => r,
         Err(e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse {
-                    success: false,
-                    data: None,
-                    error: Some(format!("Failed loading model registry from DB: {}", e)),
-                }),
-            )
-                .into_response();
+            return rebuild_failed(&state, format!("Failed loading model registry from DB: {}", e));
         }
     };
 
@@ -617,7 +641,10 @@ pub async fn bootstrap_migrate_handler(
         *reg_lock = new_registry;
     }
 
-    // 4) Recompute SMT from post-migration DB rows and force-update both roots.
+    // 4) Recompute SMT from post-migration DB rows (shadow rebuild) and force-update both roots.
+    //
+    // Until the swap, rows whose `row_to_json` shape changed fail read verification against the
+    // old tree; writes keep working and are replayed into the new tree at swap time.
     let models = {
         let reg = state.model_registry.read().await;
         let mut out = Vec::new();
@@ -629,40 +656,39 @@ pub async fn bootstrap_migrate_handler(
         out
     };
 
-    let (new_root, updated_leaves) = match db_service.rebuild_smt_from_db(models).await {
-        Ok(v) => v,
+    let snapshot = match db_service.begin_shadow_rebuild().await {
+        Ok(s) => s,
         Err(e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse {
-                    success: false,
-                    data: None,
-                    error: Some(format!("Failed rebuilding SMT from DB: {}", e)),
-                }),
-            )
-                .into_response();
+            return rebuild_failed(&state, format!("Failed starting shadow rebuild: {}", e));
         }
     };
+    drop(db_service);
+    drop(root_guard);
 
-    if let Err(e) = state.root_manager.force_set_roots_and_commit(new_root).await {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse {
-                success: false,
-                data: None,
-                error: Some(format!("Failed committing new root to Solana: {}", e)),
-            }),
-        )
-            .into_response();
-    }
+    let (new_root, updated_leaves, replayed_writes) =
+        match run_shadow_rebuild(&state, pool, snapshot, models).await {
+            Ok(v) => v,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse {
+                        success: false,
+                        data: None,
+                        error: Some(e),
+                    }),
+                )
+                    .into_response();
+            }
+        };
 
     let response_data = serde_json::json!({
         "migrated": true,
         "updated_leaves": updated_leaves,
+        "replayed_writes": replayed_writes,
         "old_temporary_root": hex::encode(old_temp_root.as_bytes()),
         "old_main_root": hex::encode(old_main_root.as_bytes()),
         "new_root": hex::encode(new_root.as_bytes()),
-        "message": "Migrations applied. SMT rebuilt from post-migration DB state. temporary_root + main_root committed to Solana."
+        "message": "Migrations applied. SMT rebuilt from a post-migration snapshot, writes made during the rebuild replayed, temporary_root + main_root committed to the anchor."
     });
 
     (
@@ -683,6 +709,7 @@ pub async fn bootstrap_migrate_handler(
     responses(
         (status = 200, description = "SMT rebuilt from DB + roots force-set and committed", body = ApiResponse),
         (status = 400, description = "Bad request", body = ApiResponse),
+        (status = 409, description = "Another rebuild is already in progress", body = ApiResponse),
         (status = 422, description = "Unprocessable entity (invalid JSON body)", body = ApiResponse),
         (status = 500, description = "Internal server error", body = ApiResponse)
     )
@@ -691,6 +718,10 @@ pub async fn bootstrap_repair_roots_handler(
     State(state): State<AppState>,
     request: Result<Json<RepairRootsRequest>, JsonRejection>,
 ) -> impl IntoResponse {
+    let write_guard = match begin_write(&state) {
+        Ok(guard) => guard,
+        Err(resp) => return resp.into_response(),
+    };
     let Json(request) = match request {
         Ok(v) => v,
         Err(e) => {
@@ -721,14 +752,112 @@ pub async fn bootstrap_repair_roots_handler(
             .into_response();
     }
 
-    // Prevent any interleaving with writes/commits while we rebuild.
-    let _root_guard = state.root_manager.lock_root().await;
+    if let Err(e) = state.rebuild.try_begin("repair-roots") {
+        return rebuild_conflict(e);
+    }
+    run_detached(&state, repair_roots(state.clone(), write_guard)).await
+}
 
-    let mut db_service = state.db_service.lock().await;
-    let pool = db_service.pool().clone();
+/// Body of `bootstrap_repair_roots_handler` once the rebuild is claimed (see `run_detached`).
+async fn repair_roots(state: AppState, _write_guard: WriteGuard) -> Response {
+    let pool = {
+        let db_service = state.db_service.lock().await;
+        db_service.pool().clone()
+    };
 
     // Load registry from DB and rebuild SMT from current table rows (canonical row_to_json hashing).
     let reg = match crate::domain::model::ModelRegistry::load_from_db(&pool).await {
+        Ok(r) => r,
+        Err(e) => {
+            return rebuild_failed(&state, format!("Failed loading model registry from DB: {}", e));
+        }
+    };
+
+    if reg.list_models().is_empty() {
+        state.rebuild.fail("No models found in verifiable_models; nothing to repair.");
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some("No models found in verifiable_models; nothing to repair.".to_string()),
+            }),
+        )
+            .into_response();
+    }
+
+    let mut models = Vec::new();
+    for name in reg.list_models() {
+        if let Some(m) = reg.get(&name) {
+            models.push(m);
+        }
+    }
+
+    // Pin the snapshot with writes paused, then rebuild while the live tree keeps serving.
+    let snapshot = {
+        let _root_guard = state.root_manager.lock_root().await;
+        let db_service = state.db_service.lock().await;
+        match db_service.begin_shadow_rebuild().await {
+            Ok(s) => s,
+            Err(e) => {
+                return rebuild_failed(&state, format!("Failed starting shadow rebuild: {}", e));
+            }
+        }
+    };
+
+    let (new_root, updated_leaves, replayed_writes) =
+        match run_shadow_rebuild(&state, pool, snapshot, models).await {
+            Ok(v) => v,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse {
+                        success: false,
+                        data: None,
+                        error: Some(e),
+                    }),
+                )
+                    .into_response();
+            }
+        };
+
+    let response_data = serde_json::json!({
+        "repaired": true,
+        "updated_leaves": updated_leaves,
+        "replayed_writes": replayed_writes,
+        "new_root": hex::encode(new_root.as_bytes()),
+        "message": "Rebuilt SMT from DB rows and force-set temporary_root + main_root to the rebuilt root."
+    });
+
+    (
+        StatusCode::OK,
+        Json(ApiResponse {
+            success: true,
+            data: Some(response_data),
+            error: None,
+        }),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/bootstrap/drift",
+    responses(
+        (status = 200, description = "Row-level drift scan completed (no roots changed)", body = ApiResponse),
+        (status = 400, description = "Bad request", body = ApiResponse),
+        (status = 500, description = "Internal server error", body = ApiResponse)
+    )
+)]
+pub async fn bootstrap_drift_scan_handler(State(state): State<AppState>) -> impl IntoResponse {
+    // Read-only: the scan uses its own snapshot transaction, so we don't take the root lock
+    // or hold the db_service mutex while hashing every row.
+    let pool = {
+        let db_service = state.db_service.lock().await;
+        db_service.pool().clone()
+    };
+
+    let reg = match ModelRegistry::load_from_db(&pool).await {
         Ok(r) => r,
         Err(e) => {
             return (
@@ -749,7 +878,7 @@ pub async fn bootstrap_repair_roots_handler(
             Json(ApiResponse {
                 success: false,
                 data: None,
-                error: Some("No models found in verifiable_models; nothing to repair.".to_string()),
+                error: Some("No models found in verifiable_models; nothing to scan.".to_string()),
             }),
         )
             .into_response();
@@ -762,7 +891,152 @@ pub async fn bootstrap_repair_roots_handler(
         }
     }
 
-    let (new_root, updated_leaves) = match db_service.rebuild_smt_from_db(models).await {
+    let report = match scan_drift(&pool, models).await {
+        Ok(r) => r,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(format!("Failed scanning rows for drift: {}", e)),
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    let temp_root = hex::encode(state.root_manager.get_temporary_root().await.as_bytes());
+    let main_root = hex::encode(state.root_manager.get_main_root().await.as_bytes());
+
+    let response_data = serde_json::json!({
+        "clean": report.is_clean(),
+        "temporary_root": temp_root,
+        "main_root": main_root,
+        "stored_leaves_match_temporary_root": report.stored_leaves_root == temp_root,
+        "rows_match_temporary_root": report.rows_root == temp_root,
+        "report": report,
+    });
+
+    (
+        StatusCode::OK,
+        Json(ApiResponse {
+            success: true,
+            data: Some(response_data),
+            error: None,
+        }),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/bootstrap/rebuild-status",
+    responses(
+        (status = 200, description = "Progress of the current (or last) shadow SMT rebuild", body = ApiResponse)
+    )
+)]
+pub async fn bootstrap_rebuild_status_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let status = state.rebuild.status();
+    (
+        StatusCode::OK,
+        Json(ApiResponse {
+            success: true,
+            data: Some(serde_json::json!(status)),
+            error: None,
+        }),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/bootstrap/remove-model",
+    request_body = RemoveModelRequest,
+    responses(
+        (status = 200, description = "Table dropped and its leaves removed in one verified root transition", body = ApiResponse),
+        (status = 400, description = "Bad request", body = ApiResponse),
+        (status = 404, description = "Model not registered", body = ApiResponse),
+        (status = 409, description = "A rebuild is in progress", body = ApiResponse),
+        (status = 422, description = "Unprocessable entity (invalid JSON body)", body = ApiResponse),
+        (status = 500, description = "Internal server error", body = ApiResponse)
+    )
+)]
+pub async fn bootstrap_remove_model_handler(
+    State(state): State<AppState>,
+    request: Result<Json<RemoveModelRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let _write_guard = match begin_write(&state) {
+        Ok(guard) => guard,
+        Err(resp) => return resp.into_response(),
+    };
+    let Json(request) = match request {
+        Ok(v) => v,
+        Err(e) => {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(format!(
+                        "Invalid JSON body: {} (expected: {{\"table_name\": \"...\", \"confirm\": true}})",
+                        e
+                    )),
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(resp) = check_leaf_removal_request(&request.table_name, request.confirm) {
+        return resp;
+    }
+
+    let _root_guard = state.root_manager.lock_root().await;
+    if let Some(resp) = check_no_rebuild(&state) {
+        return resp;
+    }
+    let db_service = state.db_service.lock().await;
+
+    let registered: bool =
+        match sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM verifiable_models WHERE table_name = $1)")
+            .bind(&request.table_name)
+            .fetch_one(db_service.pool())
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse {
+                        success: false,
+                        data: None,
+                        error: Some(format!("Failed reading verifiable_models: {}", e)),
+                    }),
+                )
+                    .into_response();
+            }
+        };
+    if !registered {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(format!(
+                    "'{}' is not a registered model (use /bootstrap/gc-leaves for orphaned leaves)",
+                    request.table_name
+                )),
+            }),
+        )
+            .into_response();
+    }
+
+    let old_root = state.root_manager.get_temporary_root().await;
+    let (new_root, removed_leaves) = match db_service
+        .remove_model_leaves(&request.table_name, true, old_root)
+        .await
+    {
         Ok(v) => v,
         Err(e) => {
             return (
@@ -770,30 +1044,154 @@ pub async fn bootstrap_repair_roots_handler(
                 Json(ApiResponse {
                     success: false,
                     data: None,
-                    error: Some(format!("Failed rebuilding SMT from DB: {}", e)),
+                    error: Some(format!("Failed removing model: {}", e)),
                 }),
             )
                 .into_response();
         }
     };
+    if new_root != old_root {
+        state.root_manager.update_temporary_root(new_root).await;
+    }
 
-    if let Err(e) = state.root_manager.force_set_roots_and_commit(new_root).await {
+    {
+        let mut reg = state.model_registry.write().await;
+        match ModelRegistry::load_from_db(db_service.pool()).await {
+            Ok(r) => *reg = r,
+            Err(e) => eprintln!("> Remove-model: failed reloading model registry: {}", e),
+        }
+    }
+
+    let response_data = serde_json::json!({
+        "removed": true,
+        "table_name": request.table_name,
+        "removed_leaves": removed_leaves,
+        "old_root": hex::encode(old_root.as_bytes()),
+        "new_root": hex::encode(new_root.as_bytes()),
+        "message": "Dropped the table, removed its registry entry and removed its leaves from the SMT in one verified transition."
+    });
+
+    (
+        StatusCode::OK,
+        Json(ApiResponse {
+            success: true,
+            data: Some(response_data),
+            error: None,
+        }),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/bootstrap/gc-leaves",
+    request_body = GcLeavesRequest,
+    responses(
+        (status = 200, description = "Orphaned leaves removed in one verified root transition", body = ApiResponse),
+        (status = 400, description = "Bad request", body = ApiResponse),
+        (status = 409, description = "Table is still registered, or a rebuild is in progress", body = ApiResponse),
+        (status = 422, description = "Unprocessable entity (invalid JSON body)", body = ApiResponse),
+        (status = 500, description = "Internal server error", body = ApiResponse)
+    )
+)]
+pub async fn bootstrap_gc_leaves_handler(
+    State(state): State<AppState>,
+    request: Result<Json<GcLeavesRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let _write_guard = match begin_write(&state) {
+        Ok(guard) => guard,
+        Err(resp) => return resp.into_response(),
+    };
+    let Json(request) = match request {
+        Ok(v) => v,
+        Err(e) => {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(format!(
+                        "Invalid JSON body: {} (expected: {{\"table_name\": \"...\", \"confirm\": true}})",
+                        e
+                    )),
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(resp) = check_leaf_removal_request(&request.table_name, request.confirm) {
+        return resp;
+    }
+
+    let _root_guard = state.root_manager.lock_root().await;
+    if let Some(resp) = check_no_rebuild(&state) {
+        return resp;
+    }
+    let db_service = state.db_service.lock().await;
+
+    // Removing the leaves of a live model would make all of its rows unverifiable.
+    let registered: bool =
+        match sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM verifiable_models WHERE table_name = $1)")
+            .bind(&request.table_name)
+            .fetch_one(db_service.pool())
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse {
+                        success: false,
+                        data: None,
+                        error: Some(format!("Failed reading verifiable_models: {}", e)),
+                    }),
+                )
+                    .into_response();
+            }
+        };
+    if registered {
         return (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::CONFLICT,
             Json(ApiResponse {
                 success: false,
                 data: None,
-                error: Some(format!("Failed committing repaired root to Solana: {}", e)),
+                error: Some(format!(
+                    "'{}' is still a registered model (use /bootstrap/remove-model)",
+                    request.table_name
+                )),
             }),
         )
             .into_response();
     }
 
+    let old_root = state.root_manager.get_temporary_root().await;
+    let (new_root, removed_leaves) = match db_service
+        .remove_model_leaves(&request.table_name, false, old_root)
+        .await
+    {
+        Ok(v) => v,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(format!("Failed removing leaves: {}", e)),
+                }),
+            )
+                .into_response();
+        }
+    };
+    if new_root != old_root {
+        state.root_manager.update_temporary_root(new_root).await;
+    }
+
     let response_data = serde_json::json!({
-        "repaired": true,
-        "updated_leaves": updated_leaves,
+        "table_name": request.table_name,
+        "removed_leaves": removed_leaves,
+        "old_root": hex::encode(old_root.as_bytes()),
         "new_root": hex::encode(new_root.as_bytes()),
-        "message": "Rebuilt SMT from DB rows and force-set temporary_root + main_root to the rebuilt root."
     });
 
     (
@@ -806,3 +1204,165 @@ pub async fn bootstrap_repair_roots_handler(
     )
         .into_response()
 }
+
+/// Shared validation for `remove-model` / `gc-leaves`.
+fn check_leaf_removal_request(table_name: &str, confirm: bool) -> Option<Response> {
+    let bad_request = |msg: String| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(msg),
+            }),
+        )
+            .into_response()
+    };
+
+    if !confirm {
+        return Some(bad_request("confirm must be true to remove leaves".to_string()));
+    }
+    if !validate_ident(table_name) || INTERNAL_TABLES.contains(&table_name) {
+        return Some(bad_request(format!("Invalid table_name: {}", table_name)));
+    }
+    None
+}
+
+/// Refuses leaf removal while a shadow rebuild runs: its snapshot predates the removal, so the
+/// swap would re-insert the removed leaves.
+///
+/// Call with the root lock held. A rebuild pins its snapshot under that lock, so one that has
+/// not started by now snapshots after the removal and sees it.
+fn check_no_rebuild(state: &AppState) -> Option<Response> {
+    let rebuild = state.rebuild.status();
+    if rebuild.in_progress() {
+        return Some(rebuild_conflict(anyhow::anyhow!(
+            "A {} rebuild is in progress; retry when it completes",
+            rebuild.operation.as_deref().unwrap_or("unknown")
+        )));
+    }
+    None
+}
+
+/// Runs a rebuild that has passed `RebuildTracker::try_begin` on its own task and waits for it.
+///
+/// Dropping the request (a client disconnecting mid-rebuild) does not cancel the task, so the
+/// rebuild still completes or fails through its own cleanup. Cancelling it halfway would leave
+/// the tracker in progress (every later write answers 409), the replay log growing and
+/// `merkle_nodes_shadow` behind. Should the task panic, that cleanup is done here.
+async fn run_detached(
+    state: &AppState,
+    rebuild: impl Future<Output = Response> + Send + 'static,
+) -> Response {
+    match tokio::spawn(rebuild).await {
+        Ok(response) => response,
+        Err(e) => {
+            if let Err(e) = state.db_service.lock().await.abort_shadow_rebuild().await {
+                eprintln!("> Rebuild: failed dropping shadow table: {}", e);
+            }
+            rebuild_failed(state, format!("Rebuild task failed: {}", e))
+        }
+    }
+}
+
+/// Phases 2-4 of a shadow rebuild (see `app::rebuild`): build without locks, then swap and
+/// anchor with writes paused.
+///
+/// On failure the live tree is left untouched, the shadow table is dropped and the tracker is
+/// marked failed. Returns `(new_root, leaves, replayed_writes)`.
+async fn run_shadow_rebuild(
+    state: &AppState,
+    pool: PgPool,
+    snapshot: ShadowSnapshot,
+    models: Vec<Arc<dyn VerifiableModel>>,
+) -> Result<(H256, u64, u64), String> {
+    let shadow = match rebuild_smt_from_db(pool, snapshot, models, &state.rebuild).await {
+        Ok(t) => t,
+        Err(e) => {
+            let msg = format!("Failed rebuilding SMT from DB: {}", e);
+            let db_service = state.db_service.lock().await;
+            if let Err(e) = db_service.abort_shadow_rebuild().await {
+                eprintln!("> Rebuild: failed dropping shadow table: {}", e);
+            }
+            state.rebuild.fail(&msg);
+            return Err(msg);
+        }
+    };
+
+    // Prevent any interleaving with writes/commits while we swap and anchor.
+    let _root_guard = state.root_manager.lock_root().await;
+    let mut db_service = state.db_service.lock().await;
+
+    state.rebuild.set_phase(RebuildPhase::Swapping);
+    let (new_root, leaves, replayed) = match db_service.swap_in_shadow(shadow).await {
+        Ok(v) => v,
+        Err(e) => {
+            let msg = format!("Failed swapping in rebuilt SMT: {}", e);
+            if let Err(e) = db_service.abort_shadow_rebuild().await {
+                eprintln!("> Rebuild: failed dropping shadow table: {}", e);
+            }
+            state.rebuild.fail(&msg);
+            return Err(msg);
+        }
+    };
+    state.rebuild.update(|s| s.replayed_writes = replayed);
+
+    state.rebuild.set_phase(RebuildPhase::Anchoring);
+    if let Err(e) = state.root_manager.force_set_roots_and_commit(new_root).await {
+        let msg = format!("Failed committing rebuilt root to the anchor: {}", e);
+        state.rebuild.fail(&msg);
+        return Err(msg);
+    }
+
+    state.rebuild.complete(new_root);
+    let operation = state.rebuild.status().operation.unwrap_or_default();
+    state.reconcile.resolve(&operation);
+    Ok((new_root, leaves, replayed))
+}
+
+/// Answers an apply-schema whose conditional anchor reset failed: **409** if another writer
+/// moved the anchor, **502** if it could not be written.
+fn reset_refused(e: anyhow::Error) -> Response {
+    let status = if e.is::<StaleAnchor>() {
+        StatusCode::CONFLICT
+    } else {
+        StatusCode::BAD_GATEWAY
+    };
+    (
+        status,
+        Json(ApiResponse {
+            success: false,
+            data: None,
+            error: Some(format!(
+                "Failed resetting the anchored root; nothing was reset (force_reset overrides): {}",
+                e
+            )),
+        }),
+    )
+        .into_response()
+}
+
+fn rebuild_conflict(e: anyhow::Error) -> Response {
+    (
+        StatusCode::CONFLICT,
+        Json(ApiResponse {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+        }),
+    )
+        .into_response()
+}
+
+fn rebuild_failed(state: &AppState, msg: String) -> Response {
+    state.rebuild.fail(&msg);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ApiResponse {
+            success: false,
+            data: None,
+            error: Some(msg),
+        }),
+    )
+        .into_response()
+}
@@ -0,0 +1,73 @@
+use crate::domain::commitment::BudgetExceeded;
+use crate::transport::http::types::{ApiResponse, AppState};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+
+#[utoipa::path(
+    get,
+    path = "/api/commit-status",
+    responses(
+        (status = 200, description = "Pending (un-anchored) updates, time since the last anchor and the commit policy", body = ApiResponse)
+    )
+)]
+pub async fn commit_status_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let status = state.root_manager.commit_status().await;
+    (
+        StatusCode::OK,
+        Json(ApiResponse {
+            success: true,
+            data: Some(serde_json::json!(status)),
+            error: None,
+        }),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/commit",
+    responses(
+        (status = 200, description = "temporary_root anchored now (or already anchored)", body = ApiResponse),
+        (status = 429, description = "Refused: the anchoring budget of the current period is spent (ANCHOR_BUDGET_ACTION=refuse)", body = ApiResponse),
+        (status = 502, description = "Anchoring failed", body = ApiResponse)
+    )
+)]
+pub async fn commit_now_handler(State(state): State<AppState>) -> impl IntoResponse {
+    match state.root_manager.commit_now().await {
+        Ok(committed) => {
+            let status = state.root_manager.commit_status().await;
+            (
+                StatusCode::OK,
+                Json(ApiResponse {
+                    success: true,
+                    data: Some(serde_json::json!({
+                        "committed": committed,
+                        "status": status,
+                    })),
+                    error: None,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) if e.is::<BudgetExceeded>() => (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ApiResponse {
+                success: false,
+                data: Some(serde_json::json!({ "budget": state.root_manager.budget_status() })),
+                error: Some(e.to_string()),
+            }),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_GATEWAY,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(format!("Failed anchoring temporary_root: {}", e)),
+            }),
+        )
+            .into_response(),
+    }
+}
@@ -1,3 +1,4 @@
+use crate::app::shutdown::WriteGuard;
 use crate::transport::http::types::{ApiResponse, ColumnType, PrimaryKeyKind};
 use axum::http::StatusCode;
 use axum::Json;
@@ -60,6 +61,109 @@ pub async fn ensure_model_registered_refreshing(
     })
 }
 
+/// Admits a mutating request through the shutdown gate; `503` once shutdown has begun.
+/// Hold the guard until the DB / SMT changes are done (see `app::shutdown`).
+pub fn begin_write(
+    state: &crate::transport::http::types::AppState,
+) -> Result<WriteGuard, (StatusCode, Json<ApiResponse>)> {
+    state.shutdown.try_enter().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some("Service is shutting down; writes are no longer accepted.".to_string()),
+            }),
+        )
+    })
+}
+
+/// Admits a data write: rejects it during shutdown (see `begin_write`) and while startup
+/// reconciliation or the chain watcher keeps the service read-only (see `app::reconcile`).
+pub fn ensure_writes_allowed(
+    state: &crate::transport::http::types::AppState,
+) -> Result<WriteGuard, (StatusCode, Json<ApiResponse>)> {
+    let guard = begin_write(state)?;
+    if state.reconcile.writes_allowed() {
+        return Ok(guard);
+    }
+    let report = state.reconcile.status();
+    let (situation, action) = report
+        .as_ref()
+        .map(|r| (format!("{:?}", r.situation), format!("{:?}", r.action)))
+        .unwrap_or_default();
+    Err((
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(ApiResponse {
+            success: false,
+            data: report.map(|r| serde_json::json!(r)),
+            error: Some(format!(
+                "Writes are disabled: reconciliation found {} (action: {}). See GET /api/reconcile-status.",
+                situation, action
+            )),
+        }),
+    ))
+}
+
+/// Waits for `update` to be anchored when the write asked for it (`wait_for_anchor`, which
+/// commits on demand) or when the update made a commit due (other writers keep going).
+///
+/// Returns whether the update is known to be anchored. A failed commit is logged; the update
+/// stays pending and is retried by the background task.
+pub async fn wait_for_write_anchor(
+    state: &crate::transport::http::types::AppState,
+    update: crate::domain::commitment::RootUpdate,
+    wait_for_anchor: bool,
+) -> bool {
+    let result = if wait_for_anchor {
+        println!("> TEE (API): Anchoring update #{} on request...", update.seq);
+        state.root_manager.anchor_through(update.seq).await
+    } else if update.commit_due {
+        println!("> TEE (API): Waiting for update #{} to be anchored...", update.seq);
+        state.root_manager.wait_for_anchor(update.seq).await
+    } else {
+        return false;
+    };
+    match result {
+        Ok(()) => {
+            println!("> TEE (API): Blockchain commit completed.");
+            true
+        }
+        Err(e) => {
+            eprintln!("> TEE (API): Blockchain commit failed (update stays pending): {}", e);
+            false
+        }
+    }
+}
+
+/// Anchoring status of update `seq`, including the covering anchor (signature, slot) when
+/// anchor history is attached.
+pub async fn anchor_status_json(
+    state: &crate::transport::http::types::AppState,
+    seq: u64,
+) -> JsonValue {
+    let progress = state.root_manager.anchor_progress();
+    let anchored = progress.anchored_seq >= seq;
+    let anchor = match (anchored, state.root_manager.history()) {
+        (true, Some(history)) => match history.covering_seq(seq).await {
+            Ok(entry) => entry,
+            Err(e) => {
+                eprintln!("> TEE (API): Failed reading anchor history: {}", e);
+                None
+            }
+        },
+        _ => None,
+    };
+    serde_json::json!({
+        "seq": seq,
+        "anchored": anchored,
+        "anchored_seq": progress.anchored_seq,
+        "update_seq": state.root_manager.update_seq().await,
+        "anchor": anchor,
+        "last_error": if anchored { None } else { progress.last_error },
+    })
+}
+
 pub fn pk_json_to_string(pk: &JsonValue) -> Option<String> {
     if let Some(s) = pk.as_str() {
         return Some(s.to_string());
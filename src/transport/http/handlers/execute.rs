@@ -1,6 +1,9 @@
 use crate::crypto::hashing::{hash_key, hash_value};
 use crate::domain::verify::verify_smt_proof;
-use crate::transport::http::handlers::common::{ensure_model_registered_refreshing, pk_json_to_string};
+use crate::transport::http::handlers::common::{
+    anchor_status_json, ensure_model_registered_refreshing, ensure_writes_allowed, pk_json_to_string,
+    wait_for_write_anchor,
+};
 use crate::transport::http::types::{Action, ApiRequest, ApiResponse, AppState};
 use axum::extract::State;
 use axum::http::StatusCode;
@@ -32,6 +35,10 @@ pub async fn execute_handler(
 
     match request.action {
         Action::CreateBatch => {
+            let write_guard = match ensure_writes_allowed(&state) {
+                Ok(guard) => guard,
+                Err(resp) => return resp.into_response(),
+            };
             let records: Vec<JsonValue> = match serde_json::from_value(request.payload) {
                 Ok(r) => r,
                 Err(e) => {
@@ -58,16 +65,20 @@ pub async fn execute_handler(
             {
                 Ok((proposed_root, _proof, inserted_records, inserted_ids)) => {
                     println!("> TEE (API): Validation successful. Updating temporary_root.");
-                    let triggers_commit = state.root_manager.update_temporary_root(proposed_root).await;
+                    let update = state.root_manager.update_temporary_root(proposed_root).await;
 
                     drop(db_service);
                     drop(root_guard);
+                    drop(write_guard);
 
-                    if triggers_commit {
-                        println!("> TEE (API): Waiting for blockchain commit to complete...");
-                        state.root_manager.wait_for_commit_completion().await;
-                        println!("> TEE (API): Blockchain commit completed.");
-                    }
+                    let seq = update.seq;
+                    let committed =
+                        wait_for_write_anchor(&state, update, request.wait_for_anchor).await;
+                    let anchor_status = if request.wait_for_anchor {
+                        Some(anchor_status_json(&state, seq).await)
+                    } else {
+                        None
+                    };
 
                     let response_data = serde_json::json!({
                         "ids": inserted_ids,
@@ -75,7 +86,9 @@ pub async fn execute_handler(
                         "verified": true,
                         "meta": {
                             "proposed_root": hex::encode(proposed_root.as_bytes()),
-                            "committed": triggers_commit
+                            "seq": seq,
+                            "committed": committed,
+                            "anchor_status": anchor_status
                         }
                     });
                     (
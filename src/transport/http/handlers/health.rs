@@ -1,3 +1,5 @@
+use crate::domain::commitment::AnchorState;
+use crate::storage::anchor_jobs::AnchorJobSummary;
 use crate::transport::http::types::{ApiResponse, AppState};
 use axum::extract::State;
 use axum::http::StatusCode;
@@ -8,33 +10,89 @@ use axum::Json;
     get,
     path = "/health",
     responses(
-        (status = 200, description = "Service is healthy (DB reachable)", body = ApiResponse),
-        (status = 503, description = "Service is unhealthy (DB unreachable)", body = ApiResponse)
+        (status = 200, description = "Service is healthy (DB reachable); includes anchoring retry state and queued anchor jobs", body = ApiResponse),
+        (status = 503, description = "Service is unhealthy (DB unreachable) or degraded (anchor circuit breaker open)", body = ApiResponse)
     )
 )]
 pub async fn healthcheck_handler(State(state): State<AppState>) -> impl IntoResponse {
     let db_service = state.db_service.lock().await;
     let pool = db_service.pool().clone();
+    drop(db_service);
 
-    match sqlx::query("SELECT 1").execute(&pool).await {
-        Ok(_) => (
-            StatusCode::OK,
+    if let Err(e) = sqlx::query("SELECT 1").execute(&pool).await {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
             Json(ApiResponse {
-                success: true,
-                data: Some(serde_json::json!({ "status": "ok" })),
-                error: None,
+                success: false,
+                data: Some(serde_json::json!({ "status": "unhealthy" })),
+                error: Some(format!("DB ping failed: {}", e)),
             }),
         )
-            .into_response(),
-        Err(e) => (
+            .into_response();
+    }
+
+    let anchoring = state.root_manager.anchor_health().await;
+    let queued_jobs: Vec<AnchorJobSummary> = match state.root_manager.jobs() {
+        Some(jobs) => match jobs.pending().await {
+            Ok(pending) => pending.iter().map(AnchorJobSummary::from).collect(),
+            Err(e) => {
+                eprintln!("> Health: failed to read anchor jobs: {}", e);
+                Vec::new()
+            }
+        },
+        None => Vec::new(),
+    };
+
+    let degraded = anchoring.state == AnchorState::Degraded;
+    let data = serde_json::json!({
+        "status": if degraded { "degraded" } else { "ok" },
+        "anchoring": anchoring,
+        "queued_jobs": queued_jobs,
+    });
+    if degraded {
+        return (
             StatusCode::SERVICE_UNAVAILABLE,
             Json(ApiResponse {
                 success: false,
-                data: Some(serde_json::json!({ "status": "unhealthy" })),
-                error: Some(format!("DB ping failed: {}", e)),
+                data: Some(data),
+                error: Some(format!(
+                    "Anchoring degraded: {} consecutive commit failures",
+                    anchoring.consecutive_failures
+                )),
             }),
         )
-            .into_response(),
+            .into_response();
     }
+    (
+        StatusCode::OK,
+        Json(ApiResponse {
+            success: true,
+            data: Some(data),
+            error: None,
+        }),
+    )
+        .into_response()
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/reconcile-status",
+    responses(
+        (status = 200, description = "Startup reconciliation outcome: root situation, applied action, whether writes are allowed", body = ApiResponse)
+    )
+)]
+pub async fn reconcile_status_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let data = match state.reconcile.status() {
+        Some(report) => serde_json::json!({ "reconciled": true, "report": report }),
+        None => serde_json::json!({ "reconciled": false, "writes_allowed": true }),
+    };
+    (
+        StatusCode::OK,
+        Json(ApiResponse {
+            success: true,
+            data: Some(data),
+            error: None,
+        }),
+    )
+        .into_response()
+}
@@ -1,8 +1,8 @@
 use crate::crypto::hashing::{hash_key, hash_value};
 use crate::domain::verify::verify_smt_proof;
 use crate::transport::http::handlers::common::{
-    coerce_scalar_for_type, ensure_model_registered_refreshing, parse_h256_hex, pk_json_to_string,
-    validate_ident, FieldError,
+    anchor_status_json, coerce_scalar_for_type, ensure_model_registered_refreshing, ensure_writes_allowed,
+    parse_h256_hex, pk_json_to_string, validate_ident, wait_for_write_anchor, FieldError,
 };
 use crate::transport::http::types::{
     ApiResponse, AppState, CreateBatchRequest, OrderDirection, ReadBatchRequest, ReadLatestRequest,
@@ -41,6 +41,10 @@ pub async fn create_batch_handler(
         Err(resp) => return resp.into_response(),
     };
     let _table_name = model.table_name();
+    let write_guard = match ensure_writes_allowed(&state) {
+        Ok(guard) => guard,
+        Err(resp) => return resp.into_response(),
+    };
 
     let Json(request) = match request {
         Ok(v) => v,
@@ -176,16 +180,19 @@ pub async fn create_batch_handler(
     {
         Ok((proposed_root, _proof, inserted_records, inserted_ids)) => {
             println!("> TEE (API): Validation successful. Updating temporary_root.");
-            let triggers_commit = state.root_manager.update_temporary_root(proposed_root).await;
+            let update = state.root_manager.update_temporary_root(proposed_root).await;
 
             drop(db_service);
             drop(root_guard);
+            drop(write_guard);
 
-            if triggers_commit {
-                println!("> TEE (API): Waiting for blockchain commit to complete...");
-                state.root_manager.wait_for_commit_completion().await;
-                println!("> TEE (API): Blockchain commit completed.");
-            }
+            let seq = update.seq;
+            let committed = wait_for_write_anchor(&state, update, request.wait_for_anchor).await;
+            let anchor_status = if request.wait_for_anchor {
+                Some(anchor_status_json(&state, seq).await)
+            } else {
+                None
+            };
 
             let response_data = serde_json::json!({
                 "ids": inserted_ids,
@@ -193,7 +200,9 @@ pub async fn create_batch_handler(
                 "verified": true,
                 "meta": {
                     "proposed_root": hex::encode(proposed_root.as_bytes()),
-                    "committed": triggers_commit
+                    "seq": seq,
+                    "committed": committed,
+                    "anchor_status": anchor_status
                 }
             });
             (
@@ -692,6 +701,10 @@ pub async fn upsert_batch_handler(
         Err(resp) => return resp.into_response(),
     };
     let _table_name = model.table_name();
+    let write_guard = match ensure_writes_allowed(&state) {
+        Ok(guard) => guard,
+        Err(resp) => return resp.into_response(),
+    };
     let pk_field = model.primary_key_field().to_string();
 
     let Json(request) = match request {
@@ -835,12 +848,17 @@ pub async fn upsert_batch_handler(
         .await
     {
         Ok((proposed_root, _proof, upserted_records, upserted_ids)) => {
-            let triggers_commit = state.root_manager.update_temporary_root(proposed_root).await;
+            let update = state.root_manager.update_temporary_root(proposed_root).await;
             drop(db_service);
             drop(root_guard);
-            if triggers_commit {
-                state.root_manager.wait_for_commit_completion().await;
-            }
+            drop(write_guard);
+            let seq = update.seq;
+            let committed = wait_for_write_anchor(&state, update, request.wait_for_anchor).await;
+            let anchor_status = if request.wait_for_anchor {
+                Some(anchor_status_json(&state, seq).await)
+            } else {
+                None
+            };
 
             let response_data = serde_json::json!({
                 "ids": upserted_ids,
@@ -848,7 +866,9 @@ pub async fn upsert_batch_handler(
                 "verified": true,
                 "meta": {
                     "proposed_root": hex::encode(proposed_root.as_bytes()),
-                    "committed": triggers_commit
+                    "seq": seq,
+                    "committed": committed,
+                    "anchor_status": anchor_status
                 }
             });
             (
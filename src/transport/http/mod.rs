@@ -1,7 +1,9 @@
 pub mod router;
 pub mod types;
 pub mod handlers {
+    pub mod anchors;
     pub mod bootstrap;
+    pub mod commit;
     pub mod common;
     pub mod execute;
     pub mod health;
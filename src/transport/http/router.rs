@@ -1,9 +1,9 @@
-use crate::transport::http::handlers::{bootstrap, execute, health, models, schema};
+use crate::transport::http::handlers::{anchors, bootstrap, commit, execute, health, models, schema};
 use crate::transport::http::types::{
     Action, ApiRequest, ApiResponse, BootstrapRequest, ClearDataRequest, ColumnSpec, ColumnType,
     CreateBatchRequest, CurrentSchemaResponse, DbColumnSchema, DbTableSchema, PrimaryKeyKind,
     ReadBatchRequest, ReadLatestRequest, TableSpec, MigrateRequest, OrderBySpec, OrderDirection,
-    UpsertBatchRequest, RepairRootsRequest,
+    UpsertBatchRequest, RepairRootsRequest, RemoveModelRequest, GcLeavesRequest,
 };
 use axum::routing::{get, post};
 use axum::Router;
@@ -18,10 +18,22 @@ use utoipa::OpenApi;
         models::read_batch_handler,
         models::read_latest_handler,
         models::upsert_batch_handler,
+        commit::commit_status_handler,
+        commit::commit_now_handler,
+        anchors::list_anchors_handler,
+        anchors::anchor_costs_handler,
+        anchors::anchor_covering_root_handler,
+        anchors::anchor_status_handler,
+        anchors::roots_handler,
+        health::reconcile_status_handler,
         bootstrap::bootstrap_apply_schema_handler,
         bootstrap::bootstrap_clear_data_handler,
         bootstrap::bootstrap_migrate_handler,
         bootstrap::bootstrap_repair_roots_handler,
+        bootstrap::bootstrap_drift_scan_handler,
+        bootstrap::bootstrap_rebuild_status_handler,
+        bootstrap::bootstrap_remove_model_handler,
+        bootstrap::bootstrap_gc_leaves_handler,
         schema::bootstrap_get_schema_handler
     ),
     components(schemas(
@@ -38,6 +50,8 @@ use utoipa::OpenApi;
         ClearDataRequest,
         MigrateRequest,
         RepairRootsRequest,
+        RemoveModelRequest,
+        GcLeavesRequest,
         TableSpec,
         ColumnSpec,
         ColumnType,
@@ -58,6 +72,17 @@ pub fn create_router(app_state: crate::transport::http::types::AppState) -> Rout
         .route("/api/models/:model/read-batch", post(models::read_batch_handler))
         .route("/api/models/:model/read-latest", post(models::read_latest_handler))
         .route("/api/models/:model/upsert", post(models::upsert_batch_handler))
+        .route("/api/commit-status", get(commit::commit_status_handler))
+        .route("/api/commit", post(commit::commit_now_handler))
+        .route("/api/anchors", get(anchors::list_anchors_handler))
+        .route("/api/anchors/costs", get(anchors::anchor_costs_handler))
+        .route(
+            "/api/anchors/covering/:root",
+            get(anchors::anchor_covering_root_handler),
+        )
+        .route("/api/anchors/seq/:seq", get(anchors::anchor_status_handler))
+        .route("/api/roots", get(anchors::roots_handler))
+        .route("/api/reconcile-status", get(health::reconcile_status_handler))
         .route(
             "/bootstrap/apply-schema",
             post(bootstrap::bootstrap_apply_schema_handler),
@@ -65,6 +90,13 @@ pub fn create_router(app_state: crate::transport::http::types::AppState) -> Rout
         .route("/bootstrap/clear-data", post(bootstrap::bootstrap_clear_data_handler))
         .route("/bootstrap/migrate", post(bootstrap::bootstrap_migrate_handler))
         .route("/bootstrap/repair-roots", post(bootstrap::bootstrap_repair_roots_handler))
+        .route("/bootstrap/drift", get(bootstrap::bootstrap_drift_scan_handler))
+        .route(
+            "/bootstrap/rebuild-status",
+            get(bootstrap::bootstrap_rebuild_status_handler),
+        )
+        .route("/bootstrap/remove-model", post(bootstrap::bootstrap_remove_model_handler))
+        .route("/bootstrap/gc-leaves", post(bootstrap::bootstrap_gc_leaves_handler))
         .route("/bootstrap/schema", get(schema::bootstrap_get_schema_handler))
         .with_state(app_state)
 }
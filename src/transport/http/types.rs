@@ -1,6 +1,10 @@
 use crate::app::database_service::DatabaseService;
+use crate::app::rebuild::RebuildTracker;
+use crate::app::reconcile::ReconcileTracker;
+use crate::app::shutdown::ShutdownGate;
 use crate::domain::commitment::RootManager;
 use crate::domain::model::ModelRegistry;
+use crate::infra::anchor::RootAnchor;
 use axum::extract::rejection::JsonRejection;
 use axum::http::StatusCode;
 use axum::Json;
@@ -16,6 +20,33 @@ pub struct AppState {
     pub db_service: Arc<Mutex<DatabaseService>>,
     pub model_registry: Arc<RwLock<ModelRegistry>>,
     pub root_manager: Arc<RootManager>,
+    /// Anchor the root manager commits to (used directly by bootstrap resets).
+    pub anchor: Arc<dyn RootAnchor>,
+    /// Progress of the current (or last) shadow SMT rebuild.
+    pub rebuild: RebuildTracker,
+    /// Outcome of startup reconciliation or a chain-watch alarm; gates data writes (see
+    /// `app::reconcile`, `app::chain_watch`).
+    pub reconcile: ReconcileTracker,
+    /// Closed on SIGTERM/SIGINT; mutating requests hold a guard from it (see `app::shutdown`).
+    pub shutdown: ShutdownGate,
+}
+
+impl AppState {
+    pub fn new(
+        db_service: Arc<Mutex<DatabaseService>>,
+        model_registry: Arc<RwLock<ModelRegistry>>,
+        root_manager: Arc<RootManager>,
+    ) -> Self {
+        Self {
+            db_service,
+            model_registry,
+            anchor: root_manager.anchor().clone(),
+            root_manager,
+            rebuild: RebuildTracker::new(),
+            reconcile: ReconcileTracker::new(),
+            shutdown: ShutdownGate::new(),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, ToSchema)]
@@ -31,6 +62,9 @@ pub struct ApiRequest {
     pub action: Action,
     #[schema(value_type = Object)]
     pub payload: JsonValue,
+    /// Wait until this write is anchored (committing on demand) before responding.
+    #[serde(default)]
+    pub wait_for_anchor: bool,
 }
 
 #[derive(Serialize, Debug, ToSchema)]
@@ -51,6 +85,9 @@ pub struct CreateBatchRequest {
     /// current trusted `temporary_root` before applying the write.
     #[serde(default)]
     pub expected_root: Option<String>,
+    /// Wait until this write is anchored (committing on demand) before responding.
+    #[serde(default)]
+    pub wait_for_anchor: bool,
 }
 
 #[derive(Deserialize, Debug, ToSchema)]
@@ -80,20 +117,15 @@ pub struct OrderBySpec {
     pub direction: OrderDirection,
 }
 
-#[derive(Deserialize, Serialize, Debug, ToSchema, Clone, Copy)]
+#[derive(Deserialize, Serialize, Debug, ToSchema, Clone, Copy, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum OrderDirection {
     Asc,
     #[serde(other)]
+    #[default]
     Desc,
 }
 
-impl Default for OrderDirection {
-    fn default() -> Self {
-        OrderDirection::Desc
-    }
-}
-
 #[derive(Deserialize, Debug, ToSchema)]
 pub struct UpsertBatchRequest {
     /// Records to upsert. Each record MUST contain the model's primary key field.
@@ -103,6 +135,9 @@ pub struct UpsertBatchRequest {
     /// current trusted `temporary_root` before applying the write.
     #[serde(default)]
     pub expected_root: Option<String>,
+    /// Wait until this write is anchored (committing on demand) before responding.
+    #[serde(default)]
+    pub wait_for_anchor: bool,
 }
 
 #[derive(Deserialize, Serialize, Debug, ToSchema)]
@@ -134,6 +169,24 @@ pub struct RepairRootsRequest {
     pub confirm: bool,
 }
 
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+pub struct RemoveModelRequest {
+    /// Registered model (table) to drop together with its SMT leaves.
+    pub table_name: String,
+    /// Safety switch to prevent accidental drops.
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+pub struct GcLeavesRequest {
+    /// Owning table of the orphaned leaves (must no longer be a registered model).
+    pub table_name: String,
+    /// Safety switch to prevent accidental root changes.
+    #[serde(default)]
+    pub confirm: bool,
+}
+
 #[derive(Deserialize, Serialize, Debug, ToSchema, Clone)]
 pub struct TableSpec {
     pub table_name: String,
@@ -201,11 +254,42 @@ pub struct DbColumnSchema {
     pub default: Option<String>,
 }
 
+/// Query parameters of `GET /api/anchors`.
+#[derive(Deserialize, Debug, Default)]
+pub struct AnchorListQuery {
+    pub limit: Option<i64>,
+    pub before_id: Option<i64>,
+}
+
+/// Query parameters of `GET /api/anchors/costs`.
+#[derive(Deserialize, Debug, Default)]
+pub struct AnchorCostsQuery {
+    /// hour, day, week or month (default: the budget period, else day).
+    pub period: Option<String>,
+    /// Number of periods to list.
+    pub limit: Option<i64>,
+    /// Days of history the forecast is based on.
+    pub window_days: Option<u64>,
+    /// Comma-separated `BATCH_COMMIT_SIZE` values to forecast.
+    pub batch_sizes: Option<String>,
+}
+
+/// Query parameters of `GET /api/anchors/seq/{seq}`.
+#[derive(Deserialize, Debug, Default)]
+pub struct AnchorWaitQuery {
+    pub wait_secs: Option<u64>,
+}
+
 // Internal tables owned by the verifiable service (not "application domain" tables).
 pub const INTERNAL_TABLES: &[&str] = &[
     "merkle_nodes",
+    "merkle_nodes_shadow",
     "verifiable_models",
     "verifiable_registry_meta",
+    "anchor_history",
+    "anchor_covered_roots",
+    "anchor_targets",
+    "anchor_jobs",
     "_sqlx_migrations",
     "schema_migrations",
 ];
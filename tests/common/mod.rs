@@ -0,0 +1,112 @@
+//! Helpers shared by the integration tests (`mod common;` in each test file).
+
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use primitive_types::H256;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use verifiable_memory_example::infra::anchor::{
+    self, AnchorReceipt, ChargedFee, MemoryAnchor, RootAnchor, StaleAnchor,
+};
+
+/// Anchors roots to a local file unless `ANCHOR_BACKEND` is set (no Solana RPC needed), and
+/// initializes the selected anchor.
+pub async fn init_anchor() -> anyhow::Result<Arc<dyn RootAnchor>> {
+    if std::env::var("ANCHOR_BACKEND").is_err() {
+        std::env::set_var("ANCHOR_BACKEND", "file");
+    }
+    let anchor = anchor::from_config()?;
+    anchor.initialize().await?;
+    Ok(anchor)
+}
+
+/// In-process anchor with failure, latency and fee knobs, backed by a `MemoryAnchor`.
+/// Conditional writes are checked atomically with the write, as the Solana program does.
+///
+/// Writes fail while `failing` is set. With `lose_ack` set, the next write is applied and then
+/// reported as failed (as when a confirmation times out after the transaction landed). A root
+/// put in `preempt` is written by "another writer" just before the next write lands. With a
+/// fee set, a conditional write rejected as stale is still charged it, as a Solana transaction
+/// failing on-chain is.
+#[derive(Default)]
+pub struct TestAnchor {
+    inner: MemoryAnchor,
+    pub failing: AtomicBool,
+    pub lose_ack: AtomicBool,
+    pub preempt: Mutex<Option<H256>>,
+    delay: Duration,
+    fee_lamports: Option<u64>,
+}
+
+impl TestAnchor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delays every write by `delay`.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Reports a fee of `fee_lamports` with every write.
+    pub fn with_fee(mut self, fee_lamports: u64) -> Self {
+        self.fee_lamports = Some(fee_lamports);
+        self
+    }
+
+    async fn write(&self, expected_prev: Option<H256>, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        tokio::time::sleep(self.delay).await;
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("RPC unavailable"));
+        }
+        let preempt = self.preempt.lock().unwrap().take();
+        if let Some(foreign) = preempt {
+            self.inner.write_root(foreign).await?;
+        }
+        let receipt = match expected_prev {
+            Some(expected_prev) => match self.inner.write_root_after(expected_prev, new_root).await {
+                Err(e) if e.is::<StaleAnchor>() => {
+                    return Err(match self.fee_lamports {
+                        Some(fee) => ChargedFee::attach(e, fee),
+                        None => e,
+                    })
+                }
+                result => result?,
+            },
+            None => self.inner.write_root(new_root).await?,
+        };
+        if self.lose_ack.swap(false, Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("confirmation timed out"));
+        }
+        Ok(AnchorReceipt {
+            fee_lamports: self.fee_lamports,
+            ..receipt
+        })
+    }
+}
+
+#[async_trait]
+impl RootAnchor for TestAnchor {
+    fn name(&self) -> &str {
+        "test"
+    }
+
+    async fn initialize(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn read_root(&self) -> anyhow::Result<H256> {
+        self.inner.read_root().await
+    }
+
+    async fn write_root(&self, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        self.write(None, new_root).await
+    }
+
+    async fn write_root_after(&self, expected_prev: H256, new_root: H256) -> anyhow::Result<AnchorReceipt> {
+        self.write(Some(expected_prev), new_root).await
+    }
+}
@@ -0,0 +1,181 @@
+//! Anchoring cost test (no Solana needed):
+//! 1) The fee reported by the anchor is recorded with every anchor and counted against the
+//!    budget of the current period.
+//! 2) Once a `refuse` budget is spent, on-demand commits are refused (`POST /api/commit`
+//!    answers 429) while forced commits still go through.
+//! 3) `GET /api/anchors/costs` reports cumulative and per-period spend, average fees and a
+//!    forecast per batch size; an oversized `window_days` is clamped.
+//! 4) A restart seeds the period's spend from the history; a `widen` budget then widens the
+//!    commit policy.
+//! 5) A write rejected as stale is still charged its fee.
+//! 6) An unparsable `ANCHOR_BUDGET_LAMPORTS` fails startup instead of disabling the budget.
+
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::TestAnchor;
+use primitive_types::H256;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use verifiable_memory_example::domain::commitment::budget::{self, BudgetPeriod};
+use verifiable_memory_example::domain::commitment::{BudgetAction, BudgetExceeded};
+use verifiable_memory_example::infra::anchor::StaleAnchor;
+use verifiable_memory_example::storage::anchor_history::AnchorHistory;
+use verifiable_memory_example::{transport, DatabaseService, ModelRegistry, RootManager};
+
+const FEE: u64 = 5_000;
+
+async fn write_and_commit(root_manager: &RootManager, roots: &[H256]) -> anyhow::Result<bool> {
+    for root in roots {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.update_temporary_root(*root).await;
+    }
+    root_manager.commit_now().await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_anchor_costs() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    env::set_var("BATCH_COMMIT_SIZE", "10");
+    env::set_var("COMMIT_MAX_AGE_SECS", "60");
+    env::set_var("ANCHOR_BUDGET_LAMPORTS", "12000");
+    env::set_var("ANCHOR_BUDGET_PERIOD", "day");
+    env::set_var("ANCHOR_BUDGET_ACTION", "refuse");
+    env::set_var("ALLOW_MULTI_INSTANCE", "true");
+    env::set_var("SEALING_KEY_PATH", env::temp_dir().join("vm_test_sealing.key"));
+    env::set_var("CLEAR_DB", "true");
+
+    let db = DatabaseService::new().await?;
+    let pool = db.pool().clone();
+    sqlx::query("TRUNCATE TABLE anchor_history CASCADE").execute(&pool).await?;
+
+    let anchor = Arc::new(TestAnchor::new().with_fee(FEE));
+    let root_manager = Arc::new(RootManager::with_anchor(anchor.clone()).await?);
+    root_manager.attach_history(AnchorHistory::new(pool.clone())).await?;
+    let root = |n: u8| H256::repeat_byte(n);
+
+    // --- Fees are recorded and counted ---
+    assert!(write_and_commit(&root_manager, &[root(1), root(2)]).await?);
+    assert!(write_and_commit(&root_manager, &[root(3), root(4)]).await?);
+    let history = root_manager.history().expect("history attached");
+    let anchors = history.list(10, None).await?;
+    assert_eq!(anchors.len(), 2);
+    assert!(anchors.iter().all(|a| a.fee_lamports == Some(FEE as i64)));
+    let status = root_manager.commit_status().await.budget.expect("budget configured");
+    assert_eq!((status.spent_lamports, status.remaining_lamports), (2 * FEE, 2_000));
+    assert!(!status.exceeded);
+
+    // --- The third anchor spends the budget; further on-demand commits are refused ---
+    assert!(write_and_commit(&root_manager, &[root(5)]).await?);
+    assert!(root_manager.budget_status().unwrap().exceeded);
+    let refused = write_and_commit(&root_manager, &[root(6)]).await.unwrap_err();
+    let refused = refused.downcast_ref::<BudgetExceeded>().expect("budget refusal");
+    assert_eq!(refused.spent_lamports, 3 * FEE);
+    assert_eq!(root_manager.get_main_root().await, root(5), "nothing was anchored");
+    assert_eq!(root_manager.commit_status().await.pending_updates, 1);
+
+    // Forced commits keep the anchor in step with the database regardless.
+    {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.force_set_roots_and_commit(root(7)).await?;
+    }
+    assert_eq!(root_manager.get_main_root().await, root(7));
+    assert_eq!(root_manager.budget_status().unwrap().spent_lamports, 4 * FEE);
+
+    // --- Over HTTP ---
+    let app_state = transport::http::AppState::new(
+        Arc::new(Mutex::new(db)),
+        Arc::new(RwLock::new(ModelRegistry::new())),
+        root_manager.clone(),
+    );
+    let router = transport::http::create_router(app_state);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let base_url = format!("http://127.0.0.1:{}", listener.local_addr()?.port());
+    let server = tokio::spawn(async move { axum::serve(listener, router).await });
+    let client = reqwest::Client::new();
+
+    let commit = client.post(format!("{}/api/commit", base_url)).send().await?;
+    assert_eq!(commit.status(), reqwest::StatusCode::TOO_MANY_REQUESTS);
+
+    let costs: serde_json::Value = client
+        .get(format!("{}/api/anchors/costs?batch_sizes=1,10,10", base_url))
+        .send()
+        .await?
+        .json()
+        .await?;
+    let data = &costs["data"];
+    assert_eq!(data["total"]["anchors"], 4);
+    assert_eq!(data["total"]["updates"], 6, "the forced commit covered the refused update");
+    assert_eq!(data["total"]["fee_lamports"], 4 * FEE);
+    assert_eq!(data["period"], "day");
+    assert_eq!(data["periods"][0]["fee_lamports"], 4 * FEE);
+    assert_eq!(data["average_fee_lamports_per_anchor"], FEE as f64);
+    assert_eq!(data["average_fee_lamports_per_update"], (4 * FEE) as f64 / 6.0);
+    let forecast = data["forecast"]["batch_sizes"].as_array().expect("forecast");
+    assert_eq!(forecast.len(), 2, "batch sizes are deduplicated");
+    assert_eq!(forecast[0]["batch_size"], 1);
+    assert_eq!(data["budget"]["spent_lamports"], 4 * FEE);
+    assert_eq!(data["budget"]["action"], "refuse");
+
+    let huge: serde_json::Value = client
+        .get(format!("{}/api/anchors/costs?window_days={}", base_url, u64::MAX))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(huge["data"]["window"]["days"], 3650, "window_days is clamped");
+
+    let bad = client
+        .get(format!("{}/api/anchors/costs?period=fortnight", base_url))
+        .send()
+        .await?;
+    assert_eq!(bad.status(), reqwest::StatusCode::BAD_REQUEST);
+    server.abort();
+
+    // --- Forecast model ---
+    // 0.01 writes/s with a 300 s max age: batches of 10 close on age with 3 updates each.
+    let f = budget::forecast(FEE as f64, 0.01, 10, Some(Duration::from_secs(300)));
+    assert_eq!(f.updates_per_anchor, 3.0);
+    assert!((f.anchors_per_day - 288.0).abs() < 1e-9);
+    assert!((f.lamports_per_day - 288.0 * FEE as f64).abs() < 1e-6);
+    let f = budget::forecast(FEE as f64, 0.01, 1, Some(Duration::from_secs(300)));
+    assert!((f.anchors_per_day - 864.0).abs() < 1e-9);
+    assert_eq!(
+        BudgetPeriod::Week.start_of(Utc.with_ymd_and_hms(2026, 10, 18, 13, 5, 0).unwrap()),
+        Utc.with_ymd_and_hms(2026, 10, 12, 0, 0, 0).unwrap()
+    );
+
+    // --- Restart with a widening budget: the period's spend comes from the history ---
+    root_manager.shutdown();
+    drop(root_manager);
+    env::set_var("ANCHOR_BUDGET_ACTION", "widen");
+    env::set_var("ANCHOR_BUDGET_WIDEN_FACTOR", "3");
+    let root_manager = RootManager::with_anchor(anchor.clone()).await?;
+    root_manager.attach_history(AnchorHistory::new(pool.clone())).await?;
+    let status = root_manager.budget_status().expect("budget configured");
+    assert_eq!(status.policy.action, BudgetAction::Widen);
+    assert_eq!(status.spent_lamports, 4 * FEE);
+    assert!(status.exceeded);
+    let widened = root_manager.effective_policy();
+    assert_eq!(widened.batch_size, 30);
+    assert_eq!(widened.max_age, Some(Duration::from_secs(180)));
+    assert!(write_and_commit(&root_manager, &[root(8)]).await?, "widen still anchors on demand");
+    assert_eq!(root_manager.budget_status().unwrap().spent_lamports, 5 * FEE);
+
+    // --- A stale write landed and failed; its fee still counts ---
+    *anchor.preempt.lock().unwrap() = Some(root(9));
+    let stale = write_and_commit(&root_manager, &[root(10)]).await.unwrap_err();
+    assert!(stale.is::<StaleAnchor>(), "{}", stale);
+    assert_eq!(root_manager.budget_status().unwrap().spent_lamports, 6 * FEE);
+
+    // --- An unparsable budget fails startup ---
+    root_manager.shutdown();
+    drop(root_manager);
+    env::set_var("ANCHOR_BUDGET_LAMPORTS", "5 SOL");
+    let refused = RootManager::with_anchor(anchor.clone()).await.err().expect("startup refused");
+    assert!(refused.to_string().contains("ANCHOR_BUDGET_LAMPORTS"), "{}", refused);
+
+    Ok(())
+}
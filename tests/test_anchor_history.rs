@@ -0,0 +1,80 @@
+//! Anchor history test (no Solana needed):
+//! 1) Attach the history table to a RootManager backed by an in-process anchor.
+//! 2) Anchor two batches and ensure each is recorded with its root and update sequence range.
+//! 3) Ensure an intermediate root resolves to the first anchor that covered it.
+
+use primitive_types::H256;
+use std::env;
+use std::sync::Arc;
+use verifiable_memory_example::infra::anchor::MemoryAnchor;
+use verifiable_memory_example::storage::anchor_history::AnchorHistory;
+use verifiable_memory_example::{DatabaseService, RootManager};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_anchor_history() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    env::set_var("BATCH_COMMIT_SIZE", "1000");
+    env::set_var("COMMIT_MAX_AGE_SECS", "0");
+    env::set_var("CLEAR_DB", "true");
+    env::set_var("ALLOW_MULTI_INSTANCE", "true");
+
+    let db = DatabaseService::new().await?;
+    let pool = db.pool().clone();
+    sqlx::query("TRUNCATE TABLE anchor_history CASCADE").execute(&pool).await?;
+
+    let anchor = Arc::new(MemoryAnchor::new());
+    let root_manager = Arc::new(RootManager::with_anchor(anchor.clone()).await?);
+    root_manager.attach_history(AnchorHistory::new(pool.clone())).await?;
+    let history = root_manager.history().expect("history attached");
+
+    // --- First batch: three updates, anchored on demand ---
+    let batch1: Vec<H256> = (1u8..=3).map(H256::repeat_byte).collect();
+    let mut seqs = Vec::new();
+    for root in &batch1 {
+        let _root_guard = root_manager.lock_root().await;
+        seqs.push(root_manager.update_temporary_root(*root).await.seq);
+    }
+    assert!(root_manager.commit_now().await?);
+
+    // --- Second batch: one more update ---
+    let batch2 = H256::repeat_byte(0x44);
+    {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.update_temporary_root(batch2).await;
+    }
+    assert!(root_manager.commit_now().await?);
+
+    let anchors = history.list(10, None).await?;
+    assert_eq!(anchors.len(), 2);
+    let (second, first) = (&anchors[0], &anchors[1]);
+    assert!(second.id > first.id, "newest first");
+
+    assert_eq!(first.root, hex::encode(batch1[2].as_bytes()));
+    assert_eq!(first.first_seq as u64, seqs[0]);
+    assert_eq!(first.last_seq as u64, seqs[2]);
+    assert_eq!(first.update_count, 3);
+    assert_eq!(first.reason, "on_demand");
+    assert_eq!(first.backend, "memory");
+
+    assert_eq!(second.root, hex::encode(batch2.as_bytes()));
+    assert_eq!(second.first_seq, first.last_seq + 1);
+    assert_eq!(second.update_count, 1);
+
+    // Pagination.
+    let older = history.list(10, Some(second.id)).await?;
+    assert_eq!(older.len(), 1);
+    assert_eq!(older[0].id, first.id);
+
+    // An intermediate root of the first batch maps to the first anchor.
+    let covering = history.first_covering(batch1[0]).await?.expect("covered");
+    assert_eq!(covering.id, first.id);
+    let covering = history.first_covering(batch2).await?.expect("covered");
+    assert_eq!(covering.id, second.id);
+    assert!(history.first_covering(H256::repeat_byte(0x99)).await?.is_none());
+
+    // Used to resume sequence numbering after a restart.
+    assert_eq!(history.max_seq().await?, second.last_seq as u64);
+
+    root_manager.shutdown();
+    Ok(())
+}
@@ -0,0 +1,145 @@
+//! Anchor indexer test (no Solana needed):
+//! 1) `RootUpdated` events decode from the program's layout (discriminator =
+//!    sha256("event:RootUpdated")[..8]) and are only taken from the program's own log frames.
+//! 2) The timeline of one namespace is rebuilt in sequence order, skipping failed transactions
+//!    and other namespaces; gaps, breaks and a stale head are reported.
+//! 3) `anchor_indexer --fixture` writes the timeline to JSON and fails on an incomplete one.
+
+use primitive_types::H256;
+use sha2::{Digest, Sha256};
+use solana_program::pubkey::Pubkey;
+use std::env;
+use std::process::Command;
+use verifiable_memory_example::infra::solana::events::{self, RootUpdated, ROOT_UPDATED_DISCRIMINATOR};
+use verifiable_memory_example::infra::solana::indexer::{AnchorTimeline, IndexedTransaction};
+use verifiable_memory_example::infra::solana::MerkleRootAccount;
+
+fn root(n: u8) -> H256 {
+    H256::repeat_byte(n)
+}
+
+fn update(namespace: &str, sequence: u64) -> RootUpdated {
+    RootUpdated {
+        namespace: namespace.to_string(),
+        sequence,
+        old_root: root(sequence as u8 - 1),
+        new_root: root(sequence as u8),
+        timestamp: 1_700_000_000 + sequence as i64,
+    }
+}
+
+/// Logs of a transaction in which `program` emits `event`.
+fn logs(program: &Pubkey, event: &RootUpdated) -> Vec<String> {
+    vec![
+        "Program ComputeBudget111111111111111111111111111111 invoke [1]".to_string(),
+        "Program ComputeBudget111111111111111111111111111111 success".to_string(),
+        format!("Program {} invoke [1]", program),
+        "Program log: Instruction: UpdateRoot".to_string(),
+        event.to_log_line(),
+        format!("Program {} consumed 9000 of 200000 compute units", program),
+        format!("Program {} success", program),
+    ]
+}
+
+fn tx(slot: u64, logs: Vec<String>) -> IndexedTransaction {
+    IndexedTransaction {
+        signature: format!("sig{}", slot),
+        slot,
+        block_time: Some(1_700_000_000 + slot as i64),
+        failed: false,
+        logs,
+    }
+}
+
+#[test]
+fn test_anchor_indexer() -> Result<(), Box<dyn std::error::Error>> {
+    let program_id = Pubkey::new_unique();
+    let impostor = Pubkey::new_unique();
+
+    // --- Event layout ---
+    assert_eq!(ROOT_UPDATED_DISCRIMINATOR[..], Sha256::digest(b"event:RootUpdated")[..8]);
+    let event = update("fleet-a", 1);
+    assert_eq!(RootUpdated::decode(&event.encode())?, event);
+    assert!(RootUpdated::decode(&event.encode()[..40]).is_err());
+    assert_eq!(events::root_updates(&logs(&program_id, &event), &program_id), vec![event.clone()]);
+    // The same bytes logged by another program, also when it is called by ours, do not count.
+    assert!(events::root_updates(&logs(&impostor, &event), &program_id).is_empty());
+    let cpi = vec![
+        format!("Program {} invoke [1]", program_id),
+        format!("Program {} invoke [2]", impostor),
+        event.to_log_line(),
+        format!("Program {} success", impostor),
+        format!("Program {} success", program_id),
+    ];
+    assert!(events::root_updates(&cpi, &program_id).is_empty());
+
+    // --- Timeline ---
+    let mut failed = tx(25, logs(&program_id, &update("fleet-a", 3)));
+    failed.failed = true;
+    let transactions = vec![
+        tx(30, logs(&program_id, &update("fleet-a", 3))),
+        tx(10, logs(&program_id, &update("fleet-a", 1))),
+        tx(15, logs(&program_id, &update("fleet-b", 1))),
+        failed,
+        tx(20, logs(&program_id, &update("fleet-a", 2))),
+        tx(22, logs(&impostor, &update("fleet-a", 9))),
+    ];
+    let mut timeline = AnchorTimeline::build(&program_id, "fleet-a", &transactions);
+    assert!(timeline.is_complete(), "{:?}", timeline.issues);
+    assert_eq!(
+        timeline.entries.iter().map(|e| (e.sequence, e.slot)).collect::<Vec<_>>(),
+        vec![(1, 10), (2, 20), (3, 30)]
+    );
+    assert_eq!(timeline.entries[2].signature, "sig30");
+    assert_eq!(timeline.entries[2].timestamp, 1_700_000_003);
+    assert_eq!(timeline.root_at(2), Some(root(2)));
+
+    let account = |sequence: u64, root: H256| MerkleRootAccount {
+        root: root.to_fixed_bytes(),
+        timestamp: 0,
+        sequence,
+        authority: Pubkey::new_unique(),
+        pending_authority: None,
+    };
+    timeline.check_head(&account(3, root(3)));
+    assert!(timeline.is_complete());
+    timeline.check_head(&account(4, root(4)));
+    assert_eq!(timeline.issues.len(), 1, "head moved past the timeline");
+
+    // A missing update, and one that does not build on the previous root.
+    let gap = AnchorTimeline::build(&program_id, "fleet-a", &[transactions[1].clone(), transactions[0].clone()]);
+    assert_eq!(gap.issues, vec!["Updates 2..=2 are missing".to_string()]);
+    let mut forged = update("fleet-a", 2);
+    forged.old_root = root(0xee);
+    let broken = AnchorTimeline::build(
+        &program_id,
+        "fleet-a",
+        &[transactions[1].clone(), tx(20, logs(&program_id, &forged))],
+    );
+    assert_eq!(broken.issues.len(), 1);
+    assert!(broken.issues[0].starts_with("Update 2 replaced"), "{:?}", broken.issues);
+
+    // --- The binary, from a fixture file ---
+    let dir = env::temp_dir().join(format!("vm-indexer-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let fixture = dir.join("transactions.json");
+    let out = dir.join("timeline.json");
+    std::fs::write(&fixture, serde_json::to_string(&transactions)?)?;
+    let run = |fixture: &std::path::Path| {
+        Command::new(env!("CARGO_BIN_EXE_anchor_indexer"))
+            .args(["--fixture", fixture.to_str().unwrap(), "--namespace", "fleet-a"])
+            .args(["--out", out.to_str().unwrap()])
+            .env("SOLANA_PROGRAM_ID", program_id.to_string())
+            .status()
+    };
+    assert!(run(&fixture)?.success());
+    let written: AnchorTimeline = serde_json::from_str(&std::fs::read_to_string(&out)?)?;
+    assert_eq!(written.entries, AnchorTimeline::build(&program_id, "fleet-a", &transactions).entries);
+    assert_eq!(written.namespace, "fleet-a");
+
+    std::fs::write(&fixture, serde_json::to_string(&[&transactions[0], &transactions[1]])?)?;
+    assert_eq!(run(&fixture)?.code(), Some(1), "a gap fails the run");
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,110 @@
+//! Anchor retry queue test (no Solana needed):
+//! 1) Commits against a failing anchor are counted, queued in `anchor_jobs`, and open the
+//!    circuit breaker after `ANCHOR_BREAKER_THRESHOLD` consecutive failures.
+//! 2) A restart restores the queued job (attempts, covered roots) from the database.
+//! 3) Once the anchor recovers, the background task retries, the queue empties, anchoring is
+//!    healthy again, and the history covers the roots of the failed attempts.
+
+mod common;
+
+use common::TestAnchor;
+use primitive_types::H256;
+use std::env;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use verifiable_memory_example::domain::commitment::AnchorState;
+use verifiable_memory_example::infra::anchor::RootAnchor;
+use verifiable_memory_example::storage::anchor_history::AnchorHistory;
+use verifiable_memory_example::storage::anchor_jobs::AnchorJobs;
+use verifiable_memory_example::{DatabaseService, RootManager};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_anchor_retry() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    env::set_var("BATCH_COMMIT_SIZE", "1000");
+    env::set_var("COMMIT_MAX_AGE_SECS", "0");
+    env::set_var("ANCHOR_RETRY_BASE_MS", "100");
+    env::set_var("ANCHOR_RETRY_MAX_MS", "400");
+    env::set_var("ANCHOR_BREAKER_THRESHOLD", "3");
+    env::set_var("ALLOW_MULTI_INSTANCE", "true");
+    env::set_var("SEALING_KEY_PATH", env::temp_dir().join("vm_test_sealing.key"));
+    env::set_var("CLEAR_DB", "true");
+
+    let db = DatabaseService::new().await?;
+    let pool = db.pool().clone();
+    sqlx::query("TRUNCATE TABLE anchor_history CASCADE").execute(&pool).await?;
+    sqlx::query("TRUNCATE TABLE anchor_jobs").execute(&pool).await?;
+
+    let anchor = Arc::new(TestAnchor::new());
+    anchor.failing.store(true, Ordering::SeqCst);
+
+    let root_manager = RootManager::with_anchor(anchor.clone()).await?;
+    root_manager.attach_history(AnchorHistory::new(pool.clone())).await?;
+    root_manager.attach_jobs(AnchorJobs::new(pool.clone())).await?;
+
+    // --- Failing anchor: three attempts open the breaker ---
+    let (r1, r2) = (H256::repeat_byte(0x11), H256::repeat_byte(0x12));
+    for root in [r1, r2] {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.update_temporary_root(root).await;
+    }
+    assert!(root_manager.commit_now().await.is_err());
+    let health = root_manager.anchor_health().await;
+    assert_eq!(health.state, AnchorState::Retrying);
+    assert_eq!(health.consecutive_failures, 1);
+    assert_eq!(health.pending_updates, 2);
+    assert!(health.last_error.as_deref().unwrap_or("").contains("RPC unavailable"));
+
+    assert!(root_manager.commit_now().await.is_err());
+    assert!(root_manager.commit_now().await.is_err());
+    let health = root_manager.anchor_health().await;
+    assert_eq!(health.state, AnchorState::Degraded);
+    assert_eq!(health.consecutive_failures, 3);
+
+    let jobs = root_manager.jobs().expect("jobs attached").pending().await?;
+    assert_eq!(jobs.len(), 1, "one job per unanchored range");
+    assert_eq!(jobs[0].attempts, 3);
+    assert_eq!(jobs[0].root, r2);
+    assert_eq!(jobs[0].covered_roots.len(), 2);
+    root_manager.shutdown();
+    drop(root_manager);
+
+    // --- Restart: the queued job is restored ---
+    env::set_var("CLEAR_DB", "false");
+    let root_manager = Arc::new(RootManager::with_anchor(anchor.clone()).await?);
+    root_manager.attach_history(AnchorHistory::new(pool.clone())).await?;
+    root_manager.attach_jobs(AnchorJobs::new(pool.clone())).await?;
+    let health = root_manager.anchor_health().await;
+    assert_eq!(health.state, AnchorState::Degraded);
+    assert_eq!(health.consecutive_failures, 3);
+    assert_eq!(health.pending_updates, 2);
+    assert_eq!(root_manager.get_temporary_root().await, r2);
+
+    // --- Recovery: the background task retries once the backoff has elapsed ---
+    anchor.failing.store(false, Ordering::SeqCst);
+    root_manager.clone().start_background_commit_task();
+    let seq = root_manager.update_seq().await;
+    assert!(
+        root_manager
+            .wait_for_anchor_timeout(seq, Duration::from_secs(10))
+            .await,
+        "retry did not anchor the queued updates"
+    );
+    assert_eq!(anchor.read_root().await?, r2);
+
+    let health = root_manager.anchor_health().await;
+    assert_eq!(health.state, AnchorState::Healthy);
+    assert_eq!(health.consecutive_failures, 0);
+    assert_eq!(health.pending_updates, 0);
+    assert!(root_manager.jobs().unwrap().pending().await?.is_empty());
+
+    let history = root_manager.history().unwrap();
+    let latest = &history.list(1, None).await?[0];
+    assert_eq!(latest.reason, "retry");
+    let covering = history.first_covering(r1).await?.expect("r1 covered after restart");
+    assert_eq!(covering.id, latest.id);
+
+    root_manager.shutdown();
+    Ok(())
+}
@@ -0,0 +1,112 @@
+//! Apply-schema reset test (no Solana needed):
+//! 1) A schema change whose conditional anchor reset finds the anchor moved by another writer
+//!    answers 409 and leaves the tables, the tree and the roots untouched.
+//! 2) The same request against an anchor that cannot be written answers 502, again touching
+//!    nothing.
+//! 3) `force_reset` overwrites the moved anchor and applies the schema.
+
+mod common;
+
+use common::TestAnchor;
+use primitive_types::H256;
+use serde_json::json;
+use std::env;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use verifiable_memory_example::infra::anchor::RootAnchor;
+use verifiable_memory_example::{transport, DatabaseService, ModelRegistry, RootManager};
+
+fn schema(force_reset: bool, columns: &[&str]) -> serde_json::Value {
+    let columns: Vec<_> = columns
+        .iter()
+        .map(|c| json!({"name": c, "col_type": "text", "nullable": true, "unique": false}))
+        .collect();
+    json!({
+        "force_reset": force_reset,
+        "tables": [{
+            "table_name": "notes",
+            "primary_key_field": "id",
+            "primary_key_kind": "big_serial",
+            "columns": columns
+        }]
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_apply_schema_reset() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    env::set_var("BATCH_COMMIT_SIZE", "1");
+    env::set_var("ALLOW_MULTI_INSTANCE", "true");
+    env::set_var("SEALING_KEY_PATH", env::temp_dir().join("vm_test_sealing.key"));
+    env::set_var("CLEAR_DB", "true");
+
+    let anchor = Arc::new(TestAnchor::new());
+    let root_manager = Arc::new(RootManager::with_anchor(anchor.clone()).await?);
+    root_manager.clone().start_background_commit_task();
+    let db_service = DatabaseService::new().await?;
+    let pool = db_service.pool().clone();
+    let app_state = transport::http::AppState::new(
+        Arc::new(Mutex::new(db_service)),
+        Arc::new(RwLock::new(ModelRegistry::new())),
+        root_manager.clone(),
+    );
+    let router = transport::http::create_router(app_state);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let base_url = format!("http://127.0.0.1:{}", listener.local_addr()?.port());
+    let server = tokio::spawn(async move { axum::serve(listener, router).await });
+    let client = reqwest::Client::new();
+    let apply = |body: serde_json::Value| {
+        client
+            .post(format!("{}/bootstrap/apply-schema", base_url))
+            .json(&body)
+            .send()
+    };
+
+    let applied = apply(schema(true, &["body"])).await?;
+    assert_eq!(applied.status(), reqwest::StatusCode::OK);
+    let create = client
+        .post(format!("{}/api/models/notes/create-batch", base_url))
+        .json(&json!({ "records": [ {"body":"kept"} ] }))
+        .send()
+        .await?;
+    assert_eq!(create.status(), reqwest::StatusCode::OK);
+    let main_root = root_manager.get_main_root().await;
+    assert_ne!(main_root, H256::zero());
+
+    let intact = || async {
+        let notes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM notes").fetch_one(&pool).await?;
+        let leaves: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM merkle_nodes").fetch_one(&pool).await?;
+        Ok::<_, sqlx::Error>(notes == 1 && leaves > 0)
+    };
+
+    // --- Another writer moved the anchor: 409, nothing reset ---
+    let foreign = H256::repeat_byte(0xf0);
+    anchor.write_root(foreign).await?;
+    let refused = apply(schema(false, &["body", "title"])).await?;
+    assert_eq!(refused.status(), reqwest::StatusCode::CONFLICT);
+    assert!(intact().await?, "tables or tree were reset");
+    assert_eq!(root_manager.get_main_root().await, main_root);
+    assert_eq!(anchor.read_root().await?, foreign);
+
+    // --- The anchor cannot be written: 502, nothing reset ---
+    anchor.write_root(main_root).await?;
+    anchor.failing.store(true, Ordering::SeqCst);
+    let refused = apply(schema(false, &["body", "title"])).await?;
+    assert_eq!(refused.status(), reqwest::StatusCode::BAD_GATEWAY);
+    assert!(intact().await?, "tables or tree were reset");
+    assert_eq!(root_manager.get_main_root().await, main_root);
+    anchor.failing.store(false, Ordering::SeqCst);
+
+    // --- force_reset overwrites a moved anchor ---
+    anchor.write_root(foreign).await?;
+    let applied = apply(schema(true, &["body", "title"])).await?;
+    assert_eq!(applied.status(), reqwest::StatusCode::OK);
+    assert_eq!(anchor.read_root().await?, H256::zero());
+    let notes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM notes").fetch_one(&pool).await?;
+    assert_eq!(notes, 0);
+
+    root_manager.shutdown();
+    server.abort();
+    Ok(())
+}
@@ -0,0 +1,72 @@
+//! Chain watch test (no Solana, no DB needed):
+//! 1) Roots this service anchored, including one that landed although its commit had not
+//!    reported back yet, raise no alarm.
+//! 2) A root written by someone else turns the service read-only with a
+//!    `foreign_anchor_write` report, raised once per foreign root.
+//! 3) A reset that re-anchors re-enables writes and quiets the watch.
+
+use primitive_types::H256;
+use std::env;
+use std::sync::Arc;
+use verifiable_memory_example::app::chain_watch::check_anchor;
+use verifiable_memory_example::app::reconcile::{ReconcileAction, ReconcileTracker, RootSituation};
+use verifiable_memory_example::infra::anchor::{MemoryAnchor, RootAnchor};
+use verifiable_memory_example::RootManager;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_chain_watch() -> Result<(), Box<dyn std::error::Error>> {
+    env::set_var("BATCH_COMMIT_SIZE", "1000");
+    env::set_var("COMMIT_MAX_AGE_SECS", "0");
+    env::set_var("CLEAR_DB", "true");
+
+    let anchor = Arc::new(MemoryAnchor::new());
+    let root_manager = RootManager::with_anchor(anchor.clone()).await?;
+    let tracker = ReconcileTracker::new();
+    let (r1, r2) = (H256::repeat_byte(0x01), H256::repeat_byte(0x02));
+
+    // --- Our own anchors ---
+    assert_eq!(check_anchor(&root_manager, &tracker).await?, None);
+    {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.update_temporary_root(r1).await;
+    }
+    assert!(root_manager.commit_now().await?);
+    assert_eq!(check_anchor(&root_manager, &tracker).await?, None);
+
+    // A produced root that reached the anchor before main_root caught up is still ours.
+    {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.update_temporary_root(r2).await;
+    }
+    anchor.write_root(r2).await?;
+    assert_eq!(check_anchor(&root_manager, &tracker).await?, None);
+    root_manager.commit_now().await?;
+    assert_eq!(root_manager.get_main_root().await, r2);
+    assert!(tracker.writes_allowed());
+
+    // --- Someone else moves the anchor ---
+    let foreign = H256::repeat_byte(0xee);
+    anchor.write_root(foreign).await?;
+    assert_eq!(check_anchor(&root_manager, &tracker).await?, Some(foreign));
+    assert!(!tracker.writes_allowed());
+    let report = tracker.status().expect("alarm recorded");
+    assert_eq!(report.situation, RootSituation::ForeignAnchorWrite);
+    assert_eq!(report.action, ReconcileAction::ReadOnly);
+    assert_eq!(report.anchored_root.as_deref(), Some(hex::encode(foreign.as_bytes()).as_str()));
+    assert_eq!(report.main_root, hex::encode(r2.as_bytes()));
+
+    // The same root is not reported again.
+    assert_eq!(check_anchor(&root_manager, &tracker).await?, Some(foreign));
+    assert_eq!(tracker.status().unwrap().reconciled_at, report.reconciled_at);
+
+    // --- An operator reset re-anchors and re-enables writes ---
+    {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.force_anchor_and_reset_roots(H256::zero()).await?;
+    }
+    tracker.resolve("clear-data");
+    assert!(tracker.writes_allowed());
+    assert_eq!(check_anchor(&root_manager, &tracker).await?, None);
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+//! SMT checkpoint test (no Solana needed):
+//! 1) Write rows, checkpoint the tree, then keep writing (inserts + an upsert).
+//! 2) Ensure `DatabaseService::new_fast_start` restores the checkpoint, replays only the newer
+//!    leaves and reproduces the live root.
+//! 3) Ensure a tampered or stale checkpoint, or a wrong trusted root, falls back to a full rebuild.
+
+use primitive_types::H256;
+use serde_json::json;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use verifiable_memory_example::domain::model::DynamicModel;
+use verifiable_memory_example::storage::smt::{checkpoint_key, write_checkpoint};
+use verifiable_memory_example::{DatabaseService, VerifiableModel};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_checkpoint_fast_start() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+
+    let dir = env::temp_dir();
+    let ckpt_path = dir.join("vm_test_smt_checkpoint.bin");
+    let _ = std::fs::remove_file(&ckpt_path);
+    env::set_var("SMT_CHECKPOINT_PATH", &ckpt_path);
+    env::set_var("SEALING_KEY_PATH", dir.join("vm_test_sealing.key"));
+    // Several services are opened against the same DB below.
+    env::set_var("ALLOW_MULTI_INSTANCE", "true");
+
+    let mut db = DatabaseService::new().await?;
+    let pool = db.pool().clone();
+    sqlx::query("DROP TABLE IF EXISTS ckpt_notes").execute(&pool).await?;
+    sqlx::query("CREATE TABLE ckpt_notes (id BIGSERIAL PRIMARY KEY, body TEXT NOT NULL)")
+        .execute(&pool)
+        .await?;
+    sqlx::query("TRUNCATE TABLE merkle_nodes").execute(&pool).await?;
+    db.reset_smt_store().await?;
+
+    let mut column_types = HashMap::new();
+    column_types.insert("body".to_string(), "text".to_string());
+    let model: Arc<dyn VerifiableModel> = Arc::new(DynamicModel::new(
+        "ckpt_notes".to_string(),
+        "id".to_string(),
+        String::new(),
+        column_types,
+    ));
+
+    let records: Vec<_> = (0..50).map(|i| json!({ "body": format!("note {}", i) })).collect();
+    let root = db.current_smt_root().await?;
+    let (_, _, _, ids) = db.create_records(model.clone(), &records, root).await?;
+
+    let checkpoint = db.checkpoint_snapshot().await?;
+    assert_eq!(checkpoint.leaf_count(), 50);
+    let key = checkpoint_key()?;
+    write_checkpoint(&ckpt_path, &key, &checkpoint)?;
+
+    // Writes after the checkpoint must be replayed on startup.
+    let root = db.current_smt_root().await?;
+    db.create_records(model.clone(), &records[..5], root).await?;
+    let root = db.current_smt_root().await?;
+    db.upsert_records(
+        model.clone(),
+        &[json!({ "id": ids[0].parse::<i64>()?, "body": "changed" })],
+        root,
+    )
+    .await?;
+    let live_root = db.current_smt_root().await?;
+
+    let restored = DatabaseService::new_fast_start(live_root).await?;
+    assert_eq!(restored.current_smt_root().await?, live_root);
+
+    // A checkpoint that does not reproduce the trusted root is ignored (full rebuild instead).
+    let fallback = DatabaseService::new_fast_start(H256::repeat_byte(7)).await?;
+    assert_eq!(fallback.current_smt_root().await?, live_root);
+
+    // Tampered checkpoint: MAC fails, full rebuild still yields the live root.
+    let mut bytes = std::fs::read(&ckpt_path)?;
+    let mid = bytes.len() / 2;
+    bytes[mid] ^= 1;
+    std::fs::write(&ckpt_path, &bytes)?;
+    let tampered = DatabaseService::new_fast_start(live_root).await?;
+    assert_eq!(tampered.current_smt_root().await?, live_root);
+
+    // Stale checkpoint: a checkpointed leaf vanished from merkle_nodes.
+    write_checkpoint(&ckpt_path, &key, &checkpoint)?;
+    sqlx::query("DELETE FROM merkle_nodes WHERE ctid IN (SELECT ctid FROM merkle_nodes LIMIT 1)")
+        .execute(&pool)
+        .await?;
+    let stale = DatabaseService::new_fast_start(live_root).await?;
+    assert_ne!(stale.current_smt_root().await?, live_root);
+
+    sqlx::query("DROP TABLE ckpt_notes").execute(&pool).await?;
+    sqlx::query("TRUNCATE TABLE merkle_nodes").execute(&pool).await?;
+    let _ = std::fs::remove_file(&ckpt_path);
+
+    Ok(())
+}
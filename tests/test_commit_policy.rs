@@ -0,0 +1,70 @@
+//! Commit policy test (no Solana, no DB needed):
+//! 1) With a large batch size, a single write is still anchored once it reaches max-age.
+//! 2) `commit_now` anchors on demand and reports when there was nothing to anchor.
+//! 3) `commit_status` reports pending updates and the time since the last anchor.
+
+use primitive_types::H256;
+use std::env;
+use std::sync::Arc;
+use verifiable_memory_example::domain::commitment::CommitReason;
+use verifiable_memory_example::infra::anchor::{MemoryAnchor, RootAnchor};
+use verifiable_memory_example::RootManager;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_commit_policy() -> Result<(), Box<dyn std::error::Error>> {
+    env::set_var("BATCH_COMMIT_SIZE", "1000");
+    env::set_var("COMMIT_MAX_AGE_SECS", "1");
+    env::set_var("CLEAR_DB", "true");
+
+    let anchor = Arc::new(MemoryAnchor::new());
+    let root_manager = Arc::new(RootManager::with_anchor(anchor.clone()).await?);
+    root_manager.clone().start_background_commit_task();
+
+    let status = root_manager.commit_status().await;
+    assert_eq!(status.pending_updates, 0);
+    assert!(status.oldest_pending_age_secs.is_none());
+    assert!(status.seconds_since_last_anchor.is_none());
+
+    // --- max-age: one write, far below the batch size ---
+    let first = H256::repeat_byte(0x11);
+    {
+        let _root_guard = root_manager.lock_root().await;
+        assert!(!root_manager.update_temporary_root(first).await.commit_due);
+    }
+    let status = root_manager.commit_status().await;
+    assert_eq!(status.pending_updates, 1);
+    assert!(status.oldest_pending_age_secs.is_some());
+
+    for _ in 0..40 {
+        if anchor.read_root().await? == first {
+            break;
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(anchor.read_root().await?, first, "max-age should anchor a lone write");
+
+    let status = root_manager.commit_status().await;
+    assert_eq!(status.pending_updates, 0);
+    assert!(status.oldest_pending_age_secs.is_none());
+    assert_eq!(status.last_anchor_reason, Some(CommitReason::MaxAge));
+    assert!(status.seconds_since_last_anchor.unwrap() < 5.0);
+
+    // --- on demand ---
+    assert!(!root_manager.commit_now().await?, "nothing pending");
+
+    let second = H256::repeat_byte(0x22);
+    {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.update_temporary_root(second).await;
+    }
+    assert!(root_manager.commit_now().await?);
+    assert_eq!(anchor.read_root().await?, second);
+    assert_eq!(root_manager.get_main_root().await, second);
+
+    let status = root_manager.commit_status().await;
+    assert_eq!(status.last_anchor_reason, Some(CommitReason::OnDemand));
+    assert_eq!(status.last_anchor_root, Some(hex::encode(second.as_bytes())));
+
+    root_manager.shutdown();
+    Ok(())
+}
@@ -0,0 +1,154 @@
+//! Drift scan test:
+//! 1) Bootstrap a schema and write a few verified rows.
+//! 2) Tamper with Postgres directly (UPDATE / INSERT / DELETE behind the service's back).
+//! 3) Ensure `GET /bootstrap/drift` reports the exact rows (including the pk of the deleted
+//!    row), the stored leaves still hash to the trusted root, and no root changed.
+
+mod common;
+
+use serde_json::json;
+use std::env;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use verifiable_memory_example::{transport, DatabaseService, ModelRegistry, RootManager};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_drift_scan() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+
+    if env::var("BATCH_COMMIT_SIZE").is_err() {
+        env::set_var("BATCH_COMMIT_SIZE", "3");
+    }
+
+    common::init_anchor().await?;
+
+    env::set_var("CLEAR_DB", "true");
+    let root_manager = Arc::new(RootManager::new().await?);
+    root_manager.clone().start_background_commit_task();
+
+    let db_service = DatabaseService::new().await?;
+    let pool = db_service.pool().clone();
+    let app_state = transport::http::AppState::new(
+        Arc::new(Mutex::new(db_service)),
+        Arc::new(RwLock::new(ModelRegistry::new())),
+        root_manager.clone(),
+    );
+    let router = transport::http::create_router(app_state);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    let server_handle = tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let base_url = format!("http://127.0.0.1:{}", port);
+    let client = reqwest::Client::new();
+
+    let bootstrap = client
+        .post(format!("{}/bootstrap/apply-schema", base_url))
+        .json(&json!({
+            "force_reset": true,
+            "tables": [
+                {
+                    "table_name": "notes",
+                    "primary_key_field": "id",
+                    "primary_key_kind": "big_serial",
+                    "columns": [
+                        {"name":"body","col_type":"text","nullable":false,"unique":false}
+                    ]
+                }
+            ]
+        }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(bootstrap["success"].as_bool().unwrap_or(false));
+
+    let create = client
+        .post(format!("{}/api/models/notes/create-batch", base_url))
+        .json(&json!({
+            "records": [ {"body":"one"}, {"body":"two"}, {"body":"three"} ]
+        }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(create["success"].as_bool().unwrap_or(false));
+    let ids: Vec<String> = create["data"]["ids"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap().to_string())
+        .collect();
+
+    // A clean DB must scan clean.
+    let clean = client
+        .get(format!("{}/bootstrap/drift", base_url))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(clean["success"].as_bool().unwrap_or(false));
+    assert!(clean["data"]["clean"].as_bool().unwrap_or(false));
+    assert_eq!(clean["data"]["report"]["scanned_rows"].as_u64(), Some(3));
+    let clean_root = hex::encode(root_manager.get_temporary_root().await.as_bytes());
+    assert_eq!(clean["data"]["report"]["stored_leaves_root"].as_str(), Some(clean_root.as_str()));
+    assert_eq!(clean["data"]["report"]["rows_root"].as_str(), Some(clean_root.as_str()));
+
+    // Tamper behind the service's back.
+    sqlx::query("UPDATE notes SET body = 'tampered' WHERE id = $1::bigint")
+        .bind(&ids[0])
+        .execute(&pool)
+        .await?;
+    sqlx::query("DELETE FROM notes WHERE id = $1::bigint")
+        .bind(&ids[1])
+        .execute(&pool)
+        .await?;
+    let injected: String = sqlx::query_scalar("INSERT INTO notes (body) VALUES ('injected') RETURNING id::text")
+        .fetch_one(&pool)
+        .await?;
+
+    let temp_before = root_manager.get_temporary_root().await;
+    let main_before = root_manager.get_main_root().await;
+
+    let drift = client
+        .get(format!("{}/bootstrap/drift", base_url))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(drift["success"].as_bool().unwrap_or(false));
+    assert!(!drift["data"]["clean"].as_bool().unwrap_or(true));
+
+    let report = &drift["data"]["report"];
+    let modified = report["modified"].as_array().unwrap();
+    assert_eq!(modified.len(), 1);
+    assert_eq!(modified[0]["table"], "notes");
+    assert_eq!(modified[0]["pk"].as_str(), Some(ids[0].as_str()));
+
+    let inserted = report["inserted_out_of_band"].as_array().unwrap();
+    assert_eq!(inserted.len(), 1);
+    assert_eq!(inserted[0]["pk"].as_str(), Some(injected.as_str()));
+
+    let deleted = report["deleted_out_of_band"].as_array().unwrap();
+    assert_eq!(deleted.len(), 1);
+    assert_eq!(deleted[0]["table"], "notes");
+    assert_eq!(deleted[0]["pk"].as_str(), Some(ids[1].as_str()));
+    assert_eq!(
+        report["stored_leaves_root"].as_str(),
+        Some(hex::encode(temp_before.as_bytes()).as_str())
+    );
+
+    // The scan must never move a root.
+    assert_eq!(root_manager.get_temporary_root().await, temp_before);
+    assert_eq!(root_manager.get_main_root().await, main_before);
+
+    if let Err(e) = root_manager.commit_pending_root().await {
+        eprintln!("commit_pending_root error: {}", e);
+    }
+    root_manager.shutdown();
+    server_handle.abort();
+
+    Ok(())
+}
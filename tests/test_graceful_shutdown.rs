@@ -0,0 +1,137 @@
+//! Graceful shutdown test:
+//! 1) Once shutdown begins, new writes get 503 while reads are still served.
+//! 2) The final anchor waits for the write still in flight, then commits the pending root.
+//! 3) The trusted state is flushed and the single-instance advisory lock is released.
+//! 4) A drain that outlives its timeout gives up instead of blocking shutdown.
+
+mod common;
+
+use serde_json::json;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use verifiable_memory_example::app::shutdown::{self, ShutdownGate};
+use verifiable_memory_example::{transport, DatabaseService, ModelRegistry, RootManager};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_graceful_shutdown() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    env::set_var("BATCH_COMMIT_SIZE", "1000");
+    env::set_var("COMMIT_MAX_AGE_SECS", "0");
+    // This test checks that the instance lock is released, so it must take it.
+    env::set_var("ALLOW_MULTI_INSTANCE", "false");
+    // The final checkpoint goes to a scratch location.
+    env::set_var("SMT_CHECKPOINT_PATH", env::temp_dir().join("vm_test_shutdown_checkpoint.bin"));
+
+    let anchor = common::init_anchor().await?;
+
+    env::set_var("CLEAR_DB", "true");
+    let root_manager = Arc::new(RootManager::new().await?);
+    root_manager.clone().start_background_commit_task();
+
+    let db_service = DatabaseService::new().await?;
+    let pool = db_service.pool().clone();
+    let db_service = Arc::new(Mutex::new(db_service));
+    let app_state = transport::http::AppState::new(
+        db_service.clone(),
+        Arc::new(RwLock::new(ModelRegistry::new())),
+        root_manager.clone(),
+    );
+    let gate = app_state.shutdown.clone();
+    let router = transport::http::create_router(app_state);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let base_url = format!("http://127.0.0.1:{}", port);
+    let client = reqwest::Client::new();
+
+    let bootstrap = client
+        .post(format!("{}/bootstrap/apply-schema", base_url))
+        .json(&json!({
+            "force_reset": true,
+            "tables": [
+                {
+                    "table_name": "notes",
+                    "primary_key_field": "id",
+                    "primary_key_kind": "big_serial",
+                    "columns": [
+                        {"name":"body","col_type":"text","nullable":false,"unique":false}
+                    ]
+                }
+            ]
+        }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(bootstrap["success"].as_bool().unwrap_or(false));
+
+    let write = client
+        .post(format!("{}/api/models/notes/create-batch", base_url))
+        .json(&json!({ "records": [ {"body":"before shutdown"} ] }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert_eq!(write["data"]["meta"]["committed"].as_bool(), Some(false));
+    let pending_root = root_manager.get_temporary_root().await;
+    assert_ne!(pending_root, root_manager.get_main_root().await);
+
+    // --- Shutdown begins while a write is still in flight ---
+    let in_flight = gate.try_enter().expect("gate open");
+    let finalize = {
+        let (gate, root_manager, db_service) = (gate.clone(), root_manager.clone(), db_service.clone());
+        tokio::spawn(async move {
+            shutdown::finalize(&gate, &root_manager, &db_service, Duration::from_secs(10)).await
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(gate.is_closed());
+    assert!(!finalize.is_finished(), "final anchor must wait for the in-flight write");
+
+    let rejected = client
+        .post(format!("{}/api/models/notes/create-batch", base_url))
+        .json(&json!({ "records": [ {"body":"after shutdown"} ] }))
+        .send()
+        .await?;
+    assert_eq!(rejected.status().as_u16(), 503);
+    let read = client
+        .post(format!("{}/api/models/notes/read-latest", base_url))
+        .json(&json!({ "limit": 5 }))
+        .send()
+        .await?;
+    assert_eq!(read.status().as_u16(), 200, "reads are served while draining");
+
+    drop(in_flight);
+    finalize.await??;
+
+    // --- Pending root anchored, trusted state flushed, instance lock released ---
+    assert_eq!(root_manager.get_main_root().await, pending_root);
+    assert_eq!(anchor.read_root().await?, pending_root);
+    let state: serde_json::Value = serde_json::from_str(&std::fs::read_to_string("trusted_state.json")?)?;
+    assert_eq!(state["root"].as_str(), Some(hex::encode(pending_root.as_bytes()).as_str()));
+
+    // Advisory locks are per session, so look at pg_locks rather than re-locking from the pool.
+    let held: i64 = sqlx::query_scalar(
+        "SELECT count(*) FROM pg_locks WHERE locktype = 'advisory' AND objid = 4240001 AND granted",
+    )
+    .fetch_one(&pool)
+    .await?;
+    assert_eq!(held, 0, "instance lock still held after shutdown");
+
+    // --- A stuck write does not block shutdown forever ---
+    let gate = ShutdownGate::new();
+    let stuck = gate.try_enter().expect("gate open");
+    gate.close();
+    assert!(gate.try_enter().is_none());
+    assert!(!gate.drain(Duration::from_millis(100)).await);
+    drop(stuck);
+    assert!(gate.drain(Duration::from_millis(100)).await);
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+//! Multi-anchor quorum test (no Solana needed):
+//! 1) A root is anchored to a memory target, a transparency log, an EVM stand-in and a flaky
+//!    target; with `quorum=3` the flaky failure is tolerated and recorded per target in
+//!    `anchor_history`.
+//! 2) `all` fails while any target fails; `primary` only requires the first target.
+//! 3) Reads return the root a quorum agrees on (with per-target records), and a tampered or
+//!    truncated transparency log is refused.
+//! 4) `ANCHOR_TARGETS` / `ANCHOR_QUORUM` build the same setup from config.
+
+mod common;
+
+use common::TestAnchor;
+use primitive_types::H256;
+use std::env;
+use std::fs;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use verifiable_memory_example::infra::anchor::{
+    self, evm_standin, AnchorTarget, EvmAnchor, MemoryAnchor, MultiAnchor, QuorumPolicy,
+    RootAnchor, TransparencyLogAnchor,
+};
+use verifiable_memory_example::storage::anchor_history::AnchorHistory;
+use verifiable_memory_example::{DatabaseService, RootManager};
+
+const CONTRACT: &str = "0x00000000000000000000000000000000000000c0";
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_multi_anchor() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    env::set_var("BATCH_COMMIT_SIZE", "1000");
+    env::set_var("COMMIT_MAX_AGE_SECS", "0");
+    env::set_var("CLEAR_DB", "true");
+    env::set_var("ALLOW_MULTI_INSTANCE", "true");
+    env::set_var("SEALING_KEY_PATH", env::temp_dir().join("vm_test_sealing.key"));
+
+    // --- Policies ---
+    assert_eq!(QuorumPolicy::parse("all", 3)?, QuorumPolicy::All);
+    assert_eq!(QuorumPolicy::parse("majority", 4)?, QuorumPolicy::Quorum(3));
+    assert_eq!(QuorumPolicy::parse("quorum=2", 3)?, QuorumPolicy::Quorum(2));
+    assert_eq!(QuorumPolicy::parse("primary", 3)?, QuorumPolicy::Primary);
+    assert!(QuorumPolicy::parse("quorum=4", 3).is_err());
+    assert!(QuorumPolicy::parse("most", 3).is_err());
+
+    // --- Targets: EVM stand-in on an ephemeral port, a fresh transparency log ---
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let evm_url = format!("http://{}", listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, evm_standin::router()).await });
+
+    let log_path = env::temp_dir().join(format!("vm_test_anchor_log_{}.jsonl", std::process::id()));
+    let memory = Arc::new(MemoryAnchor::new());
+    let log = Arc::new(TransparencyLogAnchor::new(&log_path, [7u8; 32]));
+    let _ = fs::remove_file(&log_path);
+    let _ = fs::remove_file(log.head_path());
+    let evm = Arc::new(EvmAnchor::new(&evm_url, CONTRACT));
+    let flaky = Arc::new(TestAnchor::new());
+    let targets = vec![
+        AnchorTarget::new("memory", memory.clone()),
+        AnchorTarget::new("log", log.clone()),
+        AnchorTarget::new("evm", evm.clone()),
+        AnchorTarget::new("flaky", flaky.clone()),
+    ];
+    let quorum = Arc::new(MultiAnchor::new(targets.clone(), QuorumPolicy::Quorum(3))?);
+    quorum.initialize().await?;
+    assert!(MultiAnchor::new(vec![targets[0].clone(), targets[0].clone()], QuorumPolicy::All).is_err());
+
+    // --- Quorum met despite one failing target; per-target status lands in the history ---
+    let db = DatabaseService::new().await?;
+    let pool = db.pool().clone();
+    sqlx::query("TRUNCATE TABLE anchor_history CASCADE").execute(&pool).await?;
+
+    let root_manager = RootManager::with_anchor(quorum.clone()).await?;
+    root_manager.attach_history(AnchorHistory::new(pool.clone())).await?;
+    flaky.failing.store(true, Ordering::SeqCst);
+    let r1 = H256::repeat_byte(0x31);
+    {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.update_temporary_root(r1).await;
+    }
+    assert!(root_manager.commit_now().await?);
+    assert_eq!(memory.read_root().await?, r1);
+    assert_eq!(log.read_root().await?, r1);
+    assert_eq!(evm.read_root().await?, r1);
+    assert_eq!(flaky.read_root().await?, H256::zero());
+
+    let entry = &root_manager.history().unwrap().list(1, None).await?[0];
+    assert_eq!(entry.backend, "multi");
+    let labels: Vec<&str> = entry.targets.iter().map(|t| t.target.as_str()).collect();
+    assert_eq!(labels, ["memory", "log", "evm", "flaky"]);
+    assert!(entry.targets[..3].iter().all(|t| t.ok));
+    assert_eq!(entry.targets[1].slot, Some(0), "first transparency log entry");
+    assert!(entry.targets[2].signature.as_deref().unwrap_or("").starts_with("0x"), "EVM tx hash");
+    assert!(!entry.targets[3].ok);
+    assert!(entry.targets[3].error.as_deref().unwrap_or("").contains("RPC unavailable"));
+    let covering = root_manager.history().unwrap().first_covering(r1).await?.expect("covered");
+    assert_eq!(covering.targets.len(), 4);
+
+    // Three of four targets agree on r1; the record shows what each one holds.
+    assert_eq!(quorum.read_root().await?, r1);
+    let record = quorum.read_record().await?;
+    assert_eq!(record.root, r1);
+    assert_eq!(record.targets.len(), 4);
+    assert_eq!(record.targets[3].record.as_ref().map(|r| r.root), Some(H256::zero()));
+    root_manager.shutdown();
+
+    // --- `all` fails while a target fails; `primary` only needs the first target ---
+    let all = MultiAnchor::new(targets.clone(), QuorumPolicy::All)?;
+    let err = all.write_root(H256::repeat_byte(0x32)).await.unwrap_err();
+    assert!(err.to_string().contains("flaky"), "{}", err);
+    assert!(all.read_root().await.is_err(), "flaky lags, so not all targets agree");
+
+    let flaky_primary = MultiAnchor::new(
+        vec![AnchorTarget::new("flaky", flaky.clone()), targets[0].clone()],
+        QuorumPolicy::Primary,
+    )?;
+    assert!(flaky_primary.write_root(H256::repeat_byte(0x33)).await.is_err());
+    let memory_primary = MultiAnchor::new(
+        vec![targets[0].clone(), AnchorTarget::new("flaky", flaky.clone())],
+        QuorumPolicy::Primary,
+    )?;
+    let receipt = memory_primary.write_root(H256::repeat_byte(0x34)).await?;
+    assert!(receipt.targets[0].ok && !receipt.targets[1].ok);
+    assert_eq!(memory_primary.read_root().await?, H256::repeat_byte(0x34));
+
+    flaky.failing.store(false, Ordering::SeqCst);
+    all.write_root(H256::repeat_byte(0x35)).await?;
+    assert_eq!(all.read_root().await?, H256::repeat_byte(0x35));
+
+    // --- Tampering with the transparency log is detected ---
+    let contents = fs::read_to_string(&log_path)?;
+    assert_eq!(contents.lines().count(), 3, "r1, 0x32 (all) and 0x35");
+    fs::write(&log_path, contents.lines().skip(1).collect::<Vec<_>>().join("\n"))?;
+    assert!(log.read_root().await.is_err(), "dropped entry accepted");
+    fs::write(&log_path, contents.replace(&hex::encode(r1.as_bytes()), &hex::encode([0x99u8; 32])))?;
+    assert!(log.read_root().await.is_err(), "edited entry accepted");
+    let truncated: String = contents.lines().take(2).map(|line| format!("{}\n", line)).collect();
+    fs::write(&log_path, truncated)?;
+    assert!(log.read_root().await.is_err(), "truncated log accepted");
+    fs::write(&log_path, &contents)?;
+    assert_eq!(log.read_root().await?, H256::repeat_byte(0x35));
+
+    // --- Config (the log target is keyed by the sealing key provider, so it gets its own file) ---
+    let _ = fs::remove_file(&log_path);
+    let _ = fs::remove_file(log.head_path());
+    env::set_var(
+        "ANCHOR_TARGETS",
+        format!("memory, log={}, evm={}@{}", log_path.display(), CONTRACT, evm_url),
+    );
+    env::set_var("ANCHOR_QUORUM", "majority");
+    let configured = anchor::from_config()?;
+    assert_eq!(configured.name(), "multi");
+    configured.initialize().await?;
+    let receipt = configured.write_root(H256::repeat_byte(0x36)).await?;
+    assert_eq!(receipt.targets.len(), 3);
+    assert!(receipt.targets.iter().all(|t| t.ok));
+    assert_eq!(configured.read_root().await?, H256::repeat_byte(0x36));
+    env::set_var("ANCHOR_TARGETS", "memory, bogus");
+    assert!(anchor::from_config().is_err());
+    env::remove_var("ANCHOR_TARGETS");
+    env::remove_var("ANCHOR_QUORUM");
+
+    let _ = fs::remove_file(&log_path);
+    let _ = fs::remove_file(log.head_path());
+    Ok(())
+}
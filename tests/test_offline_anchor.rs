@@ -0,0 +1,47 @@
+//! Offline anchor test (no Solana, no DB needed):
+//! 1) Drive a RootManager backed by an in-process anchor through one batch.
+//! 2) Ensure the batch commit lands in the anchor and main_root follows.
+//! 3) Ensure a restarted RootManager picks main_root up from the same anchor.
+
+use primitive_types::H256;
+use std::env;
+use std::sync::Arc;
+use verifiable_memory_example::infra::anchor::{MemoryAnchor, RootAnchor};
+use verifiable_memory_example::RootManager;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_offline_anchor() -> Result<(), Box<dyn std::error::Error>> {
+    env::set_var("BATCH_COMMIT_SIZE", "3");
+    env::set_var("CLEAR_DB", "true");
+
+    let anchor = Arc::new(MemoryAnchor::new());
+    let root_manager = Arc::new(RootManager::with_anchor(anchor.clone()).await?);
+    root_manager.clone().start_background_commit_task();
+    assert_eq!(root_manager.get_main_root().await, H256::zero());
+
+    let roots: Vec<H256> = (1u8..=3).map(H256::repeat_byte).collect();
+    let mut last = None;
+    for root in &roots {
+        let _root_guard = root_manager.lock_root().await;
+        last = Some(root_manager.update_temporary_root(*root).await);
+    }
+    let last = last.unwrap();
+    assert!(last.commit_due, "third update should reach BATCH_COMMIT_SIZE");
+    tokio::time::timeout(
+        tokio::time::Duration::from_secs(5),
+        root_manager.wait_for_anchor(last.seq),
+    )
+    .await??;
+
+    assert_eq!(anchor.read_root().await?, roots[2]);
+    assert_eq!(root_manager.get_main_root().await, roots[2]);
+    root_manager.shutdown();
+
+    // Restart against the same anchor: main_root comes from the anchor, not from scratch.
+    env::set_var("CLEAR_DB", "false");
+    let restarted = RootManager::with_anchor(anchor.clone()).await?;
+    assert_eq!(restarted.get_main_root().await, roots[2]);
+    assert_eq!(restarted.get_temporary_root().await, roots[2]);
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+//! Pipelined commit test (no Solana, no DB needed):
+//! 1) Anchor through a deliberately slow backend.
+//! 2) Ensure writes keep advancing temporary_root while a commit is in flight.
+//! 3) Ensure waiters are woken once their update is anchored, and later updates are picked up
+//!    by the next commit.
+
+mod common;
+
+use common::TestAnchor;
+use primitive_types::H256;
+use std::env;
+use std::sync::Arc;
+use tokio::time::{timeout, Duration, Instant};
+use verifiable_memory_example::infra::anchor::RootAnchor;
+use verifiable_memory_example::RootManager;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_pipelined_commit() -> Result<(), Box<dyn std::error::Error>> {
+    env::set_var("BATCH_COMMIT_SIZE", "2");
+    env::set_var("COMMIT_MAX_AGE_SECS", "0");
+    env::set_var("CLEAR_DB", "true");
+
+    let anchor = Arc::new(TestAnchor::new().with_delay(Duration::from_millis(800)));
+    let root_manager = Arc::new(RootManager::with_anchor(anchor.clone()).await?);
+    root_manager.clone().start_background_commit_task();
+
+    let update = |root: H256| {
+        let root_manager = root_manager.clone();
+        async move {
+            let _root_guard = root_manager.lock_root().await;
+            root_manager.update_temporary_root(root).await
+        }
+    };
+
+    // Two updates make the first batch due; its anchor write takes ~800ms.
+    update(H256::repeat_byte(1)).await;
+    let batch_1 = update(H256::repeat_byte(2)).await;
+    assert!(batch_1.commit_due);
+
+    // Wait until the commit has actually snapshotted the root.
+    timeout(Duration::from_secs(2), async {
+        while root_manager.commit_status().await.anchoring_root.is_none() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+
+    // Writes are not blocked by the in-flight commit.
+    let started = Instant::now();
+    update(H256::repeat_byte(3)).await;
+    let batch_2 = update(H256::repeat_byte(4)).await;
+    assert!(
+        started.elapsed() < Duration::from_millis(400),
+        "writes stalled behind the anchor commit ({:?})",
+        started.elapsed()
+    );
+    assert_eq!(root_manager.get_temporary_root().await, H256::repeat_byte(4));
+
+    let status = root_manager.commit_status().await;
+    assert_eq!(status.anchoring_root, Some(hex::encode(H256::repeat_byte(2).as_bytes())));
+    assert_eq!(status.pending_updates, 4);
+
+    // The first waiter is released by the in-flight commit, which anchored root #2 only.
+    timeout(Duration::from_secs(5), root_manager.wait_for_anchor(batch_1.seq)).await??;
+    assert_eq!(anchor.read_root().await?, H256::repeat_byte(2));
+
+    // The second batch is anchored by the next commit.
+    timeout(Duration::from_secs(5), root_manager.wait_for_anchor(batch_2.seq)).await??;
+    assert_eq!(anchor.read_root().await?, H256::repeat_byte(4));
+    assert_eq!(root_manager.get_main_root().await, H256::repeat_byte(4));
+    assert_eq!(root_manager.commit_status().await.pending_updates, 0);
+
+    root_manager.shutdown();
+    Ok(())
+}
@@ -0,0 +1,130 @@
+//! Cancelled rebuild test:
+//! 1) Start `POST /bootstrap/repair-roots` and drop the request future once the build phase has
+//!    begun (as when the client disconnects), while the swap is held back by the root lock.
+//! 2) The rebuild still runs to completion: the tracker leaves the in-progress state, the shadow
+//!    table is gone, the rebuilt root is anchored and writes are accepted again.
+
+mod common;
+
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::Json;
+use serde_json::json;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use verifiable_memory_example::app::rebuild::RebuildPhase;
+use verifiable_memory_example::transport::http::handlers::bootstrap::bootstrap_repair_roots_handler;
+use verifiable_memory_example::transport::http::types::RepairRootsRequest;
+use verifiable_memory_example::{transport, DatabaseService, ModelRegistry, RootManager};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_rebuild_cancel() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    if env::var("BATCH_COMMIT_SIZE").is_err() {
+        env::set_var("BATCH_COMMIT_SIZE", "3");
+    }
+    common::init_anchor().await?;
+
+    env::set_var("CLEAR_DB", "true");
+    let root_manager = Arc::new(RootManager::new().await?);
+    let db_service = DatabaseService::new().await?;
+    let pool = db_service.pool().clone();
+    let app_state = transport::http::AppState::new(
+        Arc::new(Mutex::new(db_service)),
+        Arc::new(RwLock::new(ModelRegistry::new())),
+        root_manager.clone(),
+    );
+    let router = transport::http::create_router(app_state.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let base_url = format!("http://127.0.0.1:{}", listener.local_addr()?.port());
+    let server = tokio::spawn(async move { axum::serve(listener, router).await });
+    let client = reqwest::Client::new();
+
+    let bootstrap = client
+        .post(format!("{}/bootstrap/apply-schema", base_url))
+        .json(&json!({
+            "force_reset": true,
+            "tables": [{
+                "table_name": "notes",
+                "primary_key_field": "id",
+                "primary_key_kind": "big_serial",
+                "columns": [{"name":"body","col_type":"text","nullable":false,"unique":false}]
+            }]
+        }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(bootstrap["success"].as_bool().unwrap_or(false), "{}", bootstrap);
+    let create = client
+        .post(format!("{}/api/models/notes/create-batch", base_url))
+        .json(&json!({ "records": [ {"body":"one"}, {"body":"two"} ] }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(create["success"].as_bool().unwrap_or(false), "{}", create);
+    // Enough rows that the build phase lasts a while.
+    sqlx::query("INSERT INTO notes (body) SELECT 'bulk ' || n FROM generate_series(1, 2000) AS n")
+        .execute(&pool)
+        .await?;
+
+    // --- Drop the request mid-rebuild ---
+    let request = tokio::spawn(bootstrap_repair_roots_handler(
+        State(app_state.clone()),
+        Ok(Json(RepairRootsRequest { confirm: true })),
+    ));
+    let building = async {
+        while app_state.rebuild.status().phase != RebuildPhase::Building {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(10), building).await?;
+    {
+        // The swap needs the root lock, so the rebuild cannot finish before the drop.
+        let _root_guard = root_manager.lock_root().await;
+        request.abort();
+        assert!(request.await.err().expect("request dropped").is_cancelled());
+        assert!(app_state.rebuild.status().in_progress());
+    }
+
+    // --- The rebuild finishes on its own ---
+    let finished = async {
+        while app_state.rebuild.status().in_progress() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(60), finished)
+        .await
+        .expect("the rebuild was left in progress");
+    let status = app_state.rebuild.status();
+    assert_eq!(status.phase, RebuildPhase::Completed, "{:?}", status.error);
+    let new_root = status.new_root.expect("rebuilt root");
+    assert_eq!(hex::encode(root_manager.get_main_root().await.as_bytes()), new_root);
+    let shadow: Option<String> = sqlx::query_scalar("SELECT to_regclass('merkle_nodes_shadow')::text")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(shadow, None, "the shadow table was dropped");
+
+    let create = client
+        .post(format!("{}/api/models/notes/create-batch", base_url))
+        .json(&json!({ "records": [ {"body":"three"} ] }))
+        .send()
+        .await?;
+    assert_eq!(create.status(), reqwest::StatusCode::OK);
+
+    // A second repair is not refused as a concurrent rebuild.
+    let repair = bootstrap_repair_roots_handler(
+        State(app_state.clone()),
+        Ok(Json(RepairRootsRequest { confirm: true })),
+    )
+    .await
+    .into_response();
+    assert_eq!(repair.status(), axum::http::StatusCode::OK);
+
+    root_manager.shutdown();
+    server.abort();
+    Ok(())
+}
@@ -0,0 +1,168 @@
+//! Startup reconciliation test (no Solana needed):
+//! 1) Classify clean, un-anchored tail, SMT behind trusted, DB tampered and chain-ahead roots.
+//! 2) Ensure `auto_anchor` anchors the tail and `refuse` aborts.
+//! 3) Ensure a read-only outcome rejects writes over HTTP until `POST /bootstrap/repair-roots`
+//!    makes the roots consistent again, and `GET /api/reconcile-status` reports it.
+
+use primitive_types::H256;
+use serde_json::json;
+use std::env;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use verifiable_memory_example::app::reconcile::{
+    reconcile, ReconcileAction, ReconcilePolicy, RootSituation,
+};
+use verifiable_memory_example::infra::anchor::{MemoryAnchor, RootAnchor};
+use verifiable_memory_example::storage::anchor_history::AnchorHistory;
+use verifiable_memory_example::{transport, DatabaseService, ModelRegistry, RootManager};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_reconcile() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    env::set_var("BATCH_COMMIT_SIZE", "1000");
+    env::set_var("COMMIT_MAX_AGE_SECS", "0");
+    env::set_var("ALLOW_MULTI_INSTANCE", "true");
+
+    let db_service = DatabaseService::new().await?;
+    let pool = db_service.pool().clone();
+    sqlx::query("TRUNCATE TABLE anchor_history CASCADE").execute(&pool).await?;
+
+    let policy = ReconcilePolicy::from_config()?;
+    let (r1, r2) = (H256::repeat_byte(0x01), H256::repeat_byte(0x02));
+
+    env::set_var("CLEAR_DB", "true");
+    let anchor = Arc::new(MemoryAnchor::new());
+    let root_manager = RootManager::with_anchor(anchor.clone()).await?;
+    root_manager.attach_history(AnchorHistory::new(pool.clone())).await?;
+
+    // --- Clean ---
+    let report = reconcile(&root_manager, H256::zero(), &policy).await?;
+    assert_eq!(report.situation, RootSituation::Clean);
+    assert!(report.writes_allowed);
+
+    // --- Un-anchored tail: auto-anchored by default ---
+    {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.update_temporary_root(r1).await;
+    }
+    let report = reconcile(&root_manager, r1, &policy).await?;
+    assert_eq!(report.situation, RootSituation::UnanchoredTail);
+    assert_eq!(report.action, ReconcileAction::AutoAnchor);
+    assert!(report.writes_allowed);
+    assert_eq!(anchor.read_root().await?, r1);
+
+    // --- SMT behind trusted: the DB is at the anchored root, the trusted root moved on ---
+    {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.update_temporary_root(r2).await;
+    }
+    let report = reconcile(&root_manager, r1, &policy).await?;
+    assert_eq!(report.situation, RootSituation::SmtBehindTrusted);
+    assert!(!report.writes_allowed);
+
+    // --- DB tampered: the DB is at a root nobody produced; `refuse` aborts ---
+    let report = reconcile(&root_manager, H256::repeat_byte(0x09), &policy).await?;
+    assert_eq!(report.situation, RootSituation::DbTampered);
+    let strict = ReconcilePolicy {
+        db_tampered: ReconcileAction::Refuse,
+        ..policy
+    };
+    assert!(reconcile(&root_manager, H256::repeat_byte(0x09), &strict).await.is_err());
+    root_manager.shutdown();
+    drop(root_manager);
+
+    // --- Chain ahead: another writer anchored a root this service never produced ---
+    anchor.write_root(H256::repeat_byte(0x07)).await?;
+    env::set_var("CLEAR_DB", "false");
+    let root_manager = Arc::new(RootManager::with_anchor(anchor.clone()).await?);
+    root_manager.attach_history(AnchorHistory::new(pool.clone())).await?;
+    let report = reconcile(&root_manager, r2, &policy).await?;
+    assert_eq!(report.situation, RootSituation::ChainAheadOfLocal);
+    assert_eq!(report.action, ReconcileAction::ReadOnly);
+    assert!(!report.writes_allowed);
+
+    // --- HTTP: writes are rejected until repair-roots makes the roots consistent ---
+    let app_state = transport::http::AppState::new(
+        Arc::new(Mutex::new(db_service)),
+        Arc::new(RwLock::new(ModelRegistry::new())),
+        root_manager.clone(),
+    );
+    let router = transport::http::create_router(app_state.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    let server_handle = tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    let base_url = format!("http://127.0.0.1:{}", port);
+    let client = reqwest::Client::new();
+
+    let bootstrap = client
+        .post(format!("{}/bootstrap/apply-schema", base_url))
+        .json(&json!({
+            "force_reset": true,
+            "tables": [
+                {
+                    "table_name": "notes",
+                    "primary_key_field": "id",
+                    "primary_key_kind": "big_serial",
+                    "columns": [
+                        {"name":"body","col_type":"text","nullable":false,"unique":false}
+                    ]
+                }
+            ]
+        }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(bootstrap["success"].as_bool().unwrap_or(false));
+
+    app_state.reconcile.set(report);
+    let status = client
+        .get(format!("{}/api/reconcile-status", base_url))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert_eq!(status["data"]["report"]["situation"], "chain_ahead_of_local");
+    assert_eq!(status["data"]["report"]["writes_allowed"], false);
+
+    let create = |body: &'static str| {
+        client
+            .post(format!("{}/api/models/notes/create-batch", base_url))
+            .json(&json!({ "records": [ {"body": body} ] }))
+            .send()
+    };
+    assert_eq!(create("blocked").await?.status().as_u16(), 503);
+
+    let read = client
+        .post(format!("{}/api/models/notes/read-latest", base_url))
+        .json(&json!({ "limit": 5 }))
+        .send()
+        .await?;
+    // The table is empty (404), but reads are not gated.
+    assert_ne!(read.status().as_u16(), 503, "reads stay available");
+
+    let repair = client
+        .post(format!("{}/bootstrap/repair-roots", base_url))
+        .json(&json!({ "confirm": true }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(repair["success"].as_bool().unwrap_or(false), "repair failed: {}", repair);
+
+    let status = client
+        .get(format!("{}/api/reconcile-status", base_url))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert_eq!(status["data"]["report"]["writes_allowed"], true);
+    assert_eq!(status["data"]["report"]["resolved_by"], "repair-roots");
+    assert_eq!(create("allowed").await?.status().as_u16(), 200);
+
+    server_handle.abort();
+    root_manager.shutdown();
+    Ok(())
+}
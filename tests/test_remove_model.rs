@@ -0,0 +1,158 @@
+//! Model removal test:
+//! 1) Bootstrap two tables and write verified rows to both.
+//! 2) Remove one model via `POST /bootstrap/remove-model`.
+//! 3) Ensure its table and leaves are gone in one root transition, the other model still
+//!    verifies, and `gc-leaves` refuses to touch a registered model.
+
+mod common;
+
+use serde_json::json;
+use std::env;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use verifiable_memory_example::{transport, DatabaseService, ModelRegistry, RootManager};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_remove_model() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+
+    if env::var("BATCH_COMMIT_SIZE").is_err() {
+        env::set_var("BATCH_COMMIT_SIZE", "3");
+    }
+
+    common::init_anchor().await?;
+
+    env::set_var("CLEAR_DB", "true");
+    let root_manager = Arc::new(RootManager::new().await?);
+    root_manager.clone().start_background_commit_task();
+
+    let db_service = DatabaseService::new().await?;
+    let pool = db_service.pool().clone();
+    let app_state = transport::http::AppState::new(
+        Arc::new(Mutex::new(db_service)),
+        Arc::new(RwLock::new(ModelRegistry::new())),
+        root_manager.clone(),
+    );
+    let router = transport::http::create_router(app_state);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    let server_handle = tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let base_url = format!("http://127.0.0.1:{}", port);
+    let client = reqwest::Client::new();
+
+    let bootstrap = client
+        .post(format!("{}/bootstrap/apply-schema", base_url))
+        .json(&json!({
+            "force_reset": true,
+            "tables": [
+                {
+                    "table_name": "notes",
+                    "primary_key_field": "id",
+                    "primary_key_kind": "big_serial",
+                    "columns": [
+                        {"name":"body","col_type":"text","nullable":false,"unique":false}
+                    ]
+                },
+                {
+                    "table_name": "tags",
+                    "primary_key_field": "id",
+                    "primary_key_kind": "big_serial",
+                    "columns": [
+                        {"name":"label","col_type":"text","nullable":false,"unique":false}
+                    ]
+                }
+            ]
+        }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(bootstrap["success"].as_bool().unwrap_or(false));
+
+    let notes = client
+        .post(format!("{}/api/models/notes/create-batch", base_url))
+        .json(&json!({ "records": [ {"body":"one"}, {"body":"two"} ] }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(notes["success"].as_bool().unwrap_or(false));
+    let note_ids = notes["data"]["ids"].clone();
+
+    let tags = client
+        .post(format!("{}/api/models/tags/create-batch", base_url))
+        .json(&json!({ "records": [ {"label":"a"}, {"label":"b"}, {"label":"c"} ] }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(tags["success"].as_bool().unwrap_or(false));
+
+    let owned: i64 = sqlx::query_scalar("SELECT count(*) FROM merkle_nodes WHERE table_name = 'tags'")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(owned, 3);
+
+    // Orphan cleanup must refuse to touch a live model.
+    let gc = client
+        .post(format!("{}/bootstrap/gc-leaves", base_url))
+        .json(&json!({ "table_name": "notes", "confirm": true }))
+        .send()
+        .await?;
+    assert_eq!(gc.status().as_u16(), 409);
+
+    let root_before = root_manager.get_temporary_root().await;
+    let removed = client
+        .post(format!("{}/bootstrap/remove-model", base_url))
+        .json(&json!({ "table_name": "tags", "confirm": true }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(removed["success"].as_bool().unwrap_or(false), "remove failed: {}", removed);
+    assert_eq!(removed["data"]["removed_leaves"].as_u64(), Some(3));
+    assert_eq!(removed["data"]["old_root"].as_str(), Some(hex::encode(root_before.as_bytes()).as_str()));
+    assert_eq!(
+        removed["data"]["new_root"].as_str(),
+        Some(hex::encode(root_manager.get_temporary_root().await.as_bytes()).as_str())
+    );
+
+    let table_exists: bool = sqlx::query_scalar("SELECT to_regclass('public.tags') IS NOT NULL")
+        .fetch_one(&pool)
+        .await?;
+    assert!(!table_exists);
+    let left: i64 = sqlx::query_scalar("SELECT count(*) FROM merkle_nodes WHERE table_name = 'tags'")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(left, 0);
+
+    // The remaining model still verifies against the new root.
+    let read = client
+        .post(format!("{}/api/models/notes/read-batch", base_url))
+        .json(&json!({ "ids": note_ids }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(read["success"].as_bool().unwrap_or(false), "read failed: {}", read);
+
+    let drift = client
+        .get(format!("{}/bootstrap/drift", base_url))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(drift["data"]["clean"].as_bool().unwrap_or(false));
+
+    if let Err(e) = root_manager.commit_pending_root().await {
+        eprintln!("commit_pending_root error: {}", e);
+    }
+    root_manager.shutdown();
+    server_handle.abort();
+
+    Ok(())
+}
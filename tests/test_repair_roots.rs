@@ -0,0 +1,152 @@
+//! Shadow repair test:
+//! 1) Bootstrap a schema and write a few verified rows.
+//! 2) Tamper with a row directly in Postgres so the tree no longer matches.
+//! 3) Run `POST /bootstrap/repair-roots` and ensure the rebuilt root is anchored, the scan is clean
+//!    again, `GET /bootstrap/rebuild-status` reports completion and writes keep verifying.
+
+mod common;
+
+use serde_json::json;
+use std::env;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use verifiable_memory_example::{transport, DatabaseService, ModelRegistry, RootManager};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_repair_roots_shadow_rebuild() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+
+    if env::var("BATCH_COMMIT_SIZE").is_err() {
+        env::set_var("BATCH_COMMIT_SIZE", "3");
+    }
+
+    common::init_anchor().await?;
+
+    env::set_var("CLEAR_DB", "true");
+    let root_manager = Arc::new(RootManager::new().await?);
+    root_manager.clone().start_background_commit_task();
+
+    let db_service = DatabaseService::new().await?;
+    let pool = db_service.pool().clone();
+    let app_state = transport::http::AppState::new(
+        Arc::new(Mutex::new(db_service)),
+        Arc::new(RwLock::new(ModelRegistry::new())),
+        root_manager.clone(),
+    );
+    let router = transport::http::create_router(app_state);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    let server_handle = tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let base_url = format!("http://127.0.0.1:{}", port);
+    let client = reqwest::Client::new();
+
+    let bootstrap = client
+        .post(format!("{}/bootstrap/apply-schema", base_url))
+        .json(&json!({
+            "force_reset": true,
+            "tables": [
+                {
+                    "table_name": "notes",
+                    "primary_key_field": "id",
+                    "primary_key_kind": "big_serial",
+                    "columns": [
+                        {"name":"body","col_type":"text","nullable":false,"unique":false}
+                    ]
+                }
+            ]
+        }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(bootstrap["success"].as_bool().unwrap_or(false));
+
+    let create = client
+        .post(format!("{}/api/models/notes/create-batch", base_url))
+        .json(&json!({
+            "records": [ {"body":"one"}, {"body":"two"}, {"body":"three"} ]
+        }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(create["success"].as_bool().unwrap_or(false));
+    let ids: Vec<String> = create["data"]["ids"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap().to_string())
+        .collect();
+
+    sqlx::query("UPDATE notes SET body = 'tampered' WHERE id = $1::bigint")
+        .bind(&ids[0])
+        .execute(&pool)
+        .await?;
+    let root_before = root_manager.get_temporary_root().await;
+
+    let repair = client
+        .post(format!("{}/bootstrap/repair-roots", base_url))
+        .json(&json!({ "confirm": true }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(repair["success"].as_bool().unwrap_or(false), "repair failed: {}", repair);
+    assert_eq!(repair["data"]["updated_leaves"].as_u64(), Some(3));
+
+    let new_root = repair["data"]["new_root"].as_str().unwrap().to_string();
+    assert_ne!(new_root, hex::encode(root_before.as_bytes()));
+    assert_eq!(hex::encode(root_manager.get_temporary_root().await.as_bytes()), new_root);
+    assert_eq!(hex::encode(root_manager.get_main_root().await.as_bytes()), new_root);
+
+    let status = client
+        .get(format!("{}/bootstrap/rebuild-status", base_url))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert_eq!(status["data"]["phase"], "completed");
+    assert_eq!(status["data"]["operation"], "repair-roots");
+    assert_eq!(status["data"]["rows_hashed"].as_u64(), Some(3));
+    assert_eq!(status["data"]["rows_total"].as_u64(), Some(3));
+    assert_eq!(status["data"]["new_root"].as_str(), Some(new_root.as_str()));
+
+    let drift = client
+        .get(format!("{}/bootstrap/drift", base_url))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(drift["data"]["clean"].as_bool().unwrap_or(false));
+
+    // The swapped-in tree is live: reads and writes keep verifying.
+    let read = client
+        .post(format!("{}/api/models/notes/read-batch", base_url))
+        .json(&json!({ "ids": [ids[0]] }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(read["success"].as_bool().unwrap_or(false), "read failed: {}", read);
+
+    let create = client
+        .post(format!("{}/api/models/notes/create-batch", base_url))
+        .json(&json!({ "records": [ {"body":"four"} ] }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(create["success"].as_bool().unwrap_or(false), "create failed: {}", create);
+
+    if let Err(e) = root_manager.commit_pending_root().await {
+        eprintln!("commit_pending_root error: {}", e);
+    }
+    root_manager.shutdown();
+    server_handle.abort();
+
+    Ok(())
+}
@@ -4,11 +4,13 @@
 //! 3) Start server again, but load ModelRegistry from DB (verifiable_models) without bootstrapping.
 //! 4) Ensure create-batch/read-batch works immediately.
 
+mod common;
+
 use serde_json::json;
 use std::env;
 use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
-use verifiable_memory_example::{solana, transport, DatabaseService, ModelRegistry, RootManager};
+use verifiable_memory_example::{transport, DatabaseService, ModelRegistry, RootManager};
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
 async fn test_restart_warm_start() -> Result<(), Box<dyn std::error::Error>> {
@@ -19,8 +21,10 @@ async fn test_restart_warm_start() -> Result<(), Box<dyn std::error::Error>> {
         env::set_var("BATCH_COMMIT_SIZE", "3");
     }
 
-    // Ensure Solana root account exists.
-    solana::initialize().await?;
+    common::init_anchor().await?;
+
+    // Both "processes" share this test's pool, so server A's advisory lock would outlive it.
+    env::set_var("ALLOW_MULTI_INSTANCE", "true");
 
     // Use two different ports for A/B to avoid port reuse races during "restart".
     let base_url_a = "http://127.0.0.1:3001";
@@ -37,11 +41,11 @@ async fn test_restart_warm_start() -> Result<(), Box<dyn std::error::Error>> {
     let registry_a = Arc::new(RwLock::new(ModelRegistry::new()));
     let db_a = DatabaseService::new().await?;
     let pool = db_a.pool().clone();
-    let state_a = transport::http::AppState {
-        db_service: Arc::new(Mutex::new(db_a)),
-        model_registry: registry_a,
-        root_manager: root_manager_a.clone(),
-    };
+    let state_a = transport::http::AppState::new(
+        Arc::new(Mutex::new(db_a)),
+        registry_a,
+        root_manager_a.clone(),
+    );
     let router_a = transport::http::create_router(state_a);
     let listener_a = tokio::net::TcpListener::bind("127.0.0.1:3001").await?;
     let server_a = tokio::spawn(async move {
@@ -58,7 +62,7 @@ async fn test_restart_warm_start() -> Result<(), Box<dyn std::error::Error>> {
 
     // Bootstrap (register schema).
     let bootstrap = client
-        .post(&format!("{}/bootstrap/apply-schema", base_url_a))
+        .post(format!("{}/bootstrap/apply-schema", base_url_a))
         .json(&json!({
             "force_reset": true,
             "tables": [
@@ -81,7 +85,7 @@ async fn test_restart_warm_start() -> Result<(), Box<dyn std::error::Error>> {
 
     // Write one row to make sure registry + DB are used.
     let create_1 = client
-        .post(&format!("{}/api/models/agents/create-batch", base_url_a))
+        .post(format!("{}/api/models/agents/create-batch", base_url_a))
         .json(&json!({
             "records": [
                 {"name":"warm_start_seed","character":{"role":"seed"}}
@@ -114,11 +118,11 @@ async fn test_restart_warm_start() -> Result<(), Box<dyn std::error::Error>> {
 
     let registry_b = Arc::new(RwLock::new(reg_from_db));
     let db_b = DatabaseService::new().await?;
-    let state_b = transport::http::AppState {
-        db_service: Arc::new(Mutex::new(db_b)),
-        model_registry: registry_b,
-        root_manager: root_manager_b.clone(),
-    };
+    let state_b = transport::http::AppState::new(
+        Arc::new(Mutex::new(db_b)),
+        registry_b,
+        root_manager_b.clone(),
+    );
     let router_b = transport::http::create_router(state_b);
 
     let listener_b = tokio::net::TcpListener::bind("127.0.0.1:3002").await?;
@@ -141,7 +145,7 @@ async fn test_restart_warm_start() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create WITHOUT calling bootstrap again.
     let create_2 = client
-        .post(&format!("{}/api/models/agents/create-batch", base_url_b))
+        .post(format!("{}/api/models/agents/create-batch", base_url_b))
         .json(&json!({
             "records": [
                 {"name":"after_restart","character":{"role":"ok"}}
@@ -156,7 +160,7 @@ async fn test_restart_warm_start() -> Result<(), Box<dyn std::error::Error>> {
     // Read the inserted id and ensure verification passes.
     let id = create_2["data"]["ids"][0].as_str().unwrap().to_string();
     let read = client
-        .post(&format!("{}/api/models/agents/read-batch", base_url_b))
+        .post(format!("{}/api/models/agents/read-batch", base_url_b))
         .json(&json!({ "ids": [id] }))
         .send()
         .await?
@@ -0,0 +1,76 @@
+//! Streaming root test (no database needed):
+//! 1) `RootBuilder` fed leaves in tree order yields the same root as `compute_root`, for an empty
+//!    set, a single leaf, keys that only differ in their lowest or highest bits, zero values and
+//!    a large random set.
+//! 2) Leaves out of tree order are refused.
+
+use primitive_types::H256;
+use rand::{Rng, RngCore};
+use verifiable_memory_example::storage::smt::{compute_root, h256_to_smt, RootBuilder};
+
+fn streamed_root(leaves: &[(H256, H256)]) -> H256 {
+    let mut sorted = leaves.to_vec();
+    sorted.sort_by_key(|(k, _)| h256_to_smt(*k));
+    let mut builder = RootBuilder::default();
+    for (k, v) in sorted {
+        builder.push(k, v).expect("leaves in tree order");
+    }
+    builder.root()
+}
+
+fn key(bytes: &[(usize, u8)]) -> H256 {
+    let mut k = [0u8; 32];
+    for (i, b) in bytes {
+        k[*i] = *b;
+    }
+    H256::from(k)
+}
+
+#[test]
+fn test_root_builder() {
+    let random = || {
+        let mut b = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut b);
+        H256::from(b)
+    };
+
+    let cases: Vec<Vec<(H256, H256)>> = vec![
+        vec![],
+        vec![(random(), random())],
+        // Siblings at height 0, and keys forking only at the top bit.
+        vec![(key(&[(0, 0)]), random()), (key(&[(0, 1)]), random())],
+        vec![(key(&[(31, 0x80)]), random()), (key(&[]), random()), (key(&[(31, 0x7f)]), random())],
+        // A zero value is an absent leaf.
+        vec![(random(), random()), (random(), H256::zero()), (random(), random())],
+        (0..500).map(|_| (random(), random())).collect(),
+    ];
+    for leaves in cases {
+        assert_eq!(
+            streamed_root(&leaves),
+            compute_root(leaves.iter().copied()).unwrap(),
+            "{} leaves",
+            leaves.len()
+        );
+    }
+
+    // Prefix-sharing clusters exercise merges across many levels at once.
+    let mut rng = rand::thread_rng();
+    let mut clustered = Vec::new();
+    for _ in 0..50 {
+        let base = random();
+        for _ in 0..rng.gen_range(1..6) {
+            let mut k = base.to_fixed_bytes();
+            k[0] = rng.gen();
+            clustered.push((H256::from(k), random()));
+        }
+    }
+    clustered.sort_by_key(|(k, _)| h256_to_smt(*k));
+    clustered.dedup_by_key(|(k, _)| *k);
+    assert_eq!(streamed_root(&clustered), compute_root(clustered.iter().copied()).unwrap());
+
+    // --- Out of order ---
+    let mut builder = RootBuilder::default();
+    builder.push(key(&[(31, 2)]), random()).unwrap();
+    assert!(builder.push(key(&[(31, 1)]), random()).is_err());
+    assert!(builder.push(key(&[(31, 2)]), random()).is_err());
+}
@@ -2,6 +2,8 @@
 //!
 //! This is the integration-test replacement for the former `src/main.rs` simulation binary.
 
+mod common;
+
 use chrono::Utc;
 use serde_json::json;
 use sqlx::Row;
@@ -9,7 +11,7 @@ use std::env;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::{Mutex, RwLock};
-use verifiable_memory_example::{solana, transport, DatabaseService, ModelRegistry, RootManager};
+use verifiable_memory_example::{transport, DatabaseService, ModelRegistry, RootManager};
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_schema_update() -> Result<(), Box<dyn std::error::Error>> {
@@ -22,8 +24,7 @@ async fn test_schema_update() -> Result<(), Box<dyn std::error::Error>> {
 
     println!("--- test_schema_update ---");
 
-    // Ensure Solana root account exists.
-    solana::initialize().await?;
+    common::init_anchor().await?;
 
     // Start RootManager + background batching.
     let root_manager = Arc::new(RootManager::new().await?);
@@ -32,11 +33,11 @@ async fn test_schema_update() -> Result<(), Box<dyn std::error::Error>> {
     // Start API in-process (router) for the test.
     let model_registry = Arc::new(RwLock::new(ModelRegistry::new()));
     let db_service_arc = Arc::new(Mutex::new(DatabaseService::new().await?));
-    let app_state = transport::http::AppState {
-        db_service: db_service_arc.clone(),
+    let app_state = transport::http::AppState::new(
+        db_service_arc.clone(),
         model_registry,
-        root_manager: root_manager.clone(),
-    };
+        root_manager.clone(),
+    );
     let router = transport::http::create_router(app_state);
 
     // Bind to an ephemeral port to avoid conflicts if an API server is already running.
@@ -52,7 +53,7 @@ async fn test_schema_update() -> Result<(), Box<dyn std::error::Error>> {
     // --- BOOTSTRAP: apply schema spec (single-tenant) ---
     let force_reset = env::var("CLEAR_DB").unwrap_or_else(|_| "true".to_string()) == "true";
     let bootstrap_resp = client
-        .post(&format!("{}/bootstrap/apply-schema", base_url))
+        .post(format!("{}/bootstrap/apply-schema", base_url))
         .json(&json!({
             "force_reset": force_reset,
             "tables": [
@@ -110,7 +111,7 @@ async fn test_schema_update() -> Result<(), Box<dyn std::error::Error>> {
         records: Vec<serde_json::Value>,
     ) -> Result<(bool, Vec<String>), Box<dyn std::error::Error>> {
         let resp = client
-        .post(&format!("{}/api/models/{}/create-batch", base_url, model))
+        .post(format!("{}/api/models/{}/create-batch", base_url, model))
             .json(&json!({ "records": records }))
             .send()
             .await?
@@ -142,7 +143,7 @@ async fn test_schema_update() -> Result<(), Box<dyn std::error::Error>> {
         ids: Vec<String>,
     ) -> Result<bool, Box<dyn std::error::Error>> {
         let resp = client
-        .post(&format!("{}/api/models/{}/read-batch", base_url, model))
+        .post(format!("{}/api/models/{}/read-batch", base_url, model))
             .json(&json!({ "ids": ids }))
             .send()
             .await?
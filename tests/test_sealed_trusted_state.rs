@@ -0,0 +1,137 @@
+//! Sealed trusted state test (no Solana needed):
+//! 1) The trusted state file is written with a MAC and an update counter.
+//! 2) A tampered file, or an unsealed file that differs from the anchor, is refused at startup.
+//! 3) An older (validly sealed) copy of the file is refused once the anchor history shows
+//!    later updates were already anchored, while the current file is accepted.
+//! 4) Against an anchor that counts its writes (the transparency log), an older copy is refused
+//!    at startup without any history, because the anchor is ahead of the sealed write count.
+
+use primitive_types::H256;
+use std::env;
+use std::fs;
+use std::sync::Arc;
+use verifiable_memory_example::infra::anchor::{MemoryAnchor, RootAnchor, TransparencyLogAnchor};
+use verifiable_memory_example::storage::anchor_history::AnchorHistory;
+use verifiable_memory_example::{DatabaseService, RootManager};
+
+const STATE_FILE: &str = "trusted_state.json";
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_sealed_trusted_state() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    env::set_var("BATCH_COMMIT_SIZE", "1000");
+    env::set_var("COMMIT_MAX_AGE_SECS", "0");
+    env::set_var("ALLOW_MULTI_INSTANCE", "true");
+    env::set_var("SEALING_KEY_PATH", env::temp_dir().join("vm_test_sealing.key"));
+
+    let db = DatabaseService::new().await?;
+    let pool = db.pool().clone();
+    sqlx::query("TRUNCATE TABLE anchor_history CASCADE").execute(&pool).await?;
+
+    // --- Fresh start: write two updates, anchor them ---
+    env::set_var("CLEAR_DB", "true");
+    let anchor = Arc::new(MemoryAnchor::new());
+    let root_manager = RootManager::with_anchor(anchor.clone()).await?;
+    root_manager.attach_history(AnchorHistory::new(pool.clone())).await?;
+
+    let (r1, r2) = (H256::repeat_byte(0x01), H256::repeat_byte(0x02));
+    {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.update_temporary_root(r1).await;
+    }
+    let stale = fs::read_to_string(STATE_FILE)?;
+    let state: serde_json::Value = serde_json::from_str(&stale)?;
+    assert!(state["mac"].is_string(), "trusted state must be sealed: {}", state);
+
+    {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.update_temporary_root(r2).await;
+    }
+    assert!(root_manager.commit_now().await?);
+    let current = fs::read_to_string(STATE_FILE)?;
+    root_manager.shutdown();
+    drop(root_manager);
+
+    env::set_var("CLEAR_DB", "false");
+
+    // --- Tampered root: MAC fails ---
+    let mut tampered: serde_json::Value = serde_json::from_str(&current)?;
+    tampered["root"] = serde_json::json!(hex::encode(H256::repeat_byte(0x03).as_bytes()));
+    fs::write(STATE_FILE, tampered.to_string())?;
+    assert!(RootManager::with_anchor(anchor.clone()).await.is_err(), "tampered file accepted");
+
+    // --- Unsealed file ahead of the anchor: refused ---
+    fs::write(
+        STATE_FILE,
+        serde_json::json!({ "root": hex::encode(r1.as_bytes()), "timestamp": 0 }).to_string(),
+    )?;
+    assert!(RootManager::with_anchor(anchor.clone()).await.is_err(), "unsealed file accepted");
+
+    // --- Unsealed file equal to the anchor: accepted and sealed ---
+    fs::write(
+        STATE_FILE,
+        serde_json::json!({ "root": hex::encode(r2.as_bytes()), "timestamp": 0, "seq": 2 })
+            .to_string(),
+    )?;
+    let legacy = RootManager::with_anchor(anchor.clone()).await?;
+    assert_eq!(legacy.get_temporary_root().await, r2);
+    let resealed: serde_json::Value = serde_json::from_str(&fs::read_to_string(STATE_FILE)?)?;
+    assert!(resealed["mac"].is_string());
+    drop(legacy);
+
+    // --- Rolled back (older, validly sealed) copy: refused once history is attached ---
+    fs::write(STATE_FILE, &stale)?;
+    let rolled_back = RootManager::with_anchor(anchor.clone()).await?;
+    assert_eq!(rolled_back.get_temporary_root().await, r1);
+    let err = rolled_back
+        .attach_history(AnchorHistory::new(pool.clone()))
+        .await
+        .expect_err("stale trusted state accepted");
+    assert!(err.to_string().contains("stale"), "{}", err);
+    drop(rolled_back);
+
+    // --- Current copy: accepted ---
+    fs::write(STATE_FILE, &current)?;
+    let restarted = RootManager::with_anchor(anchor.clone()).await?;
+    restarted.attach_history(AnchorHistory::new(pool.clone())).await?;
+    assert_eq!(restarted.get_temporary_root().await, r2);
+    assert_eq!(restarted.get_main_root().await, r2);
+    restarted.shutdown();
+    drop(restarted);
+
+    // --- Anchor ahead of the sealed write count: refused at startup, no history needed ---
+    let log_path = env::temp_dir().join("vm_test_sealed_state.log");
+    let log = Arc::new(TransparencyLogAnchor::new(&log_path, [9u8; 32]));
+    let _ = fs::remove_file(&log_path);
+    let _ = fs::remove_file(log.head_path());
+    log.initialize().await?;
+    env::set_var("CLEAR_DB", "true");
+    let root_manager = RootManager::with_anchor(log.clone()).await?;
+    env::set_var("CLEAR_DB", "false");
+    {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.update_temporary_root(r1).await;
+    }
+    let stale = fs::read_to_string(STATE_FILE)?;
+    {
+        let _root_guard = root_manager.lock_root().await;
+        root_manager.update_temporary_root(r2).await;
+    }
+    assert!(root_manager.commit_now().await?);
+    let current = fs::read_to_string(STATE_FILE)?;
+    root_manager.shutdown();
+    drop(root_manager);
+
+    fs::write(STATE_FILE, &stale)?;
+    let err = RootManager::with_anchor(log.clone()).await.err().expect("stale trusted state accepted");
+    assert!(err.to_string().contains("stale"), "{}", err);
+
+    fs::write(STATE_FILE, &current)?;
+    let restarted = RootManager::with_anchor(log.clone()).await?;
+    assert_eq!(restarted.get_main_root().await, r2);
+    drop(restarted);
+    fs::remove_file(&log_path)?;
+    fs::remove_file(log.head_path())?;
+
+    Ok(())
+}
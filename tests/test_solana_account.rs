@@ -0,0 +1,136 @@
+//! On-chain account layout test (no Solana needed):
+//! 1) `MerkleRootAccount::decode` reads root, timestamp, sequence, authority and pending
+//!    authority from the Anchor layout (discriminator = sha256("account:MerkleRootAccount")[..8]).
+//! 2) Accounts of another type, accounts written by an older program version and corrupt
+//!    `Option` tags are refused.
+//! 3) `decode_owned` refuses accounts not owned by the program, whatever their bytes.
+//! 4) `ensure_authority` accepts only the account's authority and points a pending authority
+//!    at `--accept-authority`.
+//! 5) `RootHistory::decode` returns the ring buffer oldest first, before and after it wraps,
+//!    and `find` locates an anchored root in it.
+
+use sha2::{Digest, Sha256};
+use primitive_types::H256;
+use solana_program::pubkey::Pubkey;
+use verifiable_memory_example::infra::solana::{MerkleRootAccount, RootHistory, ROOT_HISTORY_LEN};
+
+fn encode(
+    root: [u8; 32],
+    timestamp: i64,
+    sequence: u64,
+    authority: &Pubkey,
+    pending: Option<&Pubkey>,
+) -> Vec<u8> {
+    let mut data = Sha256::digest(b"account:MerkleRootAccount")[..8].to_vec();
+    data.extend_from_slice(&root);
+    data.extend_from_slice(&timestamp.to_le_bytes());
+    data.extend_from_slice(&sequence.to_le_bytes());
+    data.extend_from_slice(authority.as_ref());
+    match pending {
+        Some(pending) => {
+            data.push(1);
+            data.extend_from_slice(pending.as_ref());
+        }
+        // The account is allocated at full size, so `None` leaves zeroed bytes behind it.
+        None => data.extend_from_slice(&[0u8; 33]),
+    }
+    data
+}
+
+/// A `root_history` account after `pushes` updates (sequence `i`, root `[i; 32]`, time `1000 + i`).
+fn encode_history(pushes: u64) -> Vec<u8> {
+    let len = ROOT_HISTORY_LEN as u64;
+    let mut slots = vec![[0u8; 48]; ROOT_HISTORY_LEN];
+    for i in 0..pushes {
+        let slot = &mut slots[(i % len) as usize];
+        slot[..8].copy_from_slice(&i.to_le_bytes());
+        slot[8..40].copy_from_slice(&[i as u8; 32]);
+        slot[40..].copy_from_slice(&(1000 + i as i64).to_le_bytes());
+    }
+    let mut data = Sha256::digest(b"account:RootHistory")[..8].to_vec();
+    data.extend_from_slice(&(pushes % len).to_le_bytes());
+    data.extend_from_slice(&pushes.min(len).to_le_bytes());
+    for slot in slots {
+        data.extend_from_slice(&slot);
+    }
+    data
+}
+
+#[test]
+fn test_solana_account_layout() -> Result<(), Box<dyn std::error::Error>> {
+    let authority = Pubkey::new_unique();
+    let pending = Pubkey::new_unique();
+
+    // --- Decoding ---
+    let data = encode([0x11; 32], 1_700_000_000, 42, &authority, None);
+    assert_eq!(data.len(), MerkleRootAccount::SPACE);
+    let account = MerkleRootAccount::decode(&data)?;
+    assert_eq!(account.root, [0x11; 32]);
+    assert_eq!(account.timestamp, 1_700_000_000);
+    assert_eq!(account.sequence, 42);
+    assert_eq!(account.authority, authority);
+    assert_eq!(account.pending_authority, None);
+
+    let account = MerkleRootAccount::decode(&encode([0x22; 32], -1, 0, &authority, Some(&pending)))?;
+    assert_eq!(account.timestamp, -1);
+    assert_eq!(account.pending_authority, Some(pending));
+
+    // --- Refused layouts ---
+    let mut other_type = data.clone();
+    other_type[0] ^= 0xff;
+    assert!(MerkleRootAccount::decode(&other_type).is_err());
+
+    // The original layout: discriminator, root, timestamp.
+    let legacy = &data[..8 + 32 + 8];
+    let err = MerkleRootAccount::decode(legacy).unwrap_err();
+    assert!(err.to_string().contains("older program version"), "{}", err);
+
+    let mut bad_tag = data.clone();
+    bad_tag[88] = 2;
+    assert!(MerkleRootAccount::decode(&bad_tag).is_err());
+
+    // --- Owner check ---
+    let program_id = Pubkey::new_unique();
+    assert_eq!(MerkleRootAccount::decode_owned(&data, &program_id, &program_id)?.sequence, 42);
+    let err = MerkleRootAccount::decode_owned(&data, &Pubkey::new_unique(), &program_id).unwrap_err();
+    assert!(err.to_string().contains("SOLANA_PROGRAM_ID"), "{}", err);
+
+    // --- Authority check (what `initialize` applies to an existing account) ---
+    let account = MerkleRootAccount::decode(&encode([0x22; 32], 0, 0, &authority, Some(&pending)))?;
+    account.ensure_authority(&authority)?;
+    let err = account.ensure_authority(&Pubkey::new_unique()).unwrap_err();
+    assert!(err.to_string().contains(&authority.to_string()), "{}", err);
+    let err = account.ensure_authority(&pending).unwrap_err();
+    assert!(err.to_string().contains("--accept-authority"), "{}", err);
+
+    // --- Root history ring buffer ---
+    let fresh = encode_history(3);
+    assert_eq!(fresh.len(), RootHistory::SPACE);
+    let history = RootHistory::decode(&fresh)?;
+    let sequences: Vec<u64> = history.entries.iter().map(|e| e.sequence).collect();
+    assert_eq!(sequences, [0, 1, 2]);
+    assert_eq!(history.entries[2].timestamp, 1002);
+    assert_eq!(history.find(H256::repeat_byte(1)).map(|e| e.sequence), Some(1));
+    assert!(history.find(H256::repeat_byte(9)).is_none());
+
+    // Wrapped: the oldest entries were overwritten.
+    let pushes = ROOT_HISTORY_LEN as u64 + 10;
+    let history = RootHistory::decode(&encode_history(pushes))?;
+    assert_eq!(history.entries.len(), ROOT_HISTORY_LEN);
+    assert_eq!(history.oldest_sequence(), Some(10));
+    assert_eq!(history.entries.last().map(|e| e.sequence), Some(pushes - 1));
+    assert!(history.entries.windows(2).all(|w| w[0].sequence + 1 == w[1].sequence));
+    assert!(history.find(H256::repeat_byte(5)).is_none(), "rotated out");
+    assert!(history.find(H256::repeat_byte(10)).is_some());
+
+    let mut not_history = fresh.clone();
+    not_history[..8].copy_from_slice(&data[..8]);
+    assert!(RootHistory::decode(&not_history).is_err());
+    let mut bad_head = fresh.clone();
+    bad_head[8..16].copy_from_slice(&(ROOT_HISTORY_LEN as u64).to_le_bytes());
+    assert!(RootHistory::decode(&bad_head).is_err());
+    assert!(RootHistory::decode(&fresh[..100]).is_err());
+    assert!(RootHistory::decode_owned(&fresh, &authority, &program_id).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,152 @@
+//! Solana signer test (no Solana needed):
+//! 1) `SOLANA_SIGNER=env` loads a base58 keypair and signs transactions that verify.
+//! 2) `SOLANA_SIGNER=remote` gets the pubkey and signatures from a process on a Unix socket.
+//! 3) A remote signature for another key, a remote refusal, a remote signer that never
+//!    answers (`SOLANA_SIGNER_TIMEOUT_SECS`), a malformed secret and an unknown signer kind are
+//!    all rejected.
+
+use base64::Engine;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::hash::Hash;
+use solana_sdk::signer::{keypair::Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use std::env;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixListener;
+use verifiable_memory_example::infra::solana::signer;
+
+fn transaction_for(payer: &Pubkey) -> Transaction {
+    let instruction = Instruction {
+        program_id: Pubkey::new_unique(),
+        accounts: vec![AccountMeta::new_readonly(*payer, true)],
+        data: vec![1, 2, 3],
+    };
+    Transaction::new_with_payer(&[instruction], Some(payer))
+}
+
+/// Serves the remote signer protocol with `signing_key`, advertising `advertised` as its pubkey.
+/// Refuses to sign messages once `refuse` is set.
+fn spawn_remote_signer(
+    path: &std::path::Path,
+    signing_key: Keypair,
+    advertised: Pubkey,
+    refuse: Arc<std::sync::atomic::AtomicBool>,
+) -> tokio::task::JoinHandle<()> {
+    let listener = UnixListener::bind(path).expect("bind signer socket");
+    tokio::spawn(async move {
+        loop {
+            let Ok((stream, _)) = listener.accept().await else { return };
+            let (read, mut write) = stream.into_split();
+            let mut line = String::new();
+            BufReader::new(read).read_line(&mut line).await.unwrap();
+            let request: serde_json::Value = serde_json::from_str(&line).unwrap();
+            let reply = match request["method"].as_str() {
+                Some("pubkey") => serde_json::json!({ "pubkey": advertised.to_string() }),
+                Some("sign") if refuse.load(std::sync::atomic::Ordering::SeqCst) => {
+                    serde_json::json!({ "error": "policy denies signing" })
+                }
+                Some("sign") => {
+                    let message = base64::engine::general_purpose::STANDARD
+                        .decode(request["message"].as_str().unwrap())
+                        .unwrap();
+                    serde_json::json!({ "signature": signing_key.sign_message(&message).to_string() })
+                }
+                _ => serde_json::json!({ "error": "unknown method" }),
+            };
+            write.write_all(format!("{}\n", reply).as_bytes()).await.unwrap();
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_solana_signer() -> Result<(), Box<dyn std::error::Error>> {
+    let blockhash = Hash::new_unique();
+
+    // --- env ---
+    let keypair = Keypair::new();
+    env::set_var("SOLANA_SIGNER", "env");
+    env::set_var("SOLANA_SIGNER_SECRET", keypair.to_base58_string());
+    let env_signer = signer::from_config().await?;
+    assert_eq!(env_signer.name(), "env");
+    assert_eq!(env_signer.pubkey(), keypair.pubkey());
+    let mut tx = transaction_for(&keypair.pubkey());
+    signer::sign_transaction(env_signer.as_ref(), &mut tx, blockhash).await?;
+    assert_eq!(tx.message.recent_blockhash, blockhash);
+    tx.verify()?;
+
+    // Only single-signer transactions for this key are signed.
+    let mut foreign = transaction_for(&Pubkey::new_unique());
+    assert!(signer::sign_transaction(env_signer.as_ref(), &mut foreign, blockhash).await.is_err());
+
+    env::set_var("SOLANA_SIGNER_SECRET", "not-base58-0OIl");
+    assert!(signer::from_config().await.is_err());
+
+    // --- remote ---
+    let dir = env::temp_dir().join(format!("vm-signer-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let socket = dir.join("signer.sock");
+    let _ = std::fs::remove_file(&socket);
+    let remote_key = Keypair::new();
+    let remote_pubkey = remote_key.pubkey();
+    let refuse = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let server = spawn_remote_signer(&socket, remote_key, remote_pubkey, refuse.clone());
+
+    env::set_var("SOLANA_SIGNER", "remote");
+    env::set_var("SOLANA_SIGNER_SOCKET", &socket);
+    let remote = signer::from_config().await?;
+    assert_eq!(remote.name(), "remote");
+    assert_eq!(remote.pubkey(), remote_pubkey);
+    let mut tx = transaction_for(&remote_pubkey);
+    signer::sign_transaction(remote.as_ref(), &mut tx, blockhash).await?;
+    tx.verify()?;
+
+    refuse.store(true, std::sync::atomic::Ordering::SeqCst);
+    let err = signer::sign_transaction(remote.as_ref(), &mut transaction_for(&remote_pubkey), blockhash)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("policy denies signing"), "{}", err);
+    server.abort();
+
+    // A signer that advertises one key but signs with another is caught before sending.
+    let socket = dir.join("lying.sock");
+    let _ = std::fs::remove_file(&socket);
+    let advertised = Pubkey::new_unique();
+    let server = spawn_remote_signer(&socket, Keypair::new(), advertised, Arc::default());
+    env::set_var("SOLANA_SIGNER_SOCKET", &socket);
+    let lying = signer::from_config().await?;
+    let err = signer::sign_transaction(lying.as_ref(), &mut transaction_for(&advertised), blockhash)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("does not verify"), "{}", err);
+    server.abort();
+
+    // A signer that accepts connections but never answers times out.
+    let socket = dir.join("silent.sock");
+    let _ = std::fs::remove_file(&socket);
+    let listener = UnixListener::bind(&socket)?;
+    let server = tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            held.push(stream);
+        }
+    });
+    env::set_var("SOLANA_SIGNER_SOCKET", &socket);
+    env::set_var("SOLANA_SIGNER_TIMEOUT_SECS", "1");
+    let started = std::time::Instant::now();
+    let err = signer::from_config().await.err().expect("silent signer accepted");
+    assert!(err.to_string().contains("did not answer"), "{}", err);
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    env::remove_var("SOLANA_SIGNER_TIMEOUT_SECS");
+    server.abort();
+
+    // --- config errors ---
+    env::set_var("SOLANA_SIGNER_SOCKET", dir.join("missing.sock"));
+    assert!(signer::from_config().await.is_err());
+    env::set_var("SOLANA_SIGNER", "hsm");
+    assert!(signer::from_config().await.is_err());
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,72 @@
+//! Solana transaction settings test (no Solana needed):
+//! 1) `SOLANA_PRIORITY_FEE` and `SOLANA_COMMITMENT` parse, and bad values are refused.
+//! 2) The `auto` fee is the 75th percentile of recent fees, capped at the maximum.
+//! 3) Compute budget instructions are only added for a limit or a non-zero price.
+//! 4) Explorer links follow the cluster, explicit or derived from the RPC URL.
+
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_sdk::signature::Signature;
+use std::env;
+use verifiable_memory_example::infra::solana::transaction::{
+    compute_budget_instructions, estimate_priority_fee, parse_commitment, Cluster, PriorityFee, TxOptions,
+};
+
+#[test]
+fn test_solana_transaction_settings() -> Result<(), Box<dyn std::error::Error>> {
+    // --- Parsing ---
+    assert_eq!(PriorityFee::parse("0", 10)?, PriorityFee::None);
+    assert_eq!(PriorityFee::parse(" none ", 10)?, PriorityFee::None);
+    assert_eq!(PriorityFee::parse("5000", 10)?, PriorityFee::Fixed(5000));
+    assert_eq!(PriorityFee::parse("AUTO", 10)?, PriorityFee::Auto { max: 10 });
+    assert!(PriorityFee::parse("cheap", 10).is_err());
+
+    assert_eq!(parse_commitment("finalized")?, CommitmentConfig::finalized());
+    assert_eq!(parse_commitment("Processed")?.commitment, CommitmentLevel::Processed);
+    assert!(parse_commitment("max").is_err());
+
+    env::set_var("SOLANA_PRIORITY_FEE", "auto");
+    env::set_var("SOLANA_PRIORITY_FEE_MAX", "777");
+    env::set_var("SOLANA_COMPUTE_UNIT_LIMIT", "40000");
+    env::set_var("SOLANA_COMMITMENT", "finalized");
+    env::set_var("SOLANA_TX_RESUBMITS", "5");
+    let options = TxOptions::from_config("https://api.mainnet-beta.solana.com")?;
+    assert_eq!(options.priority_fee, PriorityFee::Auto { max: 777 });
+    assert_eq!(options.compute_unit_limit, Some(40_000));
+    assert_eq!(options.commitment, CommitmentConfig::finalized());
+    assert_eq!(options.resubmits, 5);
+    assert_eq!(options.cluster, Cluster::MainnetBeta);
+    env::set_var("SOLANA_COMMITMENT", "eventually");
+    assert!(TxOptions::from_config("http://localhost:8899").is_err());
+
+    // --- Fee estimate ---
+    assert_eq!(estimate_priority_fee(&[], 1_000), 0);
+    assert_eq!(estimate_priority_fee(&[0, 0, 0, 0], 1_000), 0, "quiet cluster");
+    assert_eq!(estimate_priority_fee(&[40, 10, 30, 20], 1_000), 30);
+    assert_eq!(estimate_priority_fee(&[7], 1_000), 7);
+    assert_eq!(estimate_priority_fee(&[500, 90_000, 90_000, 90_000], 1_000), 1_000, "capped");
+
+    // --- Compute budget ---
+    assert!(compute_budget_instructions(None, 0).is_empty());
+    let instructions = compute_budget_instructions(Some(40_000), 2_500);
+    assert_eq!(instructions.len(), 2);
+    assert!(instructions.iter().all(|i| i.program_id == solana_compute_budget_interface::ID));
+    assert_eq!(compute_budget_instructions(None, 2_500).len(), 1);
+
+    // --- Explorer links ---
+    let signature = Signature::default();
+    let devnet = Cluster::detect(None, "https://api.devnet.solana.com");
+    assert_eq!(devnet, Cluster::Devnet);
+    assert!(devnet.explorer_tx_url(&signature).ends_with("?cluster=devnet"));
+    let mainnet = Cluster::detect(Some("mainnet-beta"), "https://rpc.example.com");
+    assert_eq!(
+        mainnet.explorer_tx_url(&signature),
+        format!("https://explorer.solana.com/tx/{}", signature)
+    );
+    let local = Cluster::detect(None, "http://127.0.0.1:8899");
+    assert_eq!(local, Cluster::Custom("http://127.0.0.1:8899".to_string()));
+    assert!(local
+        .explorer_tx_url(&signature)
+        .ends_with("?cluster=custom&customUrl=http%3A%2F%2F127.0.0.1%3A8899"));
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! Stale anchor test (no Solana needed):
+//! 1) A second instance whose main_root is behind the anchor cannot roll it back: its commit
+//!    fails with a typed `StaleAnchor` and the anchored root is unchanged.
+//! 2) A write that landed but reported failure is recognised on retry: the stale write re-reads
+//!    the anchor, finds its own root and completes the commit.
+//! 3) A multi-target anchor tolerates a lagging target but still refuses a root moved on all,
+//!    and sends each target a conditional write: a target another writer moves just before
+//!    the write is not overwritten.
+//! 4) Resets are conditional too: only the forced (operator) reset overwrites a moved anchor.
+
+mod common;
+
+use common::TestAnchor;
+use primitive_types::H256;
+use std::env;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use verifiable_memory_example::domain::commitment::AnchorState;
+use verifiable_memory_example::infra::anchor::{
+    AnchorTarget, MemoryAnchor, MultiAnchor, QuorumPolicy, RootAnchor, StaleAnchor,
+};
+use verifiable_memory_example::RootManager;
+
+async fn update(root_manager: &RootManager, root: H256) {
+    let _root_guard = root_manager.lock_root().await;
+    root_manager.update_temporary_root(root).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stale_anchor() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    env::set_var("BATCH_COMMIT_SIZE", "1000");
+    env::set_var("COMMIT_MAX_AGE_SECS", "0");
+    env::set_var("SEALING_KEY_PATH", env::temp_dir().join("vm_test_sealing.key"));
+    env::set_var("CLEAR_DB", "true");
+
+    // --- A stale instance cannot roll the anchor back ---
+    let anchor = Arc::new(MemoryAnchor::new());
+    let current = RootManager::with_anchor(anchor.clone()).await?;
+    let stale = RootManager::with_anchor(anchor.clone()).await?;
+
+    let r1 = H256::repeat_byte(0x41);
+    update(&current, r1).await;
+    assert!(current.commit_now().await?);
+
+    update(&stale, H256::repeat_byte(0x42)).await;
+    let err = stale.commit_now().await.unwrap_err();
+    let typed = err.downcast_ref::<StaleAnchor>().expect("typed stale anchor error");
+    assert_eq!(typed.expected, H256::zero());
+    assert_eq!(typed.actual, Some(r1));
+    assert_eq!(anchor.read_root().await?, r1, "stale instance overwrote the anchor");
+    assert_eq!(stale.get_main_root().await, H256::zero());
+    assert!(stale
+        .anchor_progress()
+        .last_error
+        .unwrap_or_default()
+        .contains("Stale anchor"));
+
+    // The instance that owns the anchor keeps committing.
+    let r3 = H256::repeat_byte(0x43);
+    update(&current, r3).await;
+    assert!(current.commit_now().await?);
+    assert_eq!(anchor.read_root().await?, r3);
+
+    // --- A write that landed without an acknowledgement completes on retry ---
+    let unreliable = Arc::new(TestAnchor::new());
+    let root_manager = RootManager::with_anchor(unreliable.clone()).await?;
+    let r4 = H256::repeat_byte(0x44);
+    update(&root_manager, r4).await;
+    unreliable.lose_ack.store(true, Ordering::SeqCst);
+    assert!(root_manager.commit_now().await.is_err());
+    assert_eq!(unreliable.read_root().await?, r4, "the write landed");
+    assert_eq!(root_manager.get_main_root().await, H256::zero());
+
+    assert!(root_manager.commit_now().await?);
+    assert_eq!(root_manager.get_main_root().await, r4);
+    assert_eq!(root_manager.anchor_health().await.state, AnchorState::Healthy);
+    assert_eq!(root_manager.commit_status().await.pending_updates, 0);
+
+    // --- Multi-target: a lagging target is caught up, a moved quorum is stale ---
+    let memory = Arc::new(MemoryAnchor::new());
+    let lagging = Arc::new(TestAnchor::new());
+    let multi = Arc::new(MultiAnchor::new(
+        vec![
+            AnchorTarget::new("memory", memory.clone()),
+            AnchorTarget::new("lagging", lagging.clone()),
+        ],
+        QuorumPolicy::All,
+    )?);
+    let root_manager = RootManager::with_anchor(multi.clone()).await?;
+
+    let r5 = H256::repeat_byte(0x45);
+    update(&root_manager, r5).await;
+    lagging.failing.store(true, Ordering::SeqCst);
+    assert!(root_manager.commit_now().await.is_err());
+    assert_eq!(memory.read_root().await?, r5);
+    assert_eq!(lagging.read_root().await?, H256::zero());
+
+    lagging.failing.store(false, Ordering::SeqCst);
+    assert!(root_manager.commit_now().await?, "targets that disagree are not stale");
+    assert_eq!(lagging.read_root().await?, r5);
+
+    let moved = H256::repeat_byte(0x4f);
+    memory.write_root(moved).await?;
+    lagging.write_root(moved).await?;
+    update(&root_manager, H256::repeat_byte(0x46)).await;
+    let err = root_manager.commit_now().await.unwrap_err();
+    assert_eq!(err.downcast_ref::<StaleAnchor>().and_then(|s| s.actual), Some(moved));
+    assert_eq!(multi.read_root().await?, moved);
+
+    // A target moved by another writer between any read and the write keeps the foreign root.
+    let racing = Arc::new(TestAnchor::new());
+    let pair = MultiAnchor::new(
+        vec![
+            AnchorTarget::new("memory", Arc::new(MemoryAnchor::new())),
+            AnchorTarget::new("racing", racing.clone()),
+        ],
+        QuorumPolicy::All,
+    )?;
+    let r7 = H256::repeat_byte(0x47);
+    pair.write_root_after(H256::zero(), r7).await?;
+    let foreign = H256::repeat_byte(0x4e);
+    *racing.preempt.lock().unwrap() = Some(foreign);
+    let err = pair.write_root_after(r7, H256::repeat_byte(0x48)).await.unwrap_err();
+    let typed = err.downcast_ref::<StaleAnchor>().expect("typed stale anchor error");
+    assert_eq!((typed.expected, typed.actual), (r7, Some(foreign)));
+    assert_eq!(racing.read_root().await?, foreign, "moved target was overwritten");
+
+    // --- Resets: the conditional one refuses a moved anchor, the forced one overwrites it ---
+    {
+        let _root_guard = root_manager.lock_root().await;
+        let err = root_manager.anchor_and_reset_roots(H256::zero()).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<StaleAnchor>().and_then(|s| s.actual), Some(moved));
+        assert_eq!(multi.read_root().await?, moved, "conditional reset overwrote the anchor");
+        assert_eq!(root_manager.get_main_root().await, r5, "roots reset after a refused write");
+
+        root_manager.force_anchor_and_reset_roots(H256::zero()).await?;
+    }
+    assert_eq!(multi.read_root().await?, H256::zero());
+    assert_eq!(root_manager.get_main_root().await, H256::zero());
+
+    Ok(())
+}
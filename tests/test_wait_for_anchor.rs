@@ -0,0 +1,135 @@
+//! Per-write anchoring test:
+//! 1) A write far below the batch size reports its `seq` and is not anchored yet.
+//! 2) A long-poll on that `seq` times out unanchored.
+//! 3) A write with `wait_for_anchor` is anchored before it returns, covering the earlier write,
+//!    and both report the same anchor from the history.
+
+mod common;
+
+use serde_json::json;
+use std::env;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use verifiable_memory_example::storage::anchor_history::AnchorHistory;
+use verifiable_memory_example::{transport, DatabaseService, ModelRegistry, RootManager};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_wait_for_anchor() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+
+    // Only explicit or on-demand commits in this test.
+    env::set_var("BATCH_COMMIT_SIZE", "1000");
+    env::set_var("COMMIT_MAX_AGE_SECS", "0");
+
+    common::init_anchor().await?;
+
+    env::set_var("CLEAR_DB", "true");
+    let root_manager = Arc::new(RootManager::new().await?);
+    root_manager.clone().start_background_commit_task();
+
+    let db_service = DatabaseService::new().await?;
+    let pool = db_service.pool().clone();
+    sqlx::query("TRUNCATE TABLE anchor_history CASCADE").execute(&pool).await?;
+    root_manager.attach_history(AnchorHistory::new(pool.clone())).await?;
+
+    let app_state = transport::http::AppState::new(
+        Arc::new(Mutex::new(db_service)),
+        Arc::new(RwLock::new(ModelRegistry::new())),
+        root_manager.clone(),
+    );
+    let router = transport::http::create_router(app_state);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    let server_handle = tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let base_url = format!("http://127.0.0.1:{}", port);
+    let client = reqwest::Client::new();
+
+    let bootstrap = client
+        .post(format!("{}/bootstrap/apply-schema", base_url))
+        .json(&json!({
+            "force_reset": true,
+            "tables": [
+                {
+                    "table_name": "notes",
+                    "primary_key_field": "id",
+                    "primary_key_kind": "big_serial",
+                    "columns": [
+                        {"name":"body","col_type":"text","nullable":false,"unique":false}
+                    ]
+                }
+            ]
+        }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(bootstrap["success"].as_bool().unwrap_or(false));
+
+    // --- A plain write: sequenced, not anchored ---
+    let first = client
+        .post(format!("{}/api/models/notes/create-batch", base_url))
+        .json(&json!({ "records": [ {"body":"one"} ] }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(first["success"].as_bool().unwrap_or(false));
+    assert_eq!(first["data"]["meta"]["committed"].as_bool(), Some(false));
+    assert!(first["data"]["meta"]["anchor_status"].is_null());
+    let first_seq = first["data"]["meta"]["seq"].as_u64().expect("write seq");
+
+    let status = client
+        .get(format!("{}/api/anchors/seq/{}?wait_secs=1", base_url, first_seq))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert_eq!(status["data"]["anchored"].as_bool(), Some(false));
+    assert!(status["data"]["anchor"].is_null());
+
+    let unknown = client
+        .get(format!("{}/api/anchors/seq/{}", base_url, first_seq + 100))
+        .send()
+        .await?;
+    assert_eq!(unknown.status().as_u16(), 404);
+
+    // --- Long-poll from another task while a write asks to be anchored ---
+    let poll = {
+        let client = client.clone();
+        let url = format!("{}/api/anchors/seq/{}?wait_secs=30", base_url, first_seq);
+        tokio::spawn(async move { client.get(url).send().await?.json::<serde_json::Value>().await })
+    };
+
+    let second = client
+        .post(format!("{}/api/models/notes/create-batch", base_url))
+        .json(&json!({ "records": [ {"body":"two"} ], "wait_for_anchor": true }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    assert!(second["success"].as_bool().unwrap_or(false));
+    let meta = &second["data"]["meta"];
+    assert_eq!(meta["committed"].as_bool(), Some(true));
+    let second_seq = meta["seq"].as_u64().unwrap();
+    assert!(second_seq > first_seq);
+    assert_eq!(meta["anchor_status"]["anchored"].as_bool(), Some(true));
+    let anchor_entry = &meta["anchor_status"]["anchor"];
+    assert_eq!(
+        anchor_entry["root"].as_str(),
+        meta["proposed_root"].as_str(),
+        "the covering anchor is the write's own root"
+    );
+    assert_eq!(anchor_entry["reason"].as_str(), Some("on_demand"));
+
+    let polled = poll.await??;
+    assert_eq!(polled["data"]["anchored"].as_bool(), Some(true));
+    assert_eq!(polled["data"]["anchor"]["id"], anchor_entry["id"]);
+
+    server_handle.abort();
+    root_manager.shutdown();
+    Ok(())
+}